@@ -0,0 +1,217 @@
+//! Contains keyframed animation - a timeline of transforms that is
+//! blended between with an easing function
+
+use crate::easing;
+use crate::vertex::Interpolate;
+use crate::{Matrix, Vector3D};
+
+/// Where an object is, which way it is turned and how big it is
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    /// `translation` - Where the object is
+    pub translation: Vector3D,
+    /// `rotation` - The rotation around the x, y and z axes, in radians
+    pub rotation: Vector3D,
+    /// `scale` - How much the object is scaled along each axis
+    pub scale: Vector3D,
+}
+
+impl Transform {
+    /// Create a new transform
+    ///
+    /// # Arguments
+    /// * `translation` - Where the object is
+    /// * `rotation` - The rotation around the x, y and z axes, in radians
+    /// * `scale` - How much the object is scaled along each axis
+    ///
+    /// # Returns
+    /// * `Transform` - The new transform
+    pub fn new(translation: Vector3D, rotation: Vector3D, scale: Vector3D) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// Build the matrix that puts an object in place with this transform. The rotation is
+    /// around x, then y, then z.
+    ///
+    /// # Returns
+    /// * `Matrix` - The matrix for the transform
+    pub fn matrix(&self) -> Matrix {
+        let rotation = Matrix::rotation_x(self.rotation.x)
+            .multiply(&Matrix::rotation_y(self.rotation.y))
+            .multiply(&Matrix::rotation_z(self.rotation.z));
+
+        Matrix::from_trs(self.translation, &rotation, self.scale)
+    }
+}
+
+impl Default for Transform {
+    /// Create a transform that leaves an object as it is
+    ///
+    /// # Returns
+    /// * `Transform` - The default transform
+    fn default() -> Self {
+        Self::new(
+            Vector3D::default(),
+            Vector3D::default(),
+            Vector3D::new(1.0, 1.0, 1.0),
+        )
+    }
+}
+
+impl Interpolate for Transform {
+    /// Blend between two transforms, part by part
+    ///
+    /// # Arguments
+    /// * `other` - The transform to blend towards
+    /// * `t` - How far to blend
+    ///
+    /// # Returns
+    /// * `Transform` - The blended transform
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(&other.translation, t),
+            rotation: self.rotation.lerp(&other.rotation, t),
+            scale: self.scale.lerp(&other.scale, t),
+        }
+    }
+}
+
+/// A list of transforms at points in time, for animating an object. Between two keyframes the
+/// transform is blended using the easing function.
+pub struct Timeline {
+    /// `keyframes` - The time (in seconds) and transform of each keyframe, in order of time
+    keyframes: Vec<(f32, Transform)>,
+    /// `easing` - How the blend between keyframes speeds up and slows down
+    pub easing: fn(f32) -> f32,
+}
+
+impl Timeline {
+    /// Create a new, empty timeline
+    ///
+    /// # Arguments
+    /// * `easing` - How the blend between keyframes speeds up and slows down
+    ///
+    /// # Returns
+    /// * `Timeline` - The new timeline
+    pub fn new(easing: fn(f32) -> f32) -> Self {
+        Self {
+            keyframes: Vec::new(),
+            easing,
+        }
+    }
+
+    /// Add a keyframe. Keyframes can be added in any order.
+    ///
+    /// # Arguments
+    /// * `time` - When the object should have the transform, in seconds
+    /// * `transform` - The transform at that time
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn add_keyframe(&mut self, time: f32, transform: Transform) -> () {
+        let index = self
+            .keyframes
+            .partition_point(|(keyframe_time, _)| *keyframe_time <= time);
+
+        self.keyframes.insert(index, (time, transform));
+    }
+
+    /// Get the transform at a point in time. Before the first keyframe and after the last the
+    /// transform holds still.
+    ///
+    /// # Arguments
+    /// * `t` - The time to sample, in seconds
+    ///
+    /// # Returns
+    /// * `Transform` - The transform at that time, or the default transform if there are no
+    ///   keyframes
+    pub fn sample(&self, t: f32) -> Transform {
+        let next = self
+            .keyframes
+            .partition_point(|(keyframe_time, _)| *keyframe_time <= t);
+
+        if next == 0 || next == self.keyframes.len() {
+            // Hold the first or last keyframe
+            return self
+                .keyframes
+                .get(next.saturating_sub(1))
+                .map_or(Transform::default(), |(_, transform)| *transform);
+        }
+
+        let (start_time, start) = self.keyframes[next - 1];
+        let (end_time, end) = self.keyframes[next];
+        let progress = (t - start_time) / (end_time - start_time);
+
+        start.lerp(&end, (self.easing)(progress))
+    }
+}
+
+impl Default for Timeline {
+    /// Create an empty timeline that eases in and out of each keyframe
+    ///
+    /// # Returns
+    /// * `Timeline` - The default timeline
+    fn default() -> Self {
+        Self::new(easing::ease_in_out_cubic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A timeline moving from the origin to (2, 4, -6) while turning and growing, with the
+    /// keyframes added out of order
+    fn timeline(easing: fn(f32) -> f32) -> Timeline {
+        let mut timeline = Timeline::new(easing);
+        timeline.add_keyframe(
+            3.0,
+            Transform::new(
+                Vector3D::new(2.0, 4.0, -6.0),
+                Vector3D::new(0.0, 1.0, 0.0),
+                Vector3D::new(3.0, 3.0, 3.0),
+            ),
+        );
+        timeline.add_keyframe(1.0, Transform::default());
+
+        timeline
+    }
+
+    #[test]
+    fn midpoint_is_halfway_between_the_keyframes() {
+        for easing in [easing::linear, easing::ease_in_out_cubic] {
+            let sampled = timeline(easing).sample(2.0);
+
+            assert_eq!(sampled.translation, Vector3D::new(1.0, 2.0, -3.0));
+            assert_eq!(sampled.rotation, Vector3D::new(0.0, 0.5, 0.0));
+            assert_eq!(sampled.scale, Vector3D::new(2.0, 2.0, 2.0));
+        }
+    }
+
+    #[test]
+    fn easing_shapes_the_blend() {
+        let sampled = timeline(easing::ease_in_quad).sample(1.5);
+
+        // A quarter of the way through in time is a sixteenth of the way eased in
+        assert_eq!(sampled.translation, Vector3D::new(0.125, 0.25, -0.375));
+    }
+
+    #[test]
+    fn holds_still_outside_the_keyframes() {
+        let timeline = timeline(easing::linear);
+
+        assert_eq!(timeline.sample(0.0).translation, Vector3D::default());
+        assert_eq!(
+            timeline.sample(10.0).translation,
+            Vector3D::new(2.0, 4.0, -6.0)
+        );
+        assert_eq!(
+            Timeline::default().sample(1.0).scale,
+            Vector3D::new(1.0, 1.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Contains the rendering backends, so that the SDL canvas and the software
+//! framebuffer can be swapped between while the engine is running to
+//! compare how they look and how fast they are
+
+use crate::framebuffer::Framebuffer;
+use crate::target::RenderTarget;
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+/// What the scene is drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Draw straight onto the SDL canvas, which SDL can do on the GPU
+    Sdl,
+    /// Draw into a framebuffer in memory, which is copied onto the canvas once it is done
+    Software,
+}
+
+impl Backend {
+    /// Switch to the other backend
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn toggle(&mut self) -> () {
+        *self = match self {
+            Backend::Sdl => Backend::Software,
+            Backend::Software => Backend::Sdl,
+        };
+    }
+
+    /// Get the target that draw calls should go to for this backend
+    ///
+    /// # Arguments
+    /// * `canvas` - The canvas the `Sdl` backend draws on
+    /// * `framebuffer` - The framebuffer the `Software` backend draws in
+    ///
+    /// # Returns
+    /// * `BackendTarget<S>` - The target to draw on
+    pub fn target<'a, S: RenderTarget>(
+        &self,
+        canvas: &'a mut S,
+        framebuffer: &'a mut Framebuffer,
+    ) -> BackendTarget<'a, S> {
+        match self {
+            Backend::Sdl => BackendTarget::Sdl(canvas),
+            Backend::Software => BackendTarget::Software(framebuffer),
+        }
+    }
+}
+
+impl Default for Backend {
+    /// Create the default backend, which is the SDL canvas
+    ///
+    /// # Returns
+    /// * `Backend` - The default backend
+    fn default() -> Self {
+        Backend::Sdl
+    }
+}
+
+/// The target of whichever backend is in use, which passes every draw call on to it. The
+/// canvas is only generic so something else can stand in for it where there is no window.
+pub enum BackendTarget<'a, S: RenderTarget = Canvas<Window>> {
+    /// Drawing goes to the SDL canvas
+    Sdl(&'a mut S),
+    /// Drawing goes to the software framebuffer
+    Software(&'a mut Framebuffer),
+}
+
+impl<'a, S: RenderTarget> RenderTarget for BackendTarget<'a, S> {
+    /// Set the colour that the following draw calls use
+    ///
+    /// # Arguments
+    /// * `color` - The colour to draw with
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn set_draw_color(&mut self, color: Color) -> () {
+        match self {
+            BackendTarget::Sdl(canvas) => RenderTarget::set_draw_color(*canvas, color),
+            BackendTarget::Software(framebuffer) => framebuffer.set_draw_color(color),
+        }
+    }
+
+    /// Fill the whole target with the draw colour
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn clear(&mut self) -> () {
+        match self {
+            BackendTarget::Sdl(canvas) => RenderTarget::clear(*canvas),
+            BackendTarget::Software(framebuffer) => framebuffer.clear(),
+        }
+    }
+
+    /// Draw a line between two points
+    ///
+    /// # Arguments
+    /// * `start` - The start of the line
+    /// * `end` - The end of the line
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_line(&mut self, start: Point, end: Point) -> () {
+        match self {
+            BackendTarget::Sdl(canvas) => RenderTarget::draw_line(*canvas, start, end),
+            BackendTarget::Software(framebuffer) => framebuffer.draw_line(start, end),
+        }
+    }
+
+    /// Draw a single point
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_point(&mut self, point: Point) -> () {
+        match self {
+            BackendTarget::Sdl(canvas) => RenderTarget::draw_point(*canvas, point),
+            BackendTarget::Software(framebuffer) => framebuffer.draw_point(point),
+        }
+    }
+
+    /// Draw a single point that only partly covers its pixel
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    /// * `coverage` - How much of the pixel is covered, from 0 to 1
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn blend_point(&mut self, point: Point, coverage: f32) -> () {
+        match self {
+            BackendTarget::Sdl(canvas) => RenderTarget::blend_point(*canvas, point, coverage),
+            BackendTarget::Software(framebuffer) => framebuffer.blend_point(point, coverage),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::RecordingTarget;
+
+    #[test]
+    fn toggling_swaps_which_target_is_drawn_on() {
+        let mut backend = Backend::default();
+        let mut canvas = RecordingTarget::new();
+        let mut framebuffer = Framebuffer::new(8, 8);
+        let white = Color::RGB(255, 255, 255);
+
+        let mut target = backend.target(&mut canvas, &mut framebuffer);
+        target.set_draw_color(white);
+        target.draw_line(Point::new(0, 0), Point::new(7, 0));
+        assert_eq!(canvas.lines.len(), 1);
+        assert_eq!(framebuffer.get(3, 0), Some(Color::RGB(0, 0, 0)));
+
+        backend.toggle();
+        assert_eq!(backend, Backend::Software);
+        let mut target = backend.target(&mut canvas, &mut framebuffer);
+        target.set_draw_color(white);
+        target.draw_line(Point::new(0, 0), Point::new(7, 0));
+        assert_eq!(canvas.lines.len(), 1);
+        assert_eq!(framebuffer.get(3, 0), Some(white));
+
+        backend.toggle();
+        assert_eq!(backend, Backend::Sdl);
+    }
+}
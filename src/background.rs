@@ -0,0 +1,96 @@
+//! Contains the background that is drawn behind the scene - either
+//! a flat colour or a vertical gradient between two colours
+
+use crate::target::RenderTarget;
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+
+/// What gets drawn on the canvas before the scene each frame
+pub enum Background {
+    /// A single flat colour, the same as clearing the canvas
+    Solid(Color),
+    /// A vertical gradient, blending from `top` on the first row to `bottom` on the last
+    Gradient {
+        /// `top` - The colour of the first row
+        top: Color,
+        /// `bottom` - The colour of the last row
+        bottom: Color,
+    },
+}
+
+impl Background {
+    /// Get the colour of a single row of the background
+    ///
+    /// # Arguments
+    /// * `row` - The row to get the colour of, counted from the top
+    /// * `height` - The amount of rows in the canvas
+    ///
+    /// # Returns
+    /// * `Color` - The colour of the row
+    pub fn color_at_row(&self, row: u32, height: u32) -> Color {
+        match *self {
+            Background::Solid(color) => color,
+            Background::Gradient { top, bottom } => {
+                // A single row canvas has nothing to blend between, so it is just the top
+                let t = if height > 1 {
+                    row.min(height - 1) as f32 / (height - 1) as f32
+                } else {
+                    0.0
+                };
+                let blend =
+                    |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+
+                Color::RGBA(
+                    blend(top.r, bottom.r),
+                    blend(top.g, bottom.g),
+                    blend(top.b, bottom.b),
+                    blend(top.a, bottom.a),
+                )
+            }
+        }
+    }
+
+    /// Draw the background over the whole target
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the background on
+    /// * `width` - The width of the target
+    /// * `height` - The height of the target
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(&self, target: &mut T, width: u32, height: u32) -> () {
+        match *self {
+            Background::Solid(color) => {
+                target.set_draw_color(color);
+                target.clear();
+            }
+            Background::Gradient { .. } => {
+                for row in 0..height {
+                    target.set_draw_color(self.color_at_row(row, height));
+                    target.draw_line(
+                        Point::new(0, row as i32),
+                        Point::new(width as i32, row as i32),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_blends_from_top_to_bottom() {
+        let background = Background::Gradient {
+            top: Color::RGB(0, 100, 200),
+            bottom: Color::RGB(200, 100, 0),
+        };
+
+        assert_eq!(background.color_at_row(0, 101), Color::RGB(0, 100, 200));
+        assert_eq!(background.color_at_row(50, 101), Color::RGB(100, 100, 100));
+        assert_eq!(background.color_at_row(100, 101), Color::RGB(200, 100, 0));
+    }
+}
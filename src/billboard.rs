@@ -0,0 +1,53 @@
+//! Contains billboards - flat quads (e.g. sprites or particles) that are
+//! turned to face the camera every frame
+
+use crate::camera::Orientation;
+use crate::{Mesh, Triangle, Vector3D};
+
+/// Build a square quad that faces the camera. The quad lies in the camera's view plane, so
+/// it should be rebuilt every frame that the camera turns.
+///
+/// # Arguments
+/// * `center` - The position of the middle of the quad
+/// * `size` - The width (and height) of the quad
+/// * `camera` - The orientation of the camera the quad should face
+///
+/// # Returns
+/// * `Mesh` - The quad, as two triangles wound so that they face the camera
+pub fn billboard(center: &Vector3D, size: f32, camera: &Orientation) -> Mesh {
+    let right = camera.right();
+    let up = camera.up();
+    let half = size / 2.0;
+
+    let corner = |across: f32, along: f32| *center + (right * across + up * along) * half;
+
+    let bottom_left = corner(-1.0, -1.0);
+    let top_left = corner(-1.0, 1.0);
+    let top_right = corner(1.0, 1.0);
+    let bottom_right = corner(1.0, -1.0);
+
+    Mesh::new(vec![
+        Triangle::new(bottom_left, top_left, top_right),
+        Triangle::new(bottom_left, top_right, bottom_right),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn billboard_faces_the_camera() {
+        let camera = Orientation::new(0.7, 0.3, true);
+        let position = Vector3D::new(1.0, 2.0, 3.0);
+        let center = position + camera.forward() * 5.0;
+
+        let quad = billboard(&center, 2.0, &camera);
+        let to_camera = (position - center).normalize();
+
+        assert_eq!(quad.mat.len(), 2);
+        for triangle in &quad.mat {
+            assert!(triangle.normal().dot(&to_camera) > 0.999);
+        }
+    }
+}
@@ -0,0 +1,627 @@
+//! Contains everything to do with the viewpoint of the scene - where the
+//! camera is and the yaw and pitch angles that free-look rotates
+
+use crate::units::Degrees;
+use crate::{
+    clip_line_near, clip_triangle_near, multiply_matrix_vector, Matrix, Triangle, Vector3D,
+};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// Wrap an angle into the range [0, 2π)
+///
+/// # Arguments
+/// * `angle` - The angle to wrap, in radians
+///
+/// # Returns
+/// * `f32` - The equivalent angle in [0, 2π)
+pub fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = angle.rem_euclid(TAU);
+
+    // Rounding can land a tiny negative angle exactly on 2π, which is outside the range
+    if wrapped >= TAU {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+/// The direction the camera is looking in as a pair of angles. Yaw turns around the
+/// vertical axis and pitch tilts up and down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orientation {
+    /// `yaw` - The rotation around the vertical axis, in radians
+    pub yaw: f32,
+    /// `pitch` - The rotation up or down, in radians
+    pub pitch: f32,
+    /// `wrap_yaw` - Whether the yaw should be kept in [0, 2π) every frame, rather than being
+    /// left to accumulate
+    pub wrap_yaw: bool,
+}
+
+impl Orientation {
+    /// How far the pitch can go up or down. Stopping just short of straight up or down keeps
+    /// the camera from flipping over.
+    const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+
+    /// Create a new orientation
+    ///
+    /// # Arguments
+    /// * `yaw` - The rotation around the vertical axis, in radians
+    /// * `pitch` - The rotation up or down, in radians
+    /// * `wrap_yaw` - Whether the yaw should be wrapped every frame
+    ///
+    /// # Returns
+    /// * `Orientation` - The new orientation
+    pub fn new(yaw: f32, pitch: f32, wrap_yaw: bool) -> Self {
+        Self {
+            yaw,
+            pitch,
+            wrap_yaw,
+        }
+    }
+
+    /// Create an orientation that looks along a direction, with yaw wrapping turned on. The
+    /// camera can't roll, so only the way the direction points is kept.
+    ///
+    /// # Arguments
+    /// * `direction` - The direction to look in, which doesn't need to be a unit vector
+    ///
+    /// # Returns
+    /// * `Orientation` - The orientation whose `forward` is the direction
+    pub fn looking_along(direction: &Vector3D) -> Self {
+        let direction = direction.normalize();
+
+        Self::new(
+            direction.x.atan2(direction.z),
+            direction.y.clamp(-1.0, 1.0).asin(),
+            true,
+        )
+    }
+
+    /// Keep the angles in range, which should be done once per frame after the mouse has
+    /// moved the camera. The pitch is always clamped, the yaw is only wrapped when `wrap_yaw`
+    /// is set.
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn update(&mut self) -> () {
+        if self.wrap_yaw {
+            self.yaw = wrap_angle(self.yaw);
+        }
+
+        self.pitch = self
+            .pitch
+            .clamp(-Orientation::PITCH_LIMIT, Orientation::PITCH_LIMIT);
+    }
+
+    /// Get the direction the camera is looking in. A yaw and pitch of zero looks down +Z.
+    ///
+    /// # Returns
+    /// * `Vector3D` - The unit forward vector
+    pub fn forward(&self) -> Vector3D {
+        Vector3D::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        )
+    }
+
+    /// Get the direction to the right of the camera, which always lies flat as the camera
+    /// can't roll
+    ///
+    /// # Returns
+    /// * `Vector3D` - The unit right vector
+    pub fn right(&self) -> Vector3D {
+        Vector3D::new(self.yaw.cos(), 0.0, -self.yaw.sin())
+    }
+
+    /// Get the direction straight up from the camera's point of view
+    ///
+    /// # Returns
+    /// * `Vector3D` - The unit up vector
+    pub fn up(&self) -> Vector3D {
+        Vector3D::new(
+            -self.pitch.sin() * self.yaw.sin(),
+            self.pitch.cos(),
+            -self.pitch.sin() * self.yaw.cos(),
+        )
+    }
+
+    /// Get the angles in the form they should be displayed in (e.g. on a HUD), whether or not
+    /// the stored yaw is being wrapped
+    ///
+    /// # Returns
+    /// * `(f32, f32)` - The yaw in [0, 2π) and the clamped pitch, in radians
+    pub fn normalized(&self) -> (f32, f32) {
+        (
+            wrap_angle(self.yaw),
+            self.pitch
+                .clamp(-Orientation::PITCH_LIMIT, Orientation::PITCH_LIMIT),
+        )
+    }
+}
+
+impl Default for Orientation {
+    /// Create a default orientation looking straight ahead, with yaw wrapping turned on
+    ///
+    /// # Returns
+    /// * `Orientation` - The default orientation
+    fn default() -> Self {
+        Self::new(0.0, 0.0, true)
+    }
+}
+
+/// Where the scene is viewed from and what it is looking at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// `position` - Where the camera is
+    pub position: Vector3D,
+    /// `target` - The point the camera is looking at
+    pub target: Vector3D,
+    /// `up` - Which way is up for the camera
+    pub up: Vector3D,
+}
+
+impl Camera {
+    /// Create a new camera
+    ///
+    /// # Arguments
+    /// * `position` - Where the camera is
+    /// * `target` - The point the camera is looking at
+    /// * `up` - Which way is up for the camera
+    ///
+    /// # Returns
+    /// * `Camera` - The new camera
+    pub fn new(position: Vector3D, target: Vector3D, up: Vector3D) -> Self {
+        Self {
+            position,
+            target,
+            up,
+        }
+    }
+
+    /// Get the direction the camera is looking in
+    ///
+    /// # Returns
+    /// * `Vector3D` - The unit forward vector, or +Z if the camera is on its target
+    pub fn forward(&self) -> Vector3D {
+        let forward = (self.target - self.position).normalize();
+
+        if forward.length() > 0.0 {
+            forward
+        } else {
+            Vector3D::new(0.0, 0.0, 1.0)
+        }
+    }
+
+    /// Get the look-at matrix that moves the world so the camera is at the origin, looking
+    /// down +Z with its up along +Y. This goes before the projection.
+    ///
+    /// # Returns
+    /// * `Matrix` - The view matrix
+    pub fn view_matrix(&self) -> Matrix {
+        let forward = self.forward();
+        let mut right = self.up.cross(&forward).normalize();
+        // Looking straight along `up` leaves no way to tell which way is right, so any
+        // direction at a right angle to the view will do
+        if right.length() == 0.0 {
+            right = Vector3D::new(0.0, 0.0, 1.0).cross(&forward).normalize();
+            if right.length() == 0.0 {
+                right = Vector3D::new(1.0, 0.0, 0.0);
+            }
+        }
+        let up = forward.cross(&right);
+
+        // The axes go in the columns, so each row dots a vector with one of them
+        Matrix::new([
+            [right.x, up.x, forward.x, 0.0],
+            [right.y, up.y, forward.y, 0.0],
+            [right.z, up.z, forward.z, 0.0],
+            [
+                -right.dot(&self.position),
+                -up.dot(&self.position),
+                -forward.dot(&self.position),
+                1.0,
+            ],
+        ])
+    }
+
+    /// Write the camera out as `key = value` lines, which can be pasted into a TOML config
+    /// and read back with `from_config_string`. Each vector is written as its three
+    /// components in full, so the camera loads back exactly.
+    ///
+    /// # Returns
+    /// * `String` - The config lines
+    pub fn to_config_string(self) -> String {
+        let vector = |v: Vector3D| format!("{}, {}, {}", v.x, v.y, v.z);
+
+        format!(
+            "position = {}\ntarget = {}\nup = {}\n",
+            vector(self.position),
+            vector(self.target),
+            vector(self.up)
+        )
+    }
+
+    /// Read a camera from `key = value` lines, like those written by `to_config_string`.
+    /// Blank lines and `#` comments are skipped, and any key that is missing keeps its
+    /// default value.
+    ///
+    /// # Arguments
+    /// * `config` - The config lines
+    ///
+    /// # Returns
+    /// * `Result<Camera, String>` - The camera, or which line couldn't be read
+    pub fn from_config_string(config: &str) -> Result<Self, String> {
+        let mut camera = Camera::default();
+
+        for line in config.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| format!("Expected `key = value`, found `{}`", line))?;
+            let invalid = || format!("Invalid value for `{}`: `{}`", key, value);
+            let components = value
+                .split(',')
+                .map(|component| component.trim().parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| invalid())?;
+            let vector = match components[..] {
+                [x, y, z] => Vector3D::new(x, y, z),
+                _ => return Err(invalid()),
+            };
+
+            match key {
+                "position" => camera.position = vector,
+                "target" => camera.target = vector,
+                "up" => camera.up = vector,
+                _ => return Err(format!("Unknown key `{}`", key)),
+            }
+        }
+
+        Ok(camera)
+    }
+
+    /// Turn the camera to face the way an orientation looks, keeping it where it is. The
+    /// target is put one unit in front of the camera.
+    ///
+    /// # Arguments
+    /// * `orientation` - The way the camera should face
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn look(&mut self, orientation: &Orientation) -> () {
+        self.target = self.position + orientation.forward();
+        self.up = orientation.up();
+    }
+
+    /// Aim the camera at the middle of a bounding box and move it back along the way it is
+    /// facing until the box's bounding sphere fits in the field of view
+    ///
+    /// # Arguments
+    /// * `bbox` - The smallest and largest corners of the box
+    /// * `fov` - The field of view, in degrees
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn frame_bounds(&mut self, bbox: (Vector3D, Vector3D), fov: f32) -> () {
+        let (min, max) = bbox;
+        let center = min.midpoint(&max);
+        let radius = min.distance(&max) / 2.0;
+        // The sphere touches both sides of the view when its radius is at a right angle to
+        // the edge of the view
+        let distance = radius / (Degrees(fov).to_radians().0 / 2.0).sin();
+        let forward = self.forward();
+
+        self.position = center - forward * distance;
+        self.target = center;
+    }
+}
+
+impl Default for Camera {
+    /// Create a camera 3 units behind the origin, looking down +Z at it
+    ///
+    /// # Returns
+    /// * `Camera` - The default camera
+    fn default() -> Self {
+        Self::new(
+            Vector3D::new(0.0, 0.0, -3.0),
+            Vector3D::default(),
+            Vector3D::new(0.0, 1.0, 0.0),
+        )
+    }
+}
+
+/// How the camera sees the scene for one frame. Geometry is taken into view space, clipped
+/// against the near plane there and only then projected, so nothing behind the camera is
+/// ever divided by a w at or below zero.
+pub struct View {
+    /// `matrix` - Takes world coordinates into view space
+    pub matrix: Matrix,
+    /// `projection` - The projection matrix, which takes view space to the screen
+    pub projection: Matrix,
+    /// `near` - The distance of the near plane in front of the camera, in view space
+    pub near: f32,
+    /// `width` - The width of the display
+    pub width: f32,
+    /// `height` - The height of the display
+    pub height: f32,
+}
+
+impl View {
+    /// Create the view a camera has of the scene
+    ///
+    /// # Arguments
+    /// * `camera` - The camera the scene is seen from
+    /// * `projection` - The projection matrix
+    /// * `near_plane` - The distance of the near plane from the camera, as given to the
+    ///   projection
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `View` - The new view
+    pub fn new(
+        camera: &Camera,
+        projection: &Matrix,
+        near_plane: f32,
+        width: f32,
+        height: f32,
+    ) -> Self {
+        Self {
+            matrix: camera.view_matrix(),
+            projection: projection.clone(),
+            near: near_plane,
+            width,
+            height,
+        }
+    }
+
+    /// Get the matrix that takes world coordinates straight to the screen through
+    /// `project_to_screen`, e.g. for picking. It doesn't clip, so it is only safe for points
+    /// in front of the camera.
+    ///
+    /// # Returns
+    /// * `Matrix` - The view and projection matrix
+    pub fn view_projection(&self) -> Matrix {
+        self.matrix.multiply(&self.projection)
+    }
+
+    /// Take a triangle into view space and clip it against the near plane
+    ///
+    /// # Arguments
+    /// * `triangle` - The triangle in world coordinates
+    ///
+    /// # Returns
+    /// * `Vec<Triangle>` - What is left of the triangle in view space, ready to be projected
+    pub fn clip_triangle(&self, triangle: &Triangle) -> Vec<Triangle> {
+        clip_triangle_near(&triangle.transformed(&self.matrix), self.near)
+    }
+
+    /// Take a line into view space and clip it against the near plane
+    ///
+    /// # Arguments
+    /// * `start` - The start of the line in world coordinates
+    /// * `end` - The end of the line in world coordinates
+    ///
+    /// # Returns
+    /// * `Option<(Vector3D, Vector3D)>` - What is left of the line in view space, or `None` if
+    ///   it is entirely behind the camera
+    pub fn clip_line(&self, start: &Vector3D, end: &Vector3D) -> Option<(Vector3D, Vector3D)> {
+        let mut view_start = Vector3D::default();
+        let mut view_end = Vector3D::default();
+
+        multiply_matrix_vector(start, &mut view_start, &self.matrix);
+        multiply_matrix_vector(end, &mut view_end, &self.matrix);
+
+        clip_line_near(&view_start, &view_end, self.near)
+    }
+
+    /// Project a triangle that has already been clipped onto the screen
+    ///
+    /// # Arguments
+    /// * `triangle` - The triangle in view space
+    ///
+    /// # Returns
+    /// * `Triangle` - The triangle in screen coordinates
+    pub fn project(&self, triangle: &Triangle) -> Triangle {
+        triangle.project_to_screen(&self.projection, self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_to_screen;
+
+    #[test]
+    fn update_wraps_yaw_and_clamps_pitch() {
+        let mut orientation = Orientation::new(7.0, 3.0, true);
+        orientation.update();
+
+        assert!((orientation.yaw - (7.0 - TAU)).abs() < 1e-5);
+        assert!((orientation.yaw - 0.717).abs() < 1e-3);
+        assert_eq!(orientation.pitch, Orientation::PITCH_LIMIT);
+    }
+
+    #[test]
+    fn update_leaves_yaw_alone_without_wrapping() {
+        let mut orientation = Orientation::new(7.0, 0.0, false);
+        orientation.update();
+
+        assert_eq!(orientation.yaw, 7.0);
+        assert!((orientation.normalized().0 - 0.717).abs() < 1e-3);
+    }
+
+    #[test]
+    fn view_projection_matches_clipping_then_projecting() {
+        let camera = Camera::new(
+            Vector3D::new(1.0, 2.0, -4.0),
+            Vector3D::new(0.0, 0.5, 1.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+        let view = View::new(
+            &camera,
+            &Matrix::projection(90.0, 150.0 / 200.0, 0.1, 100.0),
+            0.1,
+            200.0,
+            150.0,
+        );
+        let triangle = Triangle::new(
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(1.0, 0.0, 0.5),
+            Vector3D::new(0.0, 1.0, 1.0),
+        );
+
+        let clipped = view.clip_triangle(&triangle);
+        assert_eq!(clipped.len(), 1);
+        let expected = view.project(&clipped[0]);
+        let actual = triangle.project_to_screen(&view.view_projection(), 200.0, 150.0);
+
+        for (expected, actual) in [
+            (expected.a, actual.a),
+            (expected.b, actual.b),
+            (expected.c, actual.c),
+        ] {
+            assert!(
+                (expected - actual).length() < 1e-3,
+                "{:?} {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn default_camera_sees_the_origin_in_the_middle_of_the_screen() {
+        let view = View::new(
+            &Camera::default(),
+            &Matrix::projection(90.0, 150.0 / 200.0, 0.1, 100.0),
+            0.1,
+            200.0,
+            150.0,
+        );
+        let origin = Vector3D::default();
+
+        let screen = project_to_screen(&origin, &view.view_projection(), 200.0, 150.0);
+        assert!((screen.x - 100.0).abs() < 1e-3 && (screen.y - 75.0).abs() < 1e-3);
+        // The origin is 3 units in front of the camera in view space
+        let mut in_view = Vector3D::default();
+        multiply_matrix_vector(&origin, &mut in_view, &view.matrix);
+        assert!((in_view.z - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn config_string_round_trips() {
+        let camera = Camera::new(
+            Vector3D::new(0.1, -1.0 / 3.0, 12345.678),
+            Vector3D::new(-2.5, 1e-7, 4.0),
+            Vector3D::new(0.0, 1.0, 0.0).normalize(),
+        );
+
+        assert_eq!(
+            Camera::from_config_string(&camera.to_config_string()),
+            Ok(camera)
+        );
+    }
+
+    #[test]
+    fn config_string_reports_bad_lines() {
+        assert_eq!(
+            Camera::from_config_string("# saved\n\ntarget = 1, 2, 3\n"),
+            Ok(Camera {
+                target: Vector3D::new(1.0, 2.0, 3.0),
+                ..Camera::default()
+            })
+        );
+        assert_eq!(
+            Camera::from_config_string("position = 1, 2"),
+            Err("Invalid value for `position`: `1, 2`".to_string())
+        );
+        assert_eq!(
+            Camera::from_config_string("zoom = 1, 2, 3"),
+            Err("Unknown key `zoom`".to_string())
+        );
+    }
+
+    #[test]
+    fn framing_a_unit_cube_at_ninety_degrees() {
+        let mut camera = Camera::default();
+        camera.frame_bounds(
+            (Vector3D::new(0.0, 0.0, 0.0), Vector3D::new(1.0, 1.0, 1.0)),
+            90.0,
+        );
+        let center = Vector3D::new(0.5, 0.5, 0.5);
+        // The bounding sphere's radius over the sine of half the field of view
+        let expected = (3.0f32.sqrt() / 2.0) / std::f32::consts::FRAC_PI_4.sin();
+
+        assert!((expected - 6.0f32.sqrt() / 2.0).abs() < 1e-6);
+        assert_eq!(camera.target, center);
+        assert!((camera.position.distance(&center) - expected).abs() < 1e-5);
+        assert!((camera.position - Vector3D::new(0.5, 0.5, 0.5 - expected)).length() < 1e-5);
+    }
+
+    #[test]
+    fn framing_keeps_the_way_the_camera_faces() {
+        let mut camera = Camera::new(
+            Vector3D::new(5.0, 5.0, 5.0),
+            Vector3D::new(4.0, 4.0, 4.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+        let forward = camera.forward();
+        camera.frame_bounds(
+            (
+                Vector3D::new(-1.0, -1.0, -1.0),
+                Vector3D::new(1.0, 1.0, 1.0),
+            ),
+            60.0,
+        );
+
+        assert!((camera.forward() - forward).length() < 1e-5);
+        // A radius of the square root of 3 fits in a 60 degree view from twice that away
+        assert!((camera.position.length() - 2.0 * 3.0f32.sqrt()).abs() < 1e-4);
+    }
+
+    /// Move a point by a matrix
+    fn apply(matrix: &Matrix, v: Vector3D) -> Vector3D {
+        let mut moved = Vector3D::default();
+        multiply_matrix_vector(&v, &mut moved, matrix);
+        moved
+    }
+
+    #[test]
+    fn camera_at_the_origin_looking_down_z_has_the_identity_view() {
+        let camera = Camera::new(
+            Vector3D::default(),
+            Vector3D::new(0.0, 0.0, 1.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+        let view = camera.view_matrix();
+        let identity = Matrix::identity();
+
+        for (row, expected) in view.mat.iter().zip(identity.mat.iter()) {
+            for (value, expected) in row.iter().zip(expected) {
+                assert!((value - expected).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn moving_the_camera_moves_the_world_the_other_way() {
+        let camera = Camera::new(
+            Vector3D::new(2.0, -1.0, 0.5),
+            Vector3D::new(2.0, -1.0, 1.5),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+        let point = Vector3D::new(1.0, 1.0, 4.0);
+        let moved = apply(&camera.view_matrix(), point);
+
+        assert!((moved - (point - camera.position)).length() < 1e-5);
+        // The camera itself ends up at the origin
+        assert!(apply(&camera.view_matrix(), camera.position).length() < 1e-5);
+    }
+}
@@ -0,0 +1,184 @@
+//! Contains the settings for backface culling - skipping triangles that
+//! face away from the camera, as they would be hidden by the front faces
+
+use crate::{Triangle, Vector3D};
+use sdl2::pixels::Color;
+
+/// Settings for how triangles are culled
+#[derive(Debug, Clone, Copy)]
+pub struct CullConfig {
+    /// `epsilon` - How far past edge-on a triangle can turn before it is culled. A triangle
+    /// that is edge-on to the camera has a facing of exactly zero, and rounding makes it flip
+    /// between culled and drawn from frame to frame.
+    ///
+    /// A positive epsilon keeps triangles that are slightly past edge-on, which costs drawing
+    /// a few slivers that are hidden anyway. A negative epsilon culls triangles that are
+    /// slightly before edge-on, which can open up visible gaps along silhouettes.
+    pub epsilon: f32,
+    /// `show_back_faces` - Whether triangles that would be culled are drawn in
+    /// `back_face_color` instead, to help track down triangles that are wound the wrong way
+    pub show_back_faces: bool,
+    /// `back_face_color` - The colour back faces are drawn in when they are shown
+    pub back_face_color: Color,
+}
+
+impl CullConfig {
+    /// Check whether a triangle should be drawn
+    ///
+    /// # Arguments
+    /// * `facing` - The dot product of the triangle's normal with the direction from the
+    ///   camera to the triangle, which is negative when the triangle faces the camera
+    ///
+    /// # Returns
+    /// * `bool` - Whether the triangle is visible
+    pub fn is_visible(&self, facing: f32) -> bool {
+        facing < self.epsilon
+    }
+
+    /// Check whether a triangle faces the camera closely enough to be drawn
+    ///
+    /// # Arguments
+    /// * `triangle` - The triangle to check
+    /// * `camera` - Where the camera is
+    ///
+    /// # Returns
+    /// * `bool` - Whether the triangle is visible
+    pub fn is_triangle_visible(&self, triangle: &Triangle, camera: &Vector3D) -> bool {
+        self.is_visible(triangle.normal().dot(&(triangle.a - *camera).normalize()))
+    }
+
+    /// Get the triangles that should be drawn in `back_face_color`, which are the culled ones
+    /// when back faces are shown and none otherwise
+    ///
+    /// # Arguments
+    /// * `triangles` - Every triangle, before culling
+    /// * `visible` - Whether each triangle passed culling, from `is_triangle_visible`
+    ///
+    /// # Returns
+    /// * `impl Iterator<Item = &Triangle>` - The back faces to draw
+    pub fn back_faces<'a>(
+        &self,
+        triangles: &'a [Triangle],
+        visible: &'a [bool],
+    ) -> impl Iterator<Item = &'a Triangle> + 'a {
+        let show = self.show_back_faces;
+
+        triangles
+            .iter()
+            .zip(visible)
+            .filter(move |(_, visible)| show && !**visible)
+            .map(|(triangle, _)| triangle)
+    }
+}
+
+impl Default for CullConfig {
+    /// Create a default config that leans towards keeping edge-on triangles, and hides back
+    /// faces
+    ///
+    /// # Returns
+    /// * `CullConfig` - The default config
+    fn default() -> Self {
+        Self {
+            epsilon: 1e-4,
+            show_back_faces: false,
+            back_face_color: Color::RGB(255, 60, 60),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::{RecordingTarget, RenderTarget};
+
+    #[test]
+    fn edge_on_faces_follow_the_sign_of_epsilon() {
+        let keep = CullConfig {
+            epsilon: 1e-4,
+            ..CullConfig::default()
+        };
+        let cull = CullConfig {
+            epsilon: -1e-4,
+            ..CullConfig::default()
+        };
+
+        // Just past and just before edge-on, both within epsilon of zero
+        for facing in [5e-5, 0.0, -5e-5] {
+            assert!(keep.is_visible(facing));
+            assert!(!cull.is_visible(facing));
+        }
+        // Well clear of edge-on, the epsilon makes no difference
+        for config in [keep, cull] {
+            assert!(config.is_visible(-0.5));
+            assert!(!config.is_visible(0.5));
+        }
+    }
+
+    #[test]
+    fn triangle_facing_the_camera_is_visible() {
+        let config = CullConfig::default();
+        let triangle = Triangle::new(
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+            Vector3D::new(1.0, 1.0, 0.0),
+        );
+
+        assert!(config.is_triangle_visible(&triangle, &Vector3D::new(0.5, 0.5, -3.0)));
+        assert!(!config.is_triangle_visible(&triangle, &Vector3D::new(0.5, 0.5, 3.0)));
+    }
+
+    #[test]
+    fn triangle_facing_away_from_the_camera_is_culled() {
+        let config = CullConfig::default();
+        let camera = Vector3D::default();
+        // In front of a camera looking down +Z, with its normal along +Z as well
+        let facing_away = Triangle::new(
+            Vector3D::new(0.0, 0.0, 5.0),
+            Vector3D::new(1.0, 0.0, 5.0),
+            Vector3D::new(0.0, 1.0, 5.0),
+        );
+        let mut facing_towards = facing_away.clone();
+        facing_towards.flip();
+
+        assert_eq!(facing_away.normal(), Vector3D::new(0.0, 0.0, 1.0));
+        assert!(!config.is_triangle_visible(&facing_away, &camera));
+        assert!(config.is_triangle_visible(&facing_towards, &camera));
+    }
+
+    #[test]
+    fn shown_back_faces_are_drawn_in_the_debug_colour() {
+        let camera = Vector3D::default();
+        let facing_away = Triangle::new(
+            Vector3D::new(0.0, 0.0, 5.0),
+            Vector3D::new(1.0, 0.0, 5.0),
+            Vector3D::new(0.0, 1.0, 5.0),
+        );
+        let mut facing_towards = facing_away.clone();
+        facing_towards.flip();
+        let triangles = [facing_towards, facing_away];
+        // Drawn the way the game loop draws back faces in wireframe
+        let draw = |config: &CullConfig| {
+            let visible: Vec<bool> = triangles
+                .iter()
+                .map(|triangle| config.is_triangle_visible(triangle, &camera))
+                .collect();
+            let mut target = RecordingTarget::new();
+            target.set_draw_color(config.back_face_color);
+            for triangle in config.back_faces(&triangles, &visible) {
+                triangle.draw(&mut target);
+            }
+            target
+        };
+
+        let shown = draw(&CullConfig {
+            show_back_faces: true,
+            ..CullConfig::default()
+        });
+        assert_eq!(shown.lines.len(), 3);
+        for (_, _, color) in &shown.lines {
+            assert_eq!(*color, CullConfig::default().back_face_color);
+        }
+
+        assert!(draw(&CullConfig::default()).lines.is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+//! Contains the depth testing that decides which of two overlapping
+//! fragments is in front
+
+/// Settings for how depth is compared
+///
+/// Depths are in normalized depth units, the z the projection matrix produces once the
+/// perspective divide is done: 0 at the near plane and 1 at the far plane. Because of the
+/// divide these units are not linear - a bias covers far less world distance close to the
+/// camera than it does far away.
+#[derive(Debug, Clone, Copy)]
+pub struct DepthConfig {
+    /// `wireframe_bias` - How much closer wireframe edges are treated as being when they are
+    /// drawn over filled triangles, in normalized depth units. Without this an edge and the
+    /// fill it sits on have the same depth, and rounding decides which is shown, making the
+    /// edges flicker.
+    pub wireframe_bias: f32,
+}
+
+impl Default for DepthConfig {
+    /// Create a default config with a bias that is enough to beat rounding in the depth of
+    /// coincident fragments
+    ///
+    /// # Returns
+    /// * `DepthConfig` - The default config
+    fn default() -> Self {
+        Self {
+            wireframe_bias: 1e-4,
+        }
+    }
+}
+
+/// Check whether a fragment is in front of what has already been drawn at its pixel
+///
+/// # Arguments
+/// * `depth` - The depth of the new fragment
+/// * `stored` - The depth of what has already been drawn
+/// * `bias` - How much closer the new fragment is treated as being, 0 for no bias
+///
+/// # Returns
+/// * `bool` - Whether the new fragment should be drawn
+pub fn depth_test(depth: f32, stored: f32, bias: f32) -> bool {
+    depth - bias < stored
+}
+
+/// The depth of the nearest fragment drawn at each pixel so far, so that nearer triangles
+/// hide farther ones whatever order they are drawn in
+pub struct DepthBuffer {
+    /// `width` - The width of the buffer, in pixels
+    pub width: u32,
+    /// `height` - The height of the buffer, in pixels
+    pub height: u32,
+    /// `depths` - The depth at each pixel, row by row from the top left
+    depths: Vec<f32>,
+    /// `origin` - The screen position of the top left pixel, which is only away from the
+    /// origin for a piece cut out of a bigger buffer with `region`
+    origin: (i32, i32),
+}
+
+impl DepthBuffer {
+    /// Create a new, cleared depth buffer
+    ///
+    /// # Arguments
+    /// * `width` - The width of the buffer, in pixels
+    /// * `height` - The height of the buffer, in pixels
+    ///
+    /// # Returns
+    /// * `DepthBuffer` - The new depth buffer
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            depths: vec![f32::INFINITY; width as usize * height as usize],
+            origin: (0, 0),
+        }
+    }
+
+    /// Copy a rectangle of depths out into a buffer of its own, which keeps using the same
+    /// screen coordinates (e.g. so a tile of the screen can be depth tested on its own)
+    ///
+    /// # Arguments
+    /// * `x` - The column of the top left pixel of the rectangle
+    /// * `y` - The row of the top left pixel of the rectangle
+    /// * `width` - The width of the rectangle, which must fit in the buffer
+    /// * `height` - The height of the rectangle, which must fit in the buffer
+    ///
+    /// # Returns
+    /// * `DepthBuffer` - The copy of the rectangle
+    pub fn region(&self, x: i32, y: i32, width: u32, height: u32) -> DepthBuffer {
+        let depths = (y..y + height as i32)
+            .flat_map(|row| (x..x + width as i32).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                self.index(column, row)
+                    .map_or(f32::INFINITY, |index| self.depths[index])
+            })
+            .collect();
+
+        DepthBuffer {
+            width,
+            height,
+            depths,
+            origin: (x, y),
+        }
+    }
+
+    /// Copy the depths of a region (made with `region`) back to where they came from
+    ///
+    /// # Arguments
+    /// * `region` - The region to copy back
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn copy_region(&mut self, region: &DepthBuffer) -> () {
+        let (x, y) = region.origin;
+
+        for row in y..y + region.height as i32 {
+            for column in x..x + region.width as i32 {
+                if let (Some(to), Some(from)) = (self.index(column, row), region.index(column, row))
+                {
+                    self.depths[to] = region.depths[from];
+                }
+            }
+        }
+    }
+
+    /// Get the screen position of the top left pixel of the buffer
+    ///
+    /// # Returns
+    /// * `(i32, i32)` - The column and row of the top left pixel, which is `(0, 0)` unless
+    ///   the buffer was made with `region`
+    pub fn origin(&self) -> (i32, i32) {
+        self.origin
+    }
+
+    /// Get where the depth of a pixel is stored
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    ///
+    /// # Returns
+    /// * `Option<usize>` - The index of the pixel, if it is inside the buffer
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (x, y) = (x - self.origin.0, y - self.origin.1);
+
+        if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Forget everything that has been drawn, ready for the next frame
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn clear(&mut self) -> () {
+        self.depths.fill(f32::INFINITY);
+    }
+
+    /// Check whether a fragment is in front of what has been drawn at its pixel, without
+    /// storing its depth. This is for overlays like wireframe edges, which shouldn't hide
+    /// anything drawn after them.
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    /// * `z` - The depth of the fragment
+    /// * `bias` - How much closer the fragment is treated as being, e.g.
+    ///   `DepthConfig::wireframe_bias`
+    ///
+    /// # Returns
+    /// * `bool` - Whether the fragment should be drawn, which is never the case off the buffer
+    pub fn test(&self, x: i32, y: i32, z: f32, bias: f32) -> bool {
+        self.index(x, y)
+            .is_some_and(|index| depth_test(z, self.depths[index], bias))
+    }
+
+    /// Check whether a fragment is in front of what has been drawn at its pixel, and if it is,
+    /// store its depth
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    /// * `z` - The depth of the fragment
+    ///
+    /// # Returns
+    /// * `bool` - Whether the fragment should be drawn, which is never the case off the buffer
+    pub fn test_and_set(&mut self, x: i32, y: i32, z: f32) -> bool {
+        let Some(index) = self.index(x, y) else {
+            return false;
+        };
+
+        let stored = &mut self.depths[index];
+        if depth_test(z, *stored, 0.0) {
+            *stored = z;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+    use crate::raster::RasterConfig;
+    use crate::{Triangle, Vector3D};
+    use sdl2::pixels::Color;
+
+    #[test]
+    fn bias_lets_a_coincident_fragment_through() {
+        let bias = DepthConfig::default().wireframe_bias;
+        let mut depth_buffer = DepthBuffer::new(4, 4);
+        assert!(depth_buffer.test_and_set(1, 2, 0.5));
+
+        // The edge works its depth out differently to the fill, so it can round either way
+        for rounded in [0.5, 0.5 + 1e-6, 0.5 + 1e-5] {
+            assert!(depth_buffer.test(1, 2, rounded, bias));
+        }
+        assert!(!depth_buffer.test(1, 2, 0.5, 0.0));
+        assert!(!depth_buffer.test(1, 2, 0.6, bias));
+    }
+
+    #[test]
+    fn nothing_passes_off_the_buffer() {
+        let depth_buffer = DepthBuffer::new(4, 4);
+
+        assert!(!depth_buffer.test(-1, 0, 0.0, 1.0));
+        assert!(!depth_buffer.test(0, 4, 0.0, 1.0));
+    }
+
+    /// Fill a square of two triangles covering the whole of a small buffer at one depth
+    fn fill_square(
+        framebuffer: &mut Framebuffer,
+        depth_buffer: &mut DepthBuffer,
+        z: f32,
+        color: Color,
+    ) {
+        for triangle in [
+            Triangle::new(
+                Vector3D::new(0.0, 0.0, z),
+                Vector3D::new(0.0, 8.0, z),
+                Vector3D::new(8.0, 0.0, z),
+            ),
+            Triangle::new(
+                Vector3D::new(8.0, 0.0, z),
+                Vector3D::new(0.0, 8.0, z),
+                Vector3D::new(8.0, 8.0, z),
+            ),
+        ] {
+            triangle.fill(framebuffer, color, depth_buffer, &RasterConfig::default());
+        }
+    }
+
+    #[test]
+    fn nearer_triangle_wins_whatever_the_order() {
+        let (near, far) = (Color::RGB(255, 0, 0), Color::RGB(0, 0, 255));
+
+        let mut framebuffer = Framebuffer::new(8, 8);
+        let mut depth_buffer = DepthBuffer::new(8, 8);
+        fill_square(&mut framebuffer, &mut depth_buffer, 0.8, far);
+        fill_square(&mut framebuffer, &mut depth_buffer, 0.2, near);
+        assert_eq!(framebuffer.get(4, 4), Some(near));
+
+        framebuffer = Framebuffer::new(8, 8);
+        depth_buffer.clear();
+        fill_square(&mut framebuffer, &mut depth_buffer, 0.2, near);
+        fill_square(&mut framebuffer, &mut depth_buffer, 0.8, far);
+        assert_eq!(framebuffer.get(4, 4), Some(near));
+    }
+
+    #[test]
+    fn test_and_set_keeps_the_nearest_depth() {
+        let mut depth_buffer = DepthBuffer::new(2, 2);
+
+        assert!(depth_buffer.test_and_set(0, 0, 0.7));
+        assert!(depth_buffer.test_and_set(0, 0, 0.3));
+        assert!(!depth_buffer.test_and_set(0, 0, 0.5));
+        assert!(!depth_buffer.test(0, 0, 0.3, 0.0));
+
+        depth_buffer.clear();
+        assert!(depth_buffer.test_and_set(0, 0, 0.9));
+    }
+}
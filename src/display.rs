@@ -0,0 +1,255 @@
+//! Contains the size of the display. On high-DPI screens the window's size
+//! (in logical points) and the size of what is drawn (in pixels) differ.
+
+/// The smallest window side that is worth drawing into, in pixels. Some headless X servers
+/// report a screen of zero (or next to zero) size, which would leave nothing to draw.
+pub const MIN_WINDOW_DIMENSION: u32 = 16;
+
+/// The size of the window when the screen is too small to size it from
+pub const DEFAULT_WINDOW_SIZE: (u32, u32) = (800, 600);
+
+/// The size of the window in both logical points and pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplaySize {
+    /// `logical` - The width and height of the window, which is what mouse events use
+    pub logical: (u32, u32),
+    /// `drawable` - The width and height of what is drawn, in pixels
+    pub drawable: (u32, u32),
+}
+
+impl DisplaySize {
+    /// Create a new display size
+    ///
+    /// # Arguments
+    /// * `logical` - The width and height of the window
+    /// * `drawable` - The width and height of what is drawn, in pixels
+    ///
+    /// # Returns
+    /// * `DisplaySize` - The new display size
+    pub fn new(logical: (u32, u32), drawable: (u32, u32)) -> Self {
+        Self { logical, drawable }
+    }
+
+    /// Get the size of a window
+    ///
+    /// # Arguments
+    /// * `window` - The window to get the size of
+    ///
+    /// # Returns
+    /// * `DisplaySize` - The size of the window
+    pub fn from_window(window: &sdl2::video::Window) -> Self {
+        Self::new(window.size(), window.drawable_size())
+    }
+
+    /// Get the width that should be rendered at
+    ///
+    /// # Returns
+    /// * `f32` - The drawable width, in pixels, which is at least 1 so it can be divided by
+    pub fn width(&self) -> f32 {
+        self.drawable.0.max(1) as f32
+    }
+
+    /// Get the height that should be rendered at
+    ///
+    /// # Returns
+    /// * `f32` - The drawable height, in pixels, which is at least 1 so it can be divided by
+    pub fn height(&self) -> f32 {
+        self.drawable.1.max(1) as f32
+    }
+
+    /// Get the aspect ratio the projection should use. This comes from the drawable size, as
+    /// that is what the projection is scaled to fill.
+    ///
+    /// # Returns
+    /// * `f32` - The height divided by the width, which is always finite
+    pub fn aspect_ratio(&self) -> f32 {
+        self.height() / self.width()
+    }
+
+    /// Convert a position in logical points (e.g. the mouse) to pixels
+    ///
+    /// # Arguments
+    /// * `x` - The logical x coordinate
+    /// * `y` - The logical y coordinate
+    ///
+    /// # Returns
+    /// * `(i32, i32)` - The same position in pixels
+    pub fn to_drawable(self, x: i32, y: i32) -> (i32, i32) {
+        (
+            (x as f32 * self.width() / self.logical.0.max(1) as f32) as i32,
+            (y as f32 * self.height() / self.logical.1.max(1) as f32) as i32,
+        )
+    }
+}
+
+/// A rectangle of the screen that geometry is drawn into, e.g. one half of a split screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// `x` - The left edge of the viewport, in pixels
+    pub x: f32,
+    /// `y` - The top edge of the viewport, in pixels
+    pub y: f32,
+    /// `width` - The width of the viewport, in pixels
+    pub width: f32,
+    /// `height` - The height of the viewport, in pixels
+    pub height: f32,
+}
+
+impl Viewport {
+    /// Create a new viewport
+    ///
+    /// # Arguments
+    /// * `x` - The left edge of the viewport, in pixels
+    /// * `y` - The top edge of the viewport, in pixels
+    /// * `width` - The width of the viewport, in pixels
+    /// * `height` - The height of the viewport, in pixels
+    ///
+    /// # Returns
+    /// * `Viewport` - The new viewport
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Create a viewport that covers the whole screen
+    ///
+    /// # Arguments
+    /// * `width` - The width of the screen, in pixels
+    /// * `height` - The height of the screen, in pixels
+    ///
+    /// # Returns
+    /// * `Viewport` - The viewport, starting at the top left corner
+    pub fn full(width: f32, height: f32) -> Self {
+        Self::new(0.0, 0.0, width, height)
+    }
+}
+
+/// Work out a side of the window from the same side of the screen. The window takes up two
+/// thirds of the screen, unless that would be too small to use.
+///
+/// # Arguments
+/// * `screen` - The length of the side of the screen, in pixels
+/// * `fallback` - The length to use if the screen is too small
+///
+/// # Returns
+/// * `u32` - The length of the side of the window, in pixels
+pub fn window_dimension(screen: u32, fallback: u32) -> u32 {
+    let dimension = (screen as f32 / 1.5) as u32;
+
+    if dimension < MIN_WINDOW_DIMENSION {
+        fallback
+    } else {
+        dimension
+    }
+}
+
+/// Parse a window dimension, which has to be a whole number of at least one
+///
+/// # Arguments
+/// * `value` - The text to parse
+///
+/// # Returns
+/// * `Option<u32>` - The dimension, or `None` if it isn't a positive whole number
+pub fn parse_dimension(value: &str) -> Option<u32> {
+    value.trim().parse().ok().filter(|dimension| *dimension > 0)
+}
+
+/// Read a window dimension from an environment variable (e.g. `ENGINE_WIDTH`) so scripted
+/// runs can pick the window size. A variable that is set but invalid is reported and ignored.
+///
+/// # Arguments
+/// * `name` - The name of the environment variable
+///
+/// # Returns
+/// * `Option<u32>` - The dimension, or `None` if the variable isn't set to a valid one
+pub fn dimension_from_env(name: &str) -> Option<u32> {
+    let value = std::env::var(name).ok()?;
+    let dimension = parse_dimension(&value);
+
+    if dimension.is_none() {
+        eprintln!(
+            "Ignoring {}=\"{}\" as it isn't a positive whole number",
+            name, value
+        );
+    }
+
+    dimension
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aspect_ratio_comes_from_the_drawable_size() {
+        // A window that is twice as dense in pixels as it is in points, and a stretched one
+        let retina = DisplaySize::new((800, 600), (1600, 1200));
+        let stretched = DisplaySize::new((800, 600), (1600, 600));
+
+        assert_eq!(retina.aspect_ratio(), 0.75);
+        assert_eq!(stretched.aspect_ratio(), 0.375);
+        assert_eq!((retina.width(), retina.height()), (1600.0, 1200.0));
+    }
+
+    #[test]
+    fn logical_positions_scale_to_pixels() {
+        let display = DisplaySize::new((800, 600), (1600, 1200));
+
+        assert_eq!(display.to_drawable(400, 300), (800, 600));
+        assert_eq!(display.to_drawable(0, 0), (0, 0));
+    }
+
+    #[test]
+    fn dimension_must_be_a_positive_whole_number() {
+        assert_eq!(parse_dimension("1280"), Some(1280));
+        assert_eq!(parse_dimension(" 720\n"), Some(720));
+        assert_eq!(parse_dimension("0"), None);
+        assert_eq!(parse_dimension("-640"), None);
+        assert_eq!(parse_dimension("12.5"), None);
+        assert_eq!(parse_dimension("wide"), None);
+        assert_eq!(parse_dimension(""), None);
+    }
+
+    #[test]
+    fn dimension_from_env_falls_back_when_unset_or_invalid() {
+        // These variables are only used here, so setting them can't upset other tests
+        std::env::set_var("ENGINE_TEST_VALID_WIDTH", "1024");
+        std::env::set_var("ENGINE_TEST_INVALID_WIDTH", "big");
+        std::env::remove_var("ENGINE_TEST_UNSET_WIDTH");
+
+        assert_eq!(dimension_from_env("ENGINE_TEST_VALID_WIDTH"), Some(1024));
+        assert_eq!(dimension_from_env("ENGINE_TEST_INVALID_WIDTH"), None);
+        assert_eq!(dimension_from_env("ENGINE_TEST_UNSET_WIDTH"), None);
+        assert_eq!(
+            dimension_from_env("ENGINE_TEST_UNSET_WIDTH")
+                .unwrap_or_else(|| window_dimension(1920, DEFAULT_WINDOW_SIZE.0)),
+            1280
+        );
+    }
+
+    #[test]
+    fn zero_sized_display_has_a_finite_aspect_ratio() {
+        for display in [
+            DisplaySize::new((800, 0), (800, 0)),
+            DisplaySize::new((0, 600), (0, 600)),
+            DisplaySize::new((0, 0), (0, 0)),
+        ] {
+            assert!(display.aspect_ratio().is_finite());
+            assert!(display.aspect_ratio() > 0.0);
+        }
+    }
+
+    #[test]
+    fn tiny_screens_fall_back_to_the_default_window() {
+        assert_eq!(window_dimension(0, DEFAULT_WINDOW_SIZE.1), 600);
+        assert_eq!(
+            window_dimension(MIN_WINDOW_DIMENSION, DEFAULT_WINDOW_SIZE.0),
+            800
+        );
+        assert_eq!(window_dimension(1080, DEFAULT_WINDOW_SIZE.1), 720);
+    }
+}
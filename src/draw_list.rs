@@ -0,0 +1,180 @@
+//! Contains the draw list, which keeps the back to front order of a mesh's
+//! triangles between frames so static geometry isn't sorted every frame
+
+use crate::{Mesh, Triangle, Vector3D};
+
+/// The order to draw a mesh's triangles in, furthest from the camera first so nearer
+/// triangles are drawn over them. The order is only worked out again once the camera has
+/// moved far enough or the list has been invalidated.
+pub struct DrawList {
+    /// `threshold` - How far the camera can move before the order is sorted again
+    pub threshold: f32,
+    /// `order` - The indices of the mesh's triangles, furthest first
+    order: Vec<usize>,
+    /// `sorted_from` - Where the camera was when the order was last sorted, if it is valid
+    sorted_from: Option<Vector3D>,
+    /// `sorts` - How many times the order has been sorted
+    sorts: usize,
+}
+
+impl DrawList {
+    /// Create a new, empty draw list
+    ///
+    /// # Arguments
+    /// * `threshold` - How far the camera can move before the order is sorted again
+    ///
+    /// # Returns
+    /// * `DrawList` - The new draw list
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            order: Vec::new(),
+            sorted_from: None,
+            sorts: 0,
+        }
+    }
+
+    /// Throw away the order so it is sorted again on the next draw. This must be done
+    /// whenever the mesh's triangles change.
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn invalidate(&mut self) -> () {
+        self.sorted_from = None;
+    }
+
+    /// Get how many times the order has been sorted, which shows how much work the cache is
+    /// saving
+    ///
+    /// # Returns
+    /// * `usize` - The amount of sorts so far
+    pub fn sorts(&self) -> usize {
+        self.sorts
+    }
+
+    /// Get the order to draw a mesh's triangles in, sorting them first if the camera has
+    /// moved too far or the order is out of date
+    ///
+    /// # Arguments
+    /// * `mesh` - The mesh being drawn
+    /// * `camera` - Where the camera is
+    ///
+    /// # Returns
+    /// * `&[usize]` - The indices of the mesh's triangles, furthest from the camera first
+    pub fn order(&mut self, mesh: &Mesh, camera: &Vector3D) -> &[usize] {
+        let stale = match self.sorted_from {
+            Some(sorted_from) => {
+                sorted_from.distance(camera) > self.threshold || self.order.len() != mesh.mat.len()
+            }
+            None => true,
+        };
+
+        if stale {
+            let centroid =
+                |triangle: &Triangle| (triangle.a + triangle.b + triangle.c) * (1.0 / 3.0);
+            let distances: Vec<f32> = mesh
+                .mat
+                .iter()
+                .map(|triangle| centroid(triangle).distance(camera))
+                .collect();
+
+            self.order = (0..mesh.mat.len()).collect();
+            self.order
+                .sort_by(|&i, &j| distances[j].total_cmp(&distances[i]));
+            self.sorted_from = Some(*camera);
+            self.sorts += 1;
+        }
+
+        &self.order
+    }
+}
+
+impl Default for DrawList {
+    /// Create an empty draw list that sorts again once the camera moves a tenth of a unit
+    ///
+    /// # Returns
+    /// * `DrawList` - The default draw list
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube;
+    use crate::Matrix;
+
+    #[test]
+    fn stationary_camera_sorts_once() {
+        let mesh = cube::get_cube_mesh();
+        let camera = Vector3D::new(0.5, 0.5, -3.0);
+        let mut draw_list = DrawList::default();
+
+        let first = draw_list.order(&mesh, &camera).to_vec();
+        for _ in 0..10 {
+            assert_eq!(draw_list.order(&mesh, &camera), &first[..]);
+        }
+
+        assert_eq!(draw_list.sorts(), 1);
+    }
+
+    #[test]
+    fn order_is_furthest_first() {
+        let mesh = cube::get_cube_mesh();
+        let camera = Vector3D::new(0.5, 0.5, -3.0);
+        let order = DrawList::default().order(&mesh, &camera).to_vec();
+        let distance = |index: usize| {
+            let triangle = &mesh.mat[index];
+            ((triangle.a + triangle.b + triangle.c) * (1.0 / 3.0)).distance(&camera)
+        };
+
+        assert_eq!(order.len(), mesh.mat.len());
+        assert!(order
+            .windows(2)
+            .all(|pair| distance(pair[0]) >= distance(pair[1])));
+        // The north face is the furthest from the camera and the south face the nearest
+        assert!(order[..2].contains(&4) && order[..2].contains(&5));
+        assert!(order[10..].contains(&0) && order[10..].contains(&1));
+    }
+
+    #[test]
+    fn turned_mesh_is_sorted_with_the_camera_in_its_own_space() {
+        let mesh = cube::get_cube_mesh();
+        let model = Matrix::rotation_y(0.7).multiply(&Matrix::rotation_x(0.3));
+        let camera = Vector3D::new(0.5, 0.5, -3.0);
+        let camera_in_mesh = model.inverse().unwrap().transform_point(&camera);
+
+        assert_eq!(
+            DrawList::default().order(&mesh, &camera_in_mesh),
+            DrawList::default().order(&mesh.transformed(&model), &camera)
+        );
+    }
+
+    #[test]
+    fn moving_or_invalidating_sorts_again() {
+        let mesh = cube::get_cube_mesh();
+        let mut draw_list = DrawList::new(0.5);
+
+        draw_list.order(&mesh, &Vector3D::new(0.0, 0.0, -3.0));
+        draw_list.order(&mesh, &Vector3D::new(0.3, 0.0, -3.0));
+        assert_eq!(draw_list.sorts(), 1);
+
+        draw_list.order(&mesh, &Vector3D::new(1.0, 0.0, -3.0));
+        assert_eq!(draw_list.sorts(), 2);
+
+        draw_list.invalidate();
+        draw_list.order(&mesh, &Vector3D::new(1.0, 0.0, -3.0));
+        assert_eq!(draw_list.sorts(), 3);
+
+        let mut smaller = mesh.clone();
+        smaller.mat.truncate(6);
+        assert_eq!(
+            draw_list
+                .order(&smaller, &Vector3D::new(1.0, 0.0, -3.0))
+                .len(),
+            6
+        );
+        assert_eq!(draw_list.sorts(), 4);
+    }
+}
@@ -0,0 +1,139 @@
+//! Contains easing functions for animations. Each one takes how far through
+//! the animation it is (from 0 to 1) and returns how far along the motion
+//! should be, which is also 0 at the start and 1 at the end.
+
+use std::f32::consts::PI;
+
+/// Move at a constant speed
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn linear(t: f32) -> f32 {
+    t
+}
+
+/// Start slowly and speed up
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn ease_in_quad(t: f32) -> f32 {
+    t * t
+}
+
+/// Start quickly and slow down
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn ease_out_quad(t: f32) -> f32 {
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+/// Speed up through the first half and slow down through the second
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn ease_in_out_quad(t: f32) -> f32 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+    }
+}
+
+/// Start slowly and speed up, more sharply than the quadratic version
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn ease_in_cubic(t: f32) -> f32 {
+    t * t * t
+}
+
+/// Start quickly and slow down, more sharply than the quadratic version
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Speed up through the first half and slow down through the second, more sharply than the
+/// quadratic version. This is a good default for camera moves.
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+/// Speed up and slow down following a sine wave, which is the gentlest of the ease in-outs
+///
+/// # Arguments
+/// * `t` - How far through the animation, from 0 to 1
+///
+/// # Returns
+/// * `f32` - How far along the motion, from 0 to 1
+pub fn ease_in_out_sine(t: f32) -> f32 {
+    -((PI * t).cos() - 1.0) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An easing function
+    type Easing = fn(f32) -> f32;
+
+    /// Every easing function, with its name for failure messages
+    const EASINGS: [(&str, Easing); 8] = [
+        ("linear", linear),
+        ("ease_in_quad", ease_in_quad),
+        ("ease_out_quad", ease_out_quad),
+        ("ease_in_out_quad", ease_in_out_quad),
+        ("ease_in_cubic", ease_in_cubic),
+        ("ease_out_cubic", ease_out_cubic),
+        ("ease_in_out_cubic", ease_in_out_cubic),
+        ("ease_in_out_sine", ease_in_out_sine),
+    ];
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for (name, easing) in EASINGS {
+            assert!(easing(0.0).abs() < 1e-6, "{}", name);
+            assert!((easing(1.0) - 1.0).abs() < 1e-6, "{}", name);
+        }
+    }
+
+    #[test]
+    fn easings_never_go_backwards() {
+        for (name, easing) in EASINGS {
+            let values: Vec<f32> = (0..=100).map(|i| easing(i as f32 / 100.0)).collect();
+
+            assert!(values.windows(2).all(|pair| pair[1] >= pair[0]), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,432 @@
+//! Contains the software framebuffer, which is drawn into on the CPU and
+//! then shown through an SDL texture. Having the pixels in memory is what
+//! makes post-processing possible.
+
+use crate::raster;
+use crate::target::RenderTarget;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Point;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+/// A block of pixels in memory that can be drawn into
+pub struct Framebuffer {
+    /// `width` - The amount of pixels in each row
+    pub width: u32,
+    /// `height` - The amount of rows
+    pub height: u32,
+    /// `pixels` - The colour of each pixel, row by row from the top left
+    pub pixels: Vec<Color>,
+    /// `draw_color` - The colour draw calls use
+    draw_color: Color,
+    /// `origin` - The screen position of the top left pixel, which is only away from the
+    /// origin for a piece cut out of a bigger framebuffer with `region`
+    origin: (i32, i32),
+}
+
+impl Framebuffer {
+    /// Create a new framebuffer, filled with black
+    ///
+    /// # Arguments
+    /// * `width` - The amount of pixels in each row
+    /// * `height` - The amount of rows
+    ///
+    /// # Returns
+    /// * `Framebuffer` - The new framebuffer
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::RGB(0, 0, 0); (width * height) as usize],
+            draw_color: Color::RGB(255, 255, 255),
+            origin: (0, 0),
+        }
+    }
+
+    /// Copy a rectangle of pixels out into a framebuffer of its own, which keeps using the
+    /// same screen coordinates (e.g. so a tile of the screen can be drawn into on its own)
+    ///
+    /// # Arguments
+    /// * `x` - The column of the top left pixel of the rectangle
+    /// * `y` - The row of the top left pixel of the rectangle
+    /// * `width` - The width of the rectangle, which must fit in the framebuffer
+    /// * `height` - The height of the rectangle, which must fit in the framebuffer
+    ///
+    /// # Returns
+    /// * `Framebuffer` - The copy of the rectangle
+    pub fn region(&self, x: i32, y: i32, width: u32, height: u32) -> Framebuffer {
+        let pixels = (y..y + height as i32)
+            .flat_map(|row| (x..x + width as i32).map(move |column| (column, row)))
+            .map(|(column, row)| self.get(column, row).unwrap_or(Color::RGB(0, 0, 0)))
+            .collect();
+
+        Framebuffer {
+            width,
+            height,
+            pixels,
+            draw_color: self.draw_color,
+            origin: (x, y),
+        }
+    }
+
+    /// Copy the pixels of a region (made with `region`) back to where they came from
+    ///
+    /// # Arguments
+    /// * `region` - The region to copy back
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn copy_region(&mut self, region: &Framebuffer) -> () {
+        let (x, y) = region.origin;
+
+        for row in 0..region.height as i32 {
+            for column in 0..region.width as i32 {
+                if let Some(color) = region.get(x + column, y + row) {
+                    self.set(x + column, y + row, color);
+                }
+            }
+        }
+    }
+
+    /// Get the colour of a pixel
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    ///
+    /// # Returns
+    /// * `Option<Color>` - The colour of the pixel, if it is inside the framebuffer
+    pub fn get(&self, x: i32, y: i32) -> Option<Color> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    /// Set the colour of a pixel, ignoring pixels outside the framebuffer
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    /// * `color` - The new colour of the pixel
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn set(&mut self, x: i32, y: i32, color: Color) -> () {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// Get where a pixel is stored
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    ///
+    /// # Returns
+    /// * `Option<usize>` - The index of the pixel, if it is inside the framebuffer
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (x, y) = (x - self.origin.0, y - self.origin.1);
+
+        if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
+            Some((y as u32 * self.width + x as u32) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Replace the pixels with ones read from bytes (e.g. read back from the canvas)
+    ///
+    /// # Arguments
+    /// * `bytes` - Four bytes to a pixel in red, green, blue, alpha order, row by row from the
+    ///   top left. Any pixels the bytes don't reach are left as they are.
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn load_rgba_bytes(&mut self, bytes: &[u8]) -> () {
+        for (pixel, rgba) in self.pixels.iter_mut().zip(bytes.chunks_exact(4)) {
+            *pixel = Color::RGBA(rgba[0], rgba[1], rgba[2], rgba[3]);
+        }
+    }
+
+    /// Get the pixels as bytes, four to a pixel in red, green, blue, alpha order
+    ///
+    /// # Returns
+    /// * `Vec<u8>` - The bytes of every pixel
+    pub fn to_rgba_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b, color.a])
+            .collect()
+    }
+}
+
+impl RenderTarget for Framebuffer {
+    /// Set the colour that the following draw calls use
+    ///
+    /// # Arguments
+    /// * `color` - The colour to draw with
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn set_draw_color(&mut self, color: Color) -> () {
+        self.draw_color = color;
+    }
+
+    /// Fill the whole framebuffer with the draw colour
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn clear(&mut self) -> () {
+        let color = self.draw_color;
+        self.pixels.iter_mut().for_each(|pixel| *pixel = color);
+    }
+
+    /// Draw a line between two points with Bresenham's algorithm. The line is clipped to the
+    /// framebuffer first, so only the pixels that can be seen are walked however far away
+    /// the ends are.
+    ///
+    /// # Arguments
+    /// * `start` - The start of the line
+    /// * `end` - The end of the line
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_line(&mut self, start: Point, end: Point) -> () {
+        // Clipping works from the top left of the framebuffer, wherever that is on screen
+        let (origin_x, origin_y) = (self.origin.0 as i64, self.origin.1 as i64);
+        let Some(((mut x, mut y), end)) = raster::clip_line(
+            (start.x() as i64 - origin_x, start.y() as i64 - origin_y),
+            (end.x() as i64 - origin_x, end.y() as i64 - origin_y),
+            self.width,
+            self.height,
+        ) else {
+            return;
+        };
+        let (dx, dy) = ((end.0 - x).abs(), -(end.1 - y).abs());
+        let (step_x, step_y) = ((end.0 - x).signum(), (end.1 - y).signum());
+        let mut error = dx + dy;
+
+        loop {
+            // Clipping keeps every step inside the framebuffer, so this can't truncate
+            self.set(
+                (x + origin_x) as i32,
+                (y + origin_y) as i32,
+                self.draw_color,
+            );
+
+            if (x, y) == end {
+                break;
+            }
+
+            let doubled = 2 * error;
+
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draw a single point
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_point(&mut self, point: Point) -> () {
+        self.set(point.x(), point.y(), self.draw_color);
+    }
+
+    /// Draw a single point that only partly covers its pixel, blending it with the pixel's
+    /// colour
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    /// * `coverage` - How much of the pixel is covered, from 0 to 1
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn blend_point(&mut self, point: Point, coverage: f32) -> () {
+        if let Some(background) = self.get(point.x(), point.y()) {
+            let color = raster::blend(self.draw_color, background, coverage);
+            self.set(point.x(), point.y(), color);
+        }
+    }
+}
+
+/// Renders off-screen into a framebuffer, which is then copied into an SDL streaming texture
+/// and drawn over the whole canvas. Any post-processing is done to the framebuffer in between.
+pub struct TextureTarget<'a> {
+    /// `framebuffer` - The pixels that are drawn into
+    pub framebuffer: Framebuffer,
+    /// `texture` - The texture the framebuffer is copied into to be shown
+    texture: Texture<'a>,
+}
+
+impl<'a> TextureTarget<'a> {
+    /// Create a new texture target
+    ///
+    /// # Arguments
+    /// * `texture_creator` - The texture creator of the canvas the target will be shown on
+    /// * `width` - The width of the target
+    /// * `height` - The height of the target
+    ///
+    /// # Returns
+    /// * `Result<TextureTarget, String>` - The new target, or why the texture couldn't be made
+    pub fn new(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, String> {
+        let texture = texture_creator
+            .create_texture_streaming(PixelFormatEnum::RGBA32, width, height)
+            .map_err(|error| error.to_string())?;
+
+        Ok(Self {
+            framebuffer: Framebuffer::new(width, height),
+            texture,
+        })
+    }
+
+    /// Post-process the framebuffer, then show it over the whole canvas
+    ///
+    /// # Arguments
+    /// * `canvas` - The canvas to show the framebuffer on
+    /// * `post_process` - What to do to the pixels before they are shown, if anything
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Nothing, or why the texture couldn't be updated or drawn
+    pub fn present(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        post_process: Option<&dyn Fn(&mut Framebuffer)>,
+    ) -> Result<(), String> {
+        if let Some(post_process) = post_process {
+            post_process(&mut self.framebuffer);
+        }
+
+        self.texture
+            .update(
+                None,
+                &self.framebuffer.to_rgba_bytes(),
+                self.framebuffer.width as usize * 4,
+            )
+            .map_err(|error| error.to_string())?;
+
+        canvas.copy(&self.texture, None, None)
+    }
+}
+
+impl<'a> RenderTarget for TextureTarget<'a> {
+    /// Set the colour that the following draw calls use
+    ///
+    /// # Arguments
+    /// * `color` - The colour to draw with
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn set_draw_color(&mut self, color: Color) -> () {
+        self.framebuffer.set_draw_color(color);
+    }
+
+    /// Fill the whole target with the draw colour
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn clear(&mut self) -> () {
+        self.framebuffer.clear();
+    }
+
+    /// Draw a line between two points
+    ///
+    /// # Arguments
+    /// * `start` - The start of the line
+    /// * `end` - The end of the line
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_line(&mut self, start: Point, end: Point) -> () {
+        self.framebuffer.draw_line(start, end);
+    }
+
+    /// Draw a single point
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_point(&mut self, point: Point) -> () {
+        self.framebuffer.draw_point(point);
+    }
+
+    /// Draw a single point that only partly covers its pixel
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    /// * `coverage` - How much of the pixel is covered, from 0 to 1
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn blend_point(&mut self, point: Point, coverage: f32) -> () {
+        self.framebuffer.blend_point(point, coverage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_calls_land_in_the_pixels() {
+        let mut framebuffer = Framebuffer::new(8, 6);
+
+        framebuffer.set_draw_color(Color::RGB(0, 0, 255));
+        framebuffer.clear();
+        framebuffer.set_draw_color(Color::RGB(255, 0, 0));
+        framebuffer.draw_line(Point::new(1, 2), Point::new(6, 2));
+        framebuffer.draw_point(Point::new(3, 4));
+
+        for x in 1..=6 {
+            assert_eq!(framebuffer.get(x, 2), Some(Color::RGB(255, 0, 0)));
+        }
+        assert_eq!(framebuffer.get(3, 4), Some(Color::RGB(255, 0, 0)));
+        assert_eq!(framebuffer.get(0, 0), Some(Color::RGB(0, 0, 255)));
+        assert_eq!(framebuffer.get(8, 0), None);
+
+        // These are the bytes that get copied into the texture when it is presented
+        let bytes = framebuffer.to_rgba_bytes();
+        assert_eq!(bytes.len(), 8 * 6 * 4);
+        assert_eq!(&bytes[..4], &[0, 0, 255, 255]);
+        assert_eq!(&bytes[(2 * 8 + 1) * 4..(2 * 8 + 2) * 4], &[255, 0, 0, 255]);
+
+        let mut copy = Framebuffer::new(8, 6);
+        copy.load_rgba_bytes(&bytes);
+        assert!(copy.pixels == framebuffer.pixels);
+    }
+
+    #[test]
+    fn far_off_line_is_clipped_to_the_framebuffer() {
+        let mut framebuffer = Framebuffer::new(10, 10);
+
+        // Without clipping this would walk around two billion pixels
+        framebuffer.draw_line(Point::new(-1_000_000_000, 5), Point::new(1_000_000_000, 5));
+
+        for x in 0..10 {
+            assert_eq!(framebuffer.get(x, 5), Some(Color::RGB(255, 255, 255)));
+        }
+        assert_eq!(framebuffer.get(5, 4), Some(Color::RGB(0, 0, 0)));
+        assert_eq!(
+            raster::clip_line((-1_000_000_000, 5), (1_000_000_000, 5), 10, 10),
+            Some(((0, 5), (9, 5)))
+        );
+        assert_eq!(
+            raster::clip_line((-1_000_000_000, -5), (1_000_000_000, -5), 10, 10),
+            None
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Contains the ground grid - lines across the XZ plane around the camera
+//! that fade into the background with distance, to give the scene a floor
+
+use crate::background::Background;
+use crate::camera::View;
+use crate::target::RenderTarget;
+use crate::{project_to_screen, raster, Vector3D};
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+
+/// A flat grid of lines that looks endless, as it is laid out around the camera and fades
+/// away before it ends
+pub struct Grid {
+    /// `height` - The y coordinate of the plane the grid lies on
+    pub height: f32,
+    /// `spacing` - The distance between neighbouring lines
+    pub spacing: f32,
+    /// `fade_distance` - How far from the camera the grid fades out completely
+    pub fade_distance: f32,
+    /// `color` - The colour of the lines closest to the camera
+    pub color: Color,
+}
+
+impl Grid {
+    /// Create a new grid
+    ///
+    /// # Arguments
+    /// * `height` - The y coordinate of the plane the grid lies on
+    /// * `spacing` - The distance between neighbouring lines
+    /// * `fade_distance` - How far from the camera the grid fades out completely
+    /// * `color` - The colour of the lines closest to the camera
+    ///
+    /// # Returns
+    /// * `Grid` - The new grid
+    pub fn new(height: f32, spacing: f32, fade_distance: f32, color: Color) -> Self {
+        Self {
+            height,
+            spacing,
+            fade_distance,
+            color,
+        }
+    }
+
+    /// Get how visible the grid is at a distance from the camera
+    ///
+    /// # Arguments
+    /// * `distance` - The distance from the camera
+    ///
+    /// # Returns
+    /// * `f32` - How visible the grid is, from 1 at the camera down to 0 at `fade_distance`
+    pub fn fade(&self, distance: f32) -> f32 {
+        (1.0 - distance / self.fade_distance).clamp(0.0, 1.0)
+    }
+
+    /// Get the pieces of grid line around the camera, each one grid cell long so that the
+    /// fade can change along a line. Pieces can be behind the camera, so they need clipping
+    /// before they are projected.
+    ///
+    /// # Arguments
+    /// * `camera` - Where the camera is
+    ///
+    /// # Returns
+    /// * `Vec<(Vector3D, Vector3D, f32)>` - The start, end and fade of each visible piece
+    pub fn segments(&self, camera: &Vector3D) -> Vec<(Vector3D, Vector3D, f32)> {
+        let cells = (self.fade_distance / self.spacing).ceil() as i32;
+        // Snap to the grid so the lines stay put as the camera moves
+        let (first_x, first_z) = (
+            (camera.x / self.spacing).round() as i32 - cells,
+            (camera.z / self.spacing).round() as i32 - cells,
+        );
+
+        let mut segments = Vec::new();
+
+        for i in 0..=cells * 2 {
+            for j in 0..cells * 2 {
+                let (x, z) = (
+                    (first_x + i) as f32 * self.spacing,
+                    (first_z + j) as f32 * self.spacing,
+                );
+
+                // A piece of the line running along z, then one running along x
+                for (start, end) in [
+                    (
+                        Vector3D::new(x, self.height, z),
+                        Vector3D::new(x, self.height, z + self.spacing),
+                    ),
+                    (
+                        Vector3D::new(x, self.height, z),
+                        Vector3D::new(x + self.spacing, self.height, z),
+                    ),
+                ] {
+                    let middle = start.midpoint(&end);
+                    let fade = self.fade(
+                        ((middle.x - camera.x).powi(2) + (middle.z - camera.z).powi(2)).sqrt(),
+                    );
+
+                    if fade > 0.0 {
+                        segments.push((start, end, fade));
+                    }
+                }
+            }
+        }
+
+        segments
+    }
+
+    /// Draw the grid, blending each piece into the background behind it by its fade. This
+    /// should be done after the background and before the scene. Each piece is clipped
+    /// against the near plane, so pieces passing behind the camera are cut short.
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the grid on
+    /// * `camera` - Where the camera is
+    /// * `background` - The background the grid fades into
+    /// * `view` - How the camera sees the scene
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        camera: &Vector3D,
+        background: &Background,
+        view: &View,
+    ) -> () {
+        for (start, end, fade) in self.segments(camera) {
+            let Some((start, end)) = view.clip_line(&start, &end) else {
+                continue;
+            };
+            let start = project_to_screen(&start, &view.projection, view.width, view.height);
+            let end = project_to_screen(&end, &view.projection, view.width, view.height);
+            let row = (start.y + end.y) * 0.5;
+
+            target.set_draw_color(raster::blend(
+                self.color,
+                background.color_at_row(row.clamp(0.0, view.height) as u32, view.height as u32),
+                fade,
+            ));
+            target.draw_line(
+                Point::new(start.x as i32, start.y as i32),
+                Point::new(end.x as i32, end.y as i32),
+            );
+        }
+    }
+}
+
+impl Default for Grid {
+    /// Create a grey grid just below the cube, with lines half a unit apart that fade out
+    /// ten units away
+    ///
+    /// # Returns
+    /// * `Grid` - The default grid
+    fn default() -> Self {
+        Self::new(1.0, 0.5, 10.0, Color::RGB(120, 120, 120))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::target::RecordingTarget;
+    use crate::{multiply_matrix_vector, Matrix};
+
+    #[test]
+    fn fade_runs_from_the_camera_to_the_fade_distance() {
+        let grid = Grid::default();
+
+        assert_eq!(grid.fade(0.0), 1.0);
+        assert_eq!(grid.fade(2.5), 0.75);
+        assert_eq!(grid.fade(5.0), 0.5);
+        assert_eq!(grid.fade(10.0), 0.0);
+        assert_eq!(grid.fade(25.0), 0.0);
+    }
+
+    #[test]
+    fn segments_fade_out_around_the_camera() {
+        let grid = Grid::default();
+        let camera = Vector3D::new(3.2, -4.0, 7.9);
+        let segments = grid.segments(&camera);
+
+        assert!(!segments.is_empty());
+        for (start, end, fade) in segments {
+            let middle = start.midpoint(&end);
+            let distance = ((middle.x - camera.x).powi(2) + (middle.z - camera.z).powi(2)).sqrt();
+
+            assert_eq!((start.y, end.y), (grid.height, grid.height));
+            assert!(((end - start).length() - grid.spacing).abs() < 1e-5);
+            assert!(fade > 0.0 && fade <= 1.0);
+            assert!((fade - grid.fade(distance)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn segments_behind_the_camera_are_not_drawn() {
+        let grid = Grid::default();
+        let camera = Camera::default();
+        let projection = Matrix::projection(90.0, 150.0 / 200.0, 0.1, 100.0);
+        let view = View::new(&camera, &projection, 0.1, 200.0, 150.0);
+        let segments = grid.segments(&camera.position);
+        let in_front = |v: &Vector3D| {
+            let mut view_space = Vector3D::default();
+            multiply_matrix_vector(v, &mut view_space, &view.matrix);
+
+            view_space.z >= view.near
+        };
+        let visible = segments
+            .iter()
+            .filter(|(start, end, _)| in_front(start) || in_front(end))
+            .count();
+
+        let mut target = RecordingTarget::new();
+        grid.draw(
+            &mut target,
+            &camera.position,
+            &Background::Solid(Color::RGB(0, 0, 0)),
+            &view,
+        );
+
+        assert!(visible > 0 && visible < segments.len());
+        assert_eq!(target.lines.len(), visible);
+        for (_, _, color) in &target.lines {
+            // Grey faded into black is a darker grey
+            assert!(color.r == color.g && color.g == color.b && color.r <= 120);
+        }
+    }
+}
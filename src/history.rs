@@ -0,0 +1,208 @@
+//! Contains the edit history, so that changes to the transforms of objects
+//! can be undone and redone
+
+use crate::animation::Transform;
+use std::collections::VecDeque;
+
+/// How many edits are remembered by default before the oldest are forgotten
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// A change to the transform of one object
+#[derive(Debug, Clone, Copy)]
+pub struct TransformEdit {
+    /// `object` - The index of the object that was changed
+    pub object: usize,
+    /// `before` - The transform of the object before the change
+    pub before: Transform,
+    /// `after` - The transform of the object after the change
+    pub after: Transform,
+}
+
+/// The edits that can be undone and redone, oldest first
+pub struct TransformHistory {
+    /// `limit` - How many edits can be undone before the oldest are forgotten
+    pub limit: usize,
+    /// `undo` - The edits that can be undone, with the most recent at the back
+    undo: VecDeque<TransformEdit>,
+    /// `redo` - The edits that have been undone and can be redone, with the most recently
+    /// undone at the back
+    redo: Vec<TransformEdit>,
+}
+
+impl TransformHistory {
+    /// Create a new, empty history
+    ///
+    /// # Arguments
+    /// * `limit` - How many edits can be undone before the oldest are forgotten
+    ///
+    /// # Returns
+    /// * `TransformHistory` - The new history
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Change the transform of an object and remember the change. Anything that was undone
+    /// can't be redone after this, as the history has moved on from it.
+    ///
+    /// # Arguments
+    /// * `transforms` - The transform of every object
+    /// * `object` - The index of the object to change
+    /// * `transform` - The new transform of the object
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn apply(
+        &mut self,
+        transforms: &mut [Transform],
+        object: usize,
+        transform: Transform,
+    ) -> () {
+        let edit = TransformEdit {
+            object,
+            before: transforms[object],
+            after: transform,
+        };
+
+        transforms[object] = transform;
+        self.redo.clear();
+
+        if self.limit == 0 {
+            return;
+        }
+        if self.undo.len() == self.limit {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(edit);
+    }
+
+    /// Undo the most recent edit, putting the object back how it was before it
+    ///
+    /// # Arguments
+    /// * `transforms` - The transform of every object
+    ///
+    /// # Returns
+    /// * `Option<usize>` - The index of the object that was changed, or `None` if there was
+    ///   nothing to undo
+    pub fn undo(&mut self, transforms: &mut [Transform]) -> Option<usize> {
+        let edit = self.undo.pop_back()?;
+
+        transforms[edit.object] = edit.before;
+        self.redo.push(edit);
+
+        Some(edit.object)
+    }
+
+    /// Redo the most recently undone edit
+    ///
+    /// # Arguments
+    /// * `transforms` - The transform of every object
+    ///
+    /// # Returns
+    /// * `Option<usize>` - The index of the object that was changed, or `None` if there was
+    ///   nothing to redo
+    pub fn redo(&mut self, transforms: &mut [Transform]) -> Option<usize> {
+        let edit = self.redo.pop()?;
+
+        transforms[edit.object] = edit.after;
+        self.undo.push_back(edit);
+
+        Some(edit.object)
+    }
+
+    /// Check if there is an edit to undo
+    ///
+    /// # Returns
+    /// * `bool` - Whether `undo` would change anything
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Check if there is an edit to redo
+    ///
+    /// # Returns
+    /// * `bool` - Whether `redo` would change anything
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+impl Default for TransformHistory {
+    /// Create an empty history that remembers `DEFAULT_HISTORY_LIMIT` edits
+    ///
+    /// # Returns
+    /// * `TransformHistory` - The default history
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vector3D;
+
+    /// Check that two transforms are exactly the same
+    fn assert_same(a: &Transform, b: &Transform) {
+        assert_eq!(a.translation, b.translation);
+        assert_eq!(a.rotation, b.rotation);
+        assert_eq!(a.scale, b.scale);
+    }
+
+    /// A transform with an awkward translation, which rounding would change
+    fn moved(x: f32) -> Transform {
+        Transform::new(
+            Vector3D::new(x, 0.1, -0.3),
+            Vector3D::new(0.0, 0.7, 0.0),
+            Vector3D::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn undo_restores_exactly_and_redo_reapplies() {
+        let original = moved(1.0 / 3.0);
+        let mut transforms = vec![moved(5.0), original];
+        let mut history = TransformHistory::default();
+
+        history.apply(&mut transforms, 1, moved(2.0));
+        assert_eq!(history.undo(&mut transforms), Some(1));
+        assert_same(&transforms[1], &original);
+        assert_same(&transforms[0], &moved(5.0));
+
+        assert_eq!(history.redo(&mut transforms), Some(1));
+        assert_same(&transforms[1], &moved(2.0));
+        assert!(history.can_undo() && !history.can_redo());
+    }
+
+    #[test]
+    fn history_forgets_the_oldest_edits_past_its_limit() {
+        let mut transforms = vec![moved(0.0)];
+        let mut history = TransformHistory::new(2);
+
+        for x in 1..=3 {
+            history.apply(&mut transforms, 0, moved(x as f32));
+        }
+
+        assert!(history.undo(&mut transforms).is_some());
+        assert!(history.undo(&mut transforms).is_some());
+        assert!(history.undo(&mut transforms).is_none());
+        assert_same(&transforms[0], &moved(1.0));
+    }
+
+    #[test]
+    fn a_new_edit_clears_the_redo() {
+        let mut transforms = vec![moved(0.0)];
+        let mut history = TransformHistory::default();
+
+        history.apply(&mut transforms, 0, moved(1.0));
+        history.undo(&mut transforms);
+        history.apply(&mut transforms, 0, moved(2.0));
+
+        assert!(!history.can_redo());
+        assert!(history.redo(&mut transforms).is_none());
+        assert_same(&transforms[0], &moved(2.0));
+    }
+}
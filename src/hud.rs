@@ -0,0 +1,109 @@
+//! Contains the diagnostic HUD. There is no font shipped with the engine,
+//! so the HUD is shown in the window title instead of on the canvas.
+
+use crate::light::Light;
+use crate::stats::RenderStats;
+
+/// The diagnostic HUD, showing how long frames take and how much is being drawn
+pub struct Hud {
+    /// `visible` - Whether the HUD is shown at all, so screenshots can be taken without it
+    pub visible: bool,
+}
+
+impl Hud {
+    /// Create a new HUD
+    ///
+    /// # Arguments
+    /// * `visible` - Whether the HUD starts off shown
+    ///
+    /// # Returns
+    /// * `Hud` - The new HUD
+    pub fn new(visible: bool) -> Self {
+        Self { visible }
+    }
+
+    /// Show the HUD if it is hidden, or hide it if it is shown
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn toggle(&mut self) -> () {
+        self.visible = !self.visible;
+    }
+
+    /// Get the text the HUD should show
+    ///
+    /// # Arguments
+    /// * `stats` - The stats of the last frame
+    /// * `light` - The light the scene is lit by
+    ///
+    /// # Returns
+    /// * `String` - The text of the HUD
+    pub fn text(&self, stats: &RenderStats, light: &Light) -> String {
+        let triangles = match stats.triangle_cap {
+            Some(cap) => format!("{} / {} triangles", stats.triangles, cap),
+            None => format!("{} triangles", stats.triangles),
+        };
+
+        format!(
+            "{:.2} ms | {} | light ({:.2}, {:.2}, {:.2})",
+            stats.total.as_secs_f32() * 1000.0,
+            triangles,
+            light.direction.x,
+            light.direction.y,
+            light.direction.z
+        )
+    }
+
+    /// Draw the HUD, or just the plain title if it is hidden
+    ///
+    /// # Arguments
+    /// * `canvas` - The canvas whose window the HUD is shown on
+    /// * `title` - The title of the window without the HUD
+    /// * `stats` - The stats of the last frame
+    /// * `light` - The light the scene is lit by
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw(
+        &self,
+        canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+        title: &str,
+        stats: &RenderStats,
+        light: &Light,
+    ) -> () {
+        if self.visible {
+            canvas
+                .window_mut()
+                .set_title(&format!("{} | {}", title, self.text(stats, light)));
+        } else {
+            canvas.window_mut().set_title(title);
+        }
+    }
+}
+
+impl Default for Hud {
+    /// Create a default HUD, which is visible
+    ///
+    /// # Returns
+    /// * `Hud` - The default HUD
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_hides_and_shows_the_hud() {
+        let mut hud = Hud::default();
+        assert!(hud.visible);
+
+        hud.toggle();
+        assert!(!hud.visible);
+
+        hud.toggle();
+        assert!(hud.visible);
+    }
+}
@@ -0,0 +1,358 @@
+//! Contains the convex hull of a set of points, built with the quickhull
+//! algorithm. Hulls make cheap stand-ins for meshes (e.g. for collision).
+
+use crate::{Mesh, Triangle, Vector3D};
+use core::fmt::{self, Display};
+use std::collections::HashSet;
+
+/// The reasons a set of points has no solid convex hull
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HullError {
+    /// A solid needs at least four points
+    TooFewPoints,
+    /// Every point is in the same place
+    Coincident,
+    /// Every point is on the same line
+    Collinear,
+    /// Every point is on the same plane
+    Coplanar,
+}
+
+impl Display for HullError {
+    /// Print the error
+    ///
+    /// # Arguments
+    /// * `f` - The formatter
+    ///
+    /// # Returns
+    /// * `fmt::Result` - The result of the formatter
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HullError::TooFewPoints => write!(f, "A convex hull needs at least 4 points"),
+            HullError::Coincident => write!(f, "The points are all in the same place"),
+            HullError::Collinear => write!(f, "The points all lie on a line"),
+            HullError::Coplanar => write!(f, "The points all lie on a plane"),
+        }
+    }
+}
+
+impl std::error::Error for HullError {}
+
+/// A triangle of the hull while it is being built
+struct Face {
+    /// `vertices` - The indices of the face's points, wound so the normal points out
+    vertices: [usize; 3],
+    /// `normal` - The unit normal of the face, pointing out of the hull
+    normal: Vector3D,
+    /// `offset` - The dot product of the normal with any point on the face
+    offset: f32,
+    /// `outside` - The points that are in front of this face and not yet in the hull
+    outside: Vec<usize>,
+    /// `alive` - Whether the face is still part of the hull
+    alive: bool,
+}
+
+impl Face {
+    /// Create a new face
+    ///
+    /// # Arguments
+    /// * `points` - Every point
+    /// * `vertices` - The indices of the face's points, wound so the normal points out
+    ///
+    /// # Returns
+    /// * `Face` - The new face
+    fn new(points: &[Vector3D], vertices: [usize; 3]) -> Self {
+        let [a, b, c] = vertices.map(|index| points[index]);
+        let normal = (b - a).cross(&(c - a)).normalize();
+
+        Self {
+            vertices,
+            normal,
+            offset: normal.dot(&a),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    /// Get how far in front of the face a point is
+    ///
+    /// # Arguments
+    /// * `point` - The point to check
+    ///
+    /// # Returns
+    /// * `f32` - The distance, which is negative behind the face
+    fn distance(&self, point: &Vector3D) -> f32 {
+        self.normal.dot(point) - self.offset
+    }
+}
+
+/// Give each point to the first face it is in front of. Points in front of no face are inside
+/// the hull and are dropped.
+///
+/// # Arguments
+/// * `faces` - Every face of the hull
+/// * `candidates` - The indices of the faces that can take points
+/// * `points` - Every point
+/// * `assign` - The indices of the points to give out
+/// * `epsilon` - How far in front of a face a point has to be to count
+///
+/// # Returns
+/// * `()` - Nothing
+fn assign_points(
+    faces: &mut [Face],
+    candidates: &[usize],
+    points: &[Vector3D],
+    assign: impl Iterator<Item = usize>,
+    epsilon: f32,
+) -> () {
+    for point in assign {
+        if let Some(&face) = candidates
+            .iter()
+            .find(|&&face| faces[face].distance(&points[point]) > epsilon)
+        {
+            faces[face].outside.push(point);
+        }
+    }
+}
+
+impl Mesh {
+    /// Get the convex hull of a set of points with the quickhull algorithm - the smallest
+    /// convex mesh that holds every point. Points on a face of the hull are left out, so each
+    /// flat side is made of as few triangles as possible.
+    ///
+    /// # Arguments
+    /// * `points` - The points to wrap
+    ///
+    /// # Returns
+    /// * `Result<Mesh, HullError>` - The hull with its triangles facing outwards, or why the
+    ///   points don't have a solid hull
+    pub fn convex_hull(points: &[Vector3D]) -> Result<Mesh, HullError> {
+        if points.len() < 4 {
+            return Err(HullError::TooFewPoints);
+        }
+
+        // Scale the tolerance to the size of the point set, so it works at any size
+        let extent = points
+            .iter()
+            .flat_map(|point| [point.x.abs(), point.y.abs(), point.z.abs()])
+            .fold(0.0, f32::max);
+        let epsilon = extent.max(1.0) * 1e-5;
+
+        // Start from the two points furthest apart of the extremes along each axis
+        let mut extremes = Vec::with_capacity(6);
+        for axis in [|v: &Vector3D| v.x, |v: &Vector3D| v.y, |v: &Vector3D| v.z] {
+            let by_axis = |i: &usize, j: &usize| axis(&points[*i]).total_cmp(&axis(&points[*j]));
+
+            extremes.push((0..points.len()).min_by(by_axis).unwrap());
+            extremes.push((0..points.len()).max_by(by_axis).unwrap());
+        }
+        let (a, b) = extremes
+            .iter()
+            .flat_map(|&i| extremes.iter().map(move |&j| (i, j)))
+            .max_by(|(i, j), (k, l)| {
+                points[*i]
+                    .distance(&points[*j])
+                    .total_cmp(&points[*k].distance(&points[*l]))
+            })
+            .unwrap();
+        if points[a].distance(&points[b]) <= epsilon {
+            return Err(HullError::Coincident);
+        }
+
+        // Then the point furthest from the line through them
+        let direction = (points[b] - points[a]).normalize();
+        let from_line = |p: &Vector3D| (*p - points[a]).cross(&direction).length();
+        let c = (0..points.len())
+            .max_by(|i, j| from_line(&points[*i]).total_cmp(&from_line(&points[*j])))
+            .unwrap();
+        if from_line(&points[c]) <= epsilon {
+            return Err(HullError::Collinear);
+        }
+
+        // And the point furthest from the plane through all three
+        let base = Face::new(points, [a, b, c]);
+        let d = (0..points.len())
+            .max_by(|i, j| {
+                base.distance(&points[*i])
+                    .abs()
+                    .total_cmp(&base.distance(&points[*j]).abs())
+            })
+            .unwrap();
+        if base.distance(&points[d]).abs() <= epsilon {
+            return Err(HullError::Coplanar);
+        }
+
+        // Wind each face of the starting tetrahedron so that the fourth point is behind it
+        let mut faces: Vec<Face> = [[a, b, c], [a, c, d], [a, d, b], [b, d, c]]
+            .into_iter()
+            .map(|[i, j, k]| {
+                let other = [a, b, c, d]
+                    .into_iter()
+                    .find(|vertex| ![i, j, k].contains(vertex))
+                    .unwrap();
+                let face = Face::new(points, [i, j, k]);
+
+                if face.distance(&points[other]) > 0.0 {
+                    Face::new(points, [i, k, j])
+                } else {
+                    face
+                }
+            })
+            .collect();
+
+        let initial = [a, b, c, d];
+        assign_points(
+            &mut faces,
+            &[0, 1, 2, 3],
+            points,
+            (0..points.len()).filter(|point| !initial.contains(point)),
+            epsilon,
+        );
+
+        // Grow the hull out to the furthest outside point of a face until none are left
+        while let Some(face) = faces
+            .iter()
+            .position(|face| face.alive && !face.outside.is_empty())
+        {
+            let apex = *faces[face]
+                .outside
+                .iter()
+                .max_by(|i, j| {
+                    faces[face]
+                        .distance(&points[**i])
+                        .total_cmp(&faces[face].distance(&points[**j]))
+                })
+                .unwrap();
+
+            let visible: Vec<usize> = (0..faces.len())
+                .filter(|&i| faces[i].alive && faces[i].distance(&points[apex]) > epsilon)
+                .collect();
+
+            // The horizon is every edge of the visible faces that isn't shared by two of them
+            let visible_edges: HashSet<(usize, usize)> = visible
+                .iter()
+                .flat_map(|&i| {
+                    let [p, q, r] = faces[i].vertices;
+                    [(p, q), (q, r), (r, p)]
+                })
+                .collect();
+            let horizon: Vec<(usize, usize)> = visible_edges
+                .iter()
+                .filter(|(start, end)| !visible_edges.contains(&(*end, *start)))
+                .copied()
+                .collect();
+
+            let mut orphans = Vec::new();
+            for &i in &visible {
+                faces[i].alive = false;
+                orphans.append(&mut faces[i].outside);
+            }
+
+            // Joining the horizon to the apex keeps the winding of the faces it replaces
+            let first_new = faces.len();
+            for (start, end) in horizon {
+                faces.push(Face::new(points, [start, end, apex]));
+            }
+
+            let new_faces: Vec<usize> = (first_new..faces.len()).collect();
+            assign_points(
+                &mut faces,
+                &new_faces,
+                points,
+                orphans.into_iter().filter(|point| *point != apex),
+                epsilon,
+            );
+        }
+
+        Ok(Mesh::new(
+            faces
+                .iter()
+                .filter(|face| face.alive)
+                .map(|face| {
+                    let [a, b, c] = face.vertices;
+                    Triangle::new(points[a], points[b], points[c])
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives;
+
+    /// The 8 corners of the unit cube
+    fn cube_corners() -> Vec<Vector3D> {
+        (0..8)
+            .map(|i| Vector3D::new((i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32))
+            .collect()
+    }
+
+    #[test]
+    fn hull_of_the_cube_corners_has_twelve_triangles() {
+        let hull = Mesh::convex_hull(&cube_corners()).unwrap();
+
+        assert_eq!(hull.mat.len(), 12);
+        assert_eq!(hull.boundary_edge_count(), 0);
+        assert!((hull.volume() - 1.0).abs() < 1e-5);
+        assert!((hull.surface_area() - 6.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn points_inside_the_hull_are_left_out() {
+        let mut points = cube_corners();
+        points.extend([
+            Vector3D::new(0.5, 0.5, 0.5),
+            Vector3D::new(0.1, 0.9, 0.3),
+            Vector3D::new(0.5, 0.5, 0.0),
+        ]);
+
+        assert_eq!(Mesh::convex_hull(&points).unwrap().mat.len(), 12);
+    }
+
+    #[test]
+    fn hull_of_a_sphere_wraps_the_sphere() {
+        let sphere = primitives::icosphere(1.0, 1);
+        let points: Vec<Vector3D> = sphere
+            .mat
+            .iter()
+            .flat_map(|triangle| [triangle.a, triangle.b, triangle.c])
+            .collect();
+        let hull = Mesh::convex_hull(&points).unwrap();
+
+        assert_eq!(hull.mat.len(), sphere.mat.len());
+        assert!((hull.volume() - sphere.volume()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn flat_point_sets_have_no_hull() {
+        let point = Vector3D::new(1.0, 2.0, 3.0);
+        let along = |t: f32| Vector3D::new(t, 2.0 * t, -t);
+        let on_plane = |x: f32, y: f32| Vector3D::new(x, y, 4.0);
+
+        assert_eq!(
+            Mesh::convex_hull(&cube_corners()[..3]).err(),
+            Some(HullError::TooFewPoints)
+        );
+        assert_eq!(
+            Mesh::convex_hull(&[point; 5]).err(),
+            Some(HullError::Coincident)
+        );
+        assert_eq!(
+            Mesh::convex_hull(&[along(0.0), along(1.0), along(2.5), along(-3.0)]).err(),
+            Some(HullError::Collinear)
+        );
+        assert_eq!(
+            Mesh::convex_hull(&[
+                on_plane(0.0, 0.0),
+                on_plane(1.0, 0.0),
+                on_plane(0.0, 1.0),
+                on_plane(3.0, 5.0),
+                on_plane(-2.0, 1.0),
+            ])
+            .err(),
+            Some(HullError::Coplanar)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Contains loading meshes from model files. glTF support needs the `gltf`
+//! feature, as it pulls in the `gltf` crate.
+
+use crate::obj::ObjError;
+use crate::{Mesh, Triangle, Vector3D};
+use core::fmt::{self, Display};
+
+/// The reasons a mesh can't be loaded from a file
+#[derive(Debug)]
+pub enum MeshLoadError {
+    /// The glTF file couldn't be read or parsed
+    #[cfg(feature = "gltf")]
+    Gltf(gltf::Error),
+    /// The OBJ file couldn't be read or parsed
+    Obj(ObjError),
+    /// The file doesn't contain any meshes
+    NoMesh,
+    /// The mesh has no vertex positions
+    MissingPositions,
+    /// A triangle refers to a vertex that doesn't exist
+    IndexOutOfRange {
+        /// `index` - The index of the vertex
+        index: usize,
+        /// `vertices` - The amount of vertices there are
+        vertices: usize,
+    },
+}
+
+impl Display for MeshLoadError {
+    /// Print the error
+    ///
+    /// # Arguments
+    /// * `f` - The formatter
+    ///
+    /// # Returns
+    /// * `fmt::Result` - The result of the formatter
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "gltf")]
+            MeshLoadError::Gltf(error) => write!(f, "Couldn't read the glTF file: {}", error),
+            MeshLoadError::Obj(error) => write!(f, "Couldn't read the OBJ file: {}", error),
+            MeshLoadError::NoMesh => write!(f, "The file doesn't contain a mesh"),
+            MeshLoadError::MissingPositions => write!(f, "The mesh has no vertex positions"),
+            MeshLoadError::IndexOutOfRange { index, vertices } => write!(
+                f,
+                "A triangle uses vertex {}, but there are only {} vertices",
+                index, vertices
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MeshLoadError {}
+
+#[cfg(feature = "gltf")]
+impl From<gltf::Error> for MeshLoadError {
+    /// Wrap an error from the `gltf` crate
+    ///
+    /// # Arguments
+    /// * `error` - The error to wrap
+    ///
+    /// # Returns
+    /// * `MeshLoadError` - The wrapped error
+    fn from(error: gltf::Error) -> Self {
+        MeshLoadError::Gltf(error)
+    }
+}
+
+impl From<ObjError> for MeshLoadError {
+    /// Wrap an error from the OBJ parser
+    ///
+    /// # Arguments
+    /// * `error` - The error to wrap
+    ///
+    /// # Returns
+    /// * `MeshLoadError` - The wrapped error
+    fn from(error: ObjError) -> Self {
+        MeshLoadError::Obj(error)
+    }
+}
+
+impl Mesh {
+    /// Load the first mesh in a glTF 2.0 file. Only the positions and indices of its triangle
+    /// primitives are read - materials, animation and anything else are ignored.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the `.gltf` or `.glb` file
+    ///
+    /// # Returns
+    /// * `Result<Mesh, MeshLoadError>` - The mesh, or why it couldn't be loaded
+    #[cfg(feature = "gltf")]
+    pub fn from_gltf<P: AsRef<std::path::Path>>(path: P) -> Result<Mesh, MeshLoadError> {
+        let (document, buffers, _) = gltf::import(path)?;
+        let mesh = document.meshes().next().ok_or(MeshLoadError::NoMesh)?;
+
+        let mut triangles = Vec::new();
+
+        for primitive in mesh
+            .primitives()
+            .filter(|primitive| primitive.mode() == gltf::mesh::Mode::Triangles)
+        {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let positions: Vec<Vector3D> = reader
+                .read_positions()
+                .ok_or(MeshLoadError::MissingPositions)?
+                .map(|[x, y, z]| Vector3D::new(x, y, z))
+                .collect();
+            // Without indices every three vertices make a triangle
+            let indices: Vec<usize> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().map(|index| index as usize).collect(),
+                None => (0..positions.len()).collect(),
+            };
+
+            for face in indices.chunks_exact(3) {
+                let vertex = |index: usize| {
+                    positions
+                        .get(index)
+                        .copied()
+                        .ok_or(MeshLoadError::IndexOutOfRange {
+                            index,
+                            vertices: positions.len(),
+                        })
+                };
+
+                triangles.push(Triangle::new(
+                    vertex(face[0])?,
+                    vertex(face[1])?,
+                    vertex(face[2])?,
+                ));
+            }
+        }
+
+        Ok(Mesh::new(triangles))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A glTF file holding one triangle, with its positions and indices embedded in the file
+    #[cfg(feature = "gltf")]
+    const TRIANGLE_GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{
+            "byteLength": 44,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAIAAAA="
+        }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 6 }
+        ],
+        "accessors": [
+            {
+                "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0]
+            },
+            { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+        ],
+        "meshes": [{
+            "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }]
+        }]
+    }"#;
+
+    /// Write a file into the temporary folder, named so parallel tests don't share it
+    #[cfg(feature = "gltf")]
+    fn temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("engine-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).expect("the temporary folder should be writable");
+
+        path
+    }
+
+    #[cfg(feature = "gltf")]
+    #[test]
+    fn loads_an_embedded_triangle() {
+        let path = temp_file("triangle.gltf", TRIANGLE_GLTF);
+        let mesh = Mesh::from_gltf(&path);
+        std::fs::remove_file(&path).ok();
+        let mesh = mesh.expect("the triangle should load");
+
+        assert_eq!(mesh.mat.len(), 1);
+        assert_eq!(
+            [mesh.mat[0].a, mesh.mat[0].b, mesh.mat[0].c],
+            [
+                Vector3D::new(0.0, 0.0, 0.0),
+                Vector3D::new(1.0, 0.0, 0.0),
+                Vector3D::new(0.0, 1.0, 0.0),
+            ]
+        );
+    }
+
+    #[cfg(feature = "gltf")]
+    #[test]
+    fn file_without_a_mesh_is_an_error() {
+        let path = temp_file("empty.gltf", r#"{ "asset": { "version": "2.0" } }"#);
+        let mesh = Mesh::from_gltf(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert!(matches!(mesh, Err(MeshLoadError::NoMesh)));
+        assert!(matches!(
+            Mesh::from_gltf("does/not/exist.gltf"),
+            Err(MeshLoadError::Gltf(_))
+        ));
+    }
+
+    #[test]
+    fn errors_say_what_went_wrong() {
+        assert_eq!(
+            MeshLoadError::IndexOutOfRange {
+                index: 7,
+                vertices: 3
+            }
+            .to_string(),
+            "A triangle uses vertex 7, but there are only 3 vertices"
+        );
+        assert_eq!(
+            MeshLoadError::NoMesh.to_string(),
+            "The file doesn't contain a mesh"
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Contains the mapping from keyboard keys to the actions they trigger,
+//! so that the game loop doesn't need to know about key layouts
+
+use crate::camera::{Camera, Orientation};
+use crate::Vector3D;
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Scancode};
+use std::collections::HashSet;
+
+/// How fast the camera moves while a movement key is held, in units per second
+pub const MOVE_SPEED: f32 = 2.0;
+
+/// How far the camera turns as the mouse moves with the right button held, in radians per
+/// pixel
+pub const MOUSE_SENSITIVITY: f32 = 0.005;
+
+/// Something the player can do with the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Move towards where the camera is looking
+    Forward,
+    /// Move away from where the camera is looking
+    Backward,
+    /// Move to the left of the camera
+    Left,
+    /// Move to the right of the camera
+    Right,
+    /// Move straight up
+    Up,
+    /// Move straight down
+    Down,
+}
+
+/// How key presses are turned into actions
+#[derive(Debug, Clone, Copy)]
+pub struct InputConfig {
+    /// `use_scancodes` - Whether movement is bound to the physical position of a key rather
+    /// than the letter printed on it. With this on, WASD stays in the same place on AZERTY or
+    /// Dvorak keyboards.
+    pub use_scancodes: bool,
+}
+
+impl Default for InputConfig {
+    /// Create a default config, which binds movement to physical key positions
+    ///
+    /// # Returns
+    /// * `InputConfig` - The default config
+    fn default() -> Self {
+        Self {
+            use_scancodes: true,
+        }
+    }
+}
+
+impl InputConfig {
+    /// Get the action a key triggers, using whichever of the keycode and scancode the config
+    /// says should be used
+    ///
+    /// # Arguments
+    /// * `keycode` - The keycode of the key, depending on the keyboard layout
+    /// * `scancode` - The scancode of the key, depending only on its physical position
+    ///
+    /// # Returns
+    /// * `Option<Action>` - The action the key is bound to, if any
+    pub fn action(&self, keycode: Option<Keycode>, scancode: Option<Scancode>) -> Option<Action> {
+        if self.use_scancodes {
+            scancode.and_then(action_for_scancode)
+        } else {
+            keycode.and_then(action_for_keycode)
+        }
+    }
+}
+
+/// Get the action bound to a physical key position. The positions are named after where the
+/// keys are on a QWERTY keyboard.
+///
+/// # Arguments
+/// * `scancode` - The physical key
+///
+/// # Returns
+/// * `Option<Action>` - The action the key is bound to, if any
+pub fn action_for_scancode(scancode: Scancode) -> Option<Action> {
+    match scancode {
+        Scancode::W | Scancode::Up => Some(Action::Forward),
+        Scancode::S | Scancode::Down => Some(Action::Backward),
+        Scancode::A | Scancode::Left => Some(Action::Left),
+        Scancode::D | Scancode::Right => Some(Action::Right),
+        Scancode::E => Some(Action::Up),
+        Scancode::Q => Some(Action::Down),
+        _ => None,
+    }
+}
+
+/// Get the action bound to the letter printed on a key
+///
+/// # Arguments
+/// * `keycode` - The key, as seen through the keyboard layout
+///
+/// # Returns
+/// * `Option<Action>` - The action the key is bound to, if any
+pub fn action_for_keycode(keycode: Keycode) -> Option<Action> {
+    match keycode {
+        Keycode::W | Keycode::Up => Some(Action::Forward),
+        Keycode::S | Keycode::Down => Some(Action::Backward),
+        Keycode::A | Keycode::Left => Some(Action::Left),
+        Keycode::D | Keycode::Right => Some(Action::Right),
+        Keycode::E => Some(Action::Up),
+        Keycode::Q => Some(Action::Down),
+        _ => None,
+    }
+}
+
+/// The actions whose keys are currently held down. Movement is read from this once per frame
+/// instead of reacting to key events, so it doesn't depend on how (or whether) the operating
+/// system repeats held keys.
+pub struct InputState {
+    /// `held` - The actions whose keys are held down
+    pub held: HashSet<Action>,
+}
+
+impl InputState {
+    /// Create a new input state with nothing held
+    ///
+    /// # Returns
+    /// * `InputState` - The new input state
+    pub fn new() -> Self {
+        Self {
+            held: HashSet::new(),
+        }
+    }
+
+    /// Update the held actions from an event. Repeated key down events are ignored, as the
+    /// key is already held.
+    ///
+    /// # Arguments
+    /// * `config` - How keys are turned into actions
+    /// * `event` - The event to handle
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn handle_event(&mut self, config: &InputConfig, event: &Event) -> () {
+        match *event {
+            Event::KeyDown {
+                keycode,
+                scancode,
+                repeat: false,
+                ..
+            } => {
+                if let Some(action) = config.action(keycode, scancode) {
+                    self.held.insert(action);
+                }
+            }
+            Event::KeyUp {
+                keycode, scancode, ..
+            } => {
+                if let Some(action) = config.action(keycode, scancode) {
+                    self.held.remove(&action);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Check whether an action's key is held down
+    ///
+    /// # Arguments
+    /// * `action` - The action to check
+    ///
+    /// # Returns
+    /// * `bool` - Whether the action is held
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+
+    /// Get the direction the held keys are asking to move in, relative to the camera. This
+    /// should be read every frame and scaled by the frame time, so that a held key moves at
+    /// the same speed no matter how often key events arrive.
+    ///
+    /// # Returns
+    /// * `Vector3D` - The direction, with x to the right, y up and z forward. Opposite keys
+    ///   held together cancel out.
+    pub fn movement(&self) -> Vector3D {
+        let axis = |positive: Action, negative: Action| {
+            (self.is_held(positive) as i32 - self.is_held(negative) as i32) as f32
+        };
+
+        Vector3D::new(
+            axis(Action::Right, Action::Left),
+            axis(Action::Up, Action::Down),
+            axis(Action::Forward, Action::Backward),
+        )
+    }
+}
+
+impl Default for InputState {
+    /// Create a default input state with nothing held
+    ///
+    /// # Returns
+    /// * `InputState` - The default input state
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Move the camera by the held movement keys. Forward and backward follow the way the camera
+/// is looking, left and right go across it and up and down follow the camera's up. The
+/// camera's target moves with it, so it keeps looking the same way.
+///
+/// # Arguments
+/// * `camera` - The camera to move
+/// * `keys` - The keys that are held down
+/// * `dt` - How long the last frame took, in seconds, so the speed doesn't depend on the
+///   frame rate
+///
+/// # Returns
+/// * `()` - Nothing
+pub fn apply_input(camera: &mut Camera, keys: &InputState, dt: f32) -> () {
+    let movement = keys.movement();
+    let forward = camera.forward();
+    let up = camera.up.normalize();
+    let right = up.cross(&forward).normalize();
+
+    let offset = (right * movement.x + up * movement.y + forward * movement.z) * (MOVE_SPEED * dt);
+
+    camera.position += offset;
+    camera.target += offset;
+}
+
+/// Turn the camera by how far the mouse moved. Moving right turns right and moving up looks
+/// up. The angles are left for `Orientation::update` to put back in range.
+///
+/// # Arguments
+/// * `orientation` - The orientation to turn
+/// * `xrel` - How far the mouse moved to the right, in pixels
+/// * `yrel` - How far the mouse moved down, in pixels
+///
+/// # Returns
+/// * `()` - Nothing
+pub fn apply_mouse_look(orientation: &mut Orientation, xrel: i32, yrel: i32) -> () {
+    orientation.yaw += xrel as f32 * MOUSE_SENSITIVITY;
+    orientation.pitch -= yrel as f32 * MOUSE_SENSITIVITY;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scancodes_map_to_actions() {
+        for (scancode, action) in [
+            (Scancode::W, Some(Action::Forward)),
+            (Scancode::Up, Some(Action::Forward)),
+            (Scancode::S, Some(Action::Backward)),
+            (Scancode::Down, Some(Action::Backward)),
+            (Scancode::A, Some(Action::Left)),
+            (Scancode::Left, Some(Action::Left)),
+            (Scancode::D, Some(Action::Right)),
+            (Scancode::Right, Some(Action::Right)),
+            (Scancode::E, Some(Action::Up)),
+            (Scancode::Q, Some(Action::Down)),
+            (Scancode::Z, None),
+            (Scancode::Space, None),
+        ] {
+            assert_eq!(action_for_scancode(scancode), action, "{:?}", scancode);
+        }
+    }
+
+    #[test]
+    fn config_picks_between_scancodes_and_keycodes() {
+        // On AZERTY the key where W is on QWERTY is labelled Z
+        let (keycode, scancode) = (Some(Keycode::Z), Some(Scancode::W));
+
+        assert_eq!(
+            InputConfig {
+                use_scancodes: true
+            }
+            .action(keycode, scancode),
+            Some(Action::Forward)
+        );
+        assert_eq!(
+            InputConfig {
+                use_scancodes: false
+            }
+            .action(keycode, scancode),
+            None
+        );
+    }
+
+    /// A key event for the key at the W position on a QWERTY keyboard
+    fn w_key(down: bool) -> Event {
+        let (keycode, scancode) = (Some(Keycode::W), Some(Scancode::W));
+
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode,
+                scancode,
+                keymod: sdl2::keyboard::Mod::NOMOD,
+                repeat: false,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode,
+                scancode,
+                keymod: sdl2::keyboard::Mod::NOMOD,
+                repeat: false,
+            }
+        }
+    }
+
+    #[test]
+    fn held_key_moves_the_camera_every_frame() {
+        let config = InputConfig::default();
+        let mut keys = InputState::new();
+        let mut camera = Camera::default();
+
+        // A single key down, with no repeats after it
+        keys.handle_event(&config, &w_key(true));
+
+        let mut last = camera.position.z;
+        for _ in 0..5 {
+            apply_input(&mut camera, &keys, 0.1);
+
+            assert!((camera.position.z - last - MOVE_SPEED * 0.1).abs() < 1e-5);
+            last = camera.position.z;
+        }
+
+        keys.handle_event(&config, &w_key(false));
+        apply_input(&mut camera, &keys, 0.1);
+        assert_eq!(camera.position.z, last);
+    }
+
+    #[test]
+    fn held_keys_move_the_camera_by_speed_and_time() {
+        let mut keys = InputState::new();
+        keys.held.extend([Action::Right, Action::Up]);
+        let mut camera = Camera::default();
+        let (start, target) = (camera.position, camera.target);
+
+        apply_input(&mut camera, &keys, 0.5);
+
+        let expected = Vector3D::new(MOVE_SPEED * 0.5, MOVE_SPEED * 0.5, 0.0);
+        assert!((camera.position - start - expected).length() < 1e-5);
+        // The camera keeps looking the same way
+        assert!((camera.target - target - expected).length() < 1e-5);
+    }
+
+    #[test]
+    fn opposite_keys_cancel_and_no_time_means_no_movement() {
+        let mut keys = InputState::new();
+        keys.held
+            .extend([Action::Forward, Action::Backward, Action::Left]);
+        let mut camera = Camera::default();
+        let start = camera.position;
+
+        apply_input(&mut camera, &keys, 0.0);
+        assert_eq!(
+            (camera.position.x, camera.position.y, camera.position.z),
+            (start.x, start.y, start.z)
+        );
+
+        apply_input(&mut camera, &keys, 0.25);
+        assert!(
+            (camera.position - start - Vector3D::new(-MOVE_SPEED * 0.25, 0.0, 0.0)).length() < 1e-5
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Contains the directional light the scene is lit by, which can be turned
+//! from the keyboard
+
+use crate::units::{Degrees, Radians};
+use crate::Vector3D;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+
+/// A light infinitely far away, so that its rays all travel in the same direction (e.g. the sun)
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    /// `direction` - The unit direction the light travels in
+    pub direction: Vector3D,
+}
+
+impl Light {
+    /// How far a single key press turns the light
+    pub const ROTATION_STEP: Degrees = Degrees(15.0);
+
+    /// Create a new light
+    ///
+    /// # Arguments
+    /// * `direction` - The direction the light travels in, which doesn't have to be unit length
+    ///
+    /// # Returns
+    /// * `Light` - The new light
+    pub fn new(direction: Vector3D) -> Self {
+        Self {
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Turn the light, first around the vertical axis and then around the x axis. The
+    /// direction is normalized afterwards so rounding can't build up over many turns.
+    ///
+    /// # Arguments
+    /// * `yaw` - How far to turn around the vertical axis
+    /// * `pitch` - How far to turn around the x axis
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn rotate(&mut self, yaw: Radians, pitch: Radians) -> () {
+        let Vector3D { x, y, z } = self.direction;
+        let (x, z) = (
+            x * yaw.0.cos() + z * yaw.0.sin(),
+            -x * yaw.0.sin() + z * yaw.0.cos(),
+        );
+        let (y, z) = (
+            y * pitch.0.cos() - z * pitch.0.sin(),
+            y * pitch.0.sin() + z * pitch.0.cos(),
+        );
+
+        self.direction = Vector3D::new(x, y, z).normalize();
+    }
+
+    /// Turn the light one step for a key press: J and L turn it left and right, I and K
+    /// turn it up and down. Any other key leaves it alone.
+    ///
+    /// # Arguments
+    /// * `keycode` - The key that was pressed
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn turn_with_key(&mut self, keycode: Keycode) -> () {
+        let step = Self::ROTATION_STEP.to_radians().0;
+        let (yaw, pitch) = match keycode {
+            Keycode::J => (-step, 0.0),
+            Keycode::L => (step, 0.0),
+            Keycode::I => (0.0, -step),
+            Keycode::K => (0.0, step),
+            _ => return,
+        };
+
+        self.rotate(Radians(yaw), Radians(pitch));
+    }
+
+    /// Get how brightly a surface is lit, which is brightest when it faces straight back
+    /// towards where the light comes from
+    ///
+    /// # Arguments
+    /// * `normal` - The unit normal of the surface
+    ///
+    /// # Returns
+    /// * `f32` - The brightness, from 0 for unlit to 1 for fully lit
+    pub fn brightness(&self, normal: &Vector3D) -> f32 {
+        normal.dot(&-self.direction).clamp(0.0, 1.0)
+    }
+
+    /// Get the flat shaded colour of a surface lit by the light
+    ///
+    /// # Arguments
+    /// * `normal` - The unit normal of the surface
+    ///
+    /// # Returns
+    /// * `Color` - A grey as bright as the surface is lit
+    pub fn shade(&self, normal: &Vector3D) -> Color {
+        let level = (self.brightness(normal) * 255.0).round() as u8;
+
+        Color::RGB(level, level, level)
+    }
+}
+
+impl Default for Light {
+    /// Create a light shining straight into the screen, the same way the camera looks
+    ///
+    /// # Returns
+    /// * `Light` - The default light
+    fn default() -> Self {
+        Self::new(Vector3D::new(0.0, 0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The angle between two unit vectors, in degrees
+    fn angle_between(a: &Vector3D, b: &Vector3D) -> f32 {
+        a.dot(b).clamp(-1.0, 1.0).acos().to_degrees()
+    }
+
+    #[test]
+    fn each_key_turns_the_light_one_step() {
+        for keycode in [Keycode::I, Keycode::J, Keycode::K, Keycode::L] {
+            let mut light = Light::default();
+            let before = light.direction;
+            light.turn_with_key(keycode);
+
+            assert!((light.direction.length() - 1.0).abs() < 1e-5);
+            assert!(
+                (angle_between(&before, &light.direction) - Light::ROTATION_STEP.0).abs() < 1e-2,
+                "{:?}",
+                keycode
+            );
+        }
+    }
+
+    #[test]
+    fn opposite_keys_turn_opposite_ways() {
+        let turned = |keycode: Keycode| {
+            let mut light = Light::default();
+            light.turn_with_key(keycode);
+
+            light.direction
+        };
+
+        let (left, right) = (turned(Keycode::J), turned(Keycode::L));
+        assert!(left.x < 0.0 && right.x > 0.0);
+        assert!(left.y.abs() < 1e-6 && right.y.abs() < 1e-6);
+
+        let (up, down) = (turned(Keycode::I), turned(Keycode::K));
+        assert!((up.y + down.y).abs() < 1e-6 && up.y.abs() > 0.1);
+        assert!(up.x.abs() < 1e-6 && down.x.abs() < 1e-6);
+
+        assert_eq!(turned(Keycode::W), Light::default().direction);
+    }
+
+    #[test]
+    fn a_full_turn_comes_back_to_the_start() {
+        let mut light = Light::new(Vector3D::new(1.0, -2.0, 3.0));
+        let start = light.direction;
+
+        for _ in 0..24 {
+            light.turn_with_key(Keycode::J);
+        }
+
+        assert!((light.direction - start).length() < 1e-4);
+        assert!((light.direction.length() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn face_towards_the_light_is_fully_lit() {
+        let light = Light::new(Vector3D::new(0.0, 0.0, 2.0));
+        // The south face of the cube faces -z, back towards where the light comes from
+        let south = crate::cube::get_cube_mesh().mat[0].normal();
+
+        assert!((light.brightness(&south) - 1.0).abs() < 1e-5);
+        assert_eq!(light.shade(&south), Color::RGB(255, 255, 255));
+    }
+
+    #[test]
+    fn perpendicular_and_facing_away_faces_are_unlit() {
+        let light = Light::default();
+
+        assert!(light.brightness(&Vector3D::new(1.0, 0.0, 0.0)) < 1e-5);
+        assert_eq!(
+            light.shade(&Vector3D::new(0.0, 1.0, 0.0)),
+            Color::RGB(0, 0, 0)
+        );
+        assert_eq!(light.brightness(&Vector3D::new(0.0, 0.0, 1.0)), 0.0);
+    }
+}
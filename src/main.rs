@@ -4,18 +4,77 @@
 
 extern crate sdl2;
 
+use arrayvec::ArrayVec;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
-use std::time::Duration;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use std::collections::{HashMap, HashSet};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::time::{Duration, Instant};
 
+mod animation;
+mod backend;
+mod background;
+mod billboard;
+mod camera;
 mod cube;
+mod cull;
+mod depth;
+mod display;
+mod draw_list;
+mod easing;
+mod framebuffer;
+mod grid;
+mod history;
+mod hud;
+mod hull;
+mod import;
+mod input;
+mod light;
+mod obj;
+mod post;
+mod primitives;
+mod raster;
+mod run;
+mod scene;
+mod skybox;
+mod spring;
+mod stats;
+mod target;
+mod textures;
+mod tiles;
+mod units;
+mod vertex;
+mod wireframe;
 
+use animation::{Timeline, Transform};
+use backend::{Backend, BackendTarget};
+use background::Background;
+use camera::{Camera, Orientation, View};
 use core::fmt::{self, Display};
-use warn;
+use cull::CullConfig;
+use depth::{DepthBuffer, DepthConfig};
+use display::{DisplaySize, Viewport};
+use draw_list::DrawList;
+use framebuffer::{Framebuffer, TextureTarget};
+use grid::Grid;
+use history::TransformHistory;
+use hud::Hud;
+use input::{apply_input, apply_mouse_look, InputConfig, InputState};
+use light::Light;
+use raster::RasterConfig;
+use run::{FrameCallback, FrameTimer, FrameTimes, RunConfig, FRAME_RATE_CAP};
+use scene::{PickResult, RenderMode, Scene};
+use skybox::Skybox;
+use stats::{RenderStats, StageTimer, StatsLog, SystemClock};
+use target::RenderTarget;
+use units::Degrees;
+use warn::{self, Warn};
+use wireframe::WireframeConfig;
 
 /// A simple vector that is 3d which has 3 common components that represent each dimension.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Vector3D {
     /// 'x' for the x-axis
     pub x: f32,
@@ -53,6 +112,122 @@ impl Vector3D {
             z: self.z,
         }
     }
+
+    /// Get the straight line distance between this vector and another
+    ///
+    /// # Arguments
+    /// * `other` - The other vector
+    ///
+    /// # Returns
+    /// * `f32` - The distance between the two vectors
+    pub fn distance(&self, other: &Vector3D) -> f32 {
+        (*self - *other).length()
+    }
+
+    /// Get the length of the vector
+    ///
+    /// # Returns
+    /// * `f32` - The Euclidean length
+    pub fn length(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Get a copy of the vector scaled to a length of 1
+    ///
+    /// # Returns
+    /// * `Vector3D` - The unit vector, or the zero vector if this vector has no length (as it
+    ///   has no direction to keep)
+    pub fn normalize(&self) -> Vector3D {
+        let length = self.length();
+
+        if length > f32::EPSILON {
+            *self * (1.0 / length)
+        } else {
+            Vector3D::default()
+        }
+    }
+
+    /// Get the dot product of this vector and another, which is zero when they are at right
+    /// angles and positive when they point the same way
+    ///
+    /// # Arguments
+    /// * `other` - The other vector
+    ///
+    /// # Returns
+    /// * `f32` - The dot product
+    pub fn dot(&self, other: &Vector3D) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Get the cross product of this vector and another, which is at right angles to both
+    /// following the right hand rule (e.g. x × y = z)
+    ///
+    /// # Arguments
+    /// * `other` - The other vector
+    ///
+    /// # Returns
+    /// * `Vector3D` - The cross product
+    pub fn cross(&self, other: &Vector3D) -> Vector3D {
+        Vector3D {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    /// Multiply this vector by another component by component (e.g. to scale each axis by a
+    /// different amount)
+    ///
+    /// # Arguments
+    /// * `other` - The other vector
+    ///
+    /// # Returns
+    /// * `Vector3D` - The component-wise product
+    pub fn hadamard(&self, other: &Vector3D) -> Vector3D {
+        Vector3D {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+        }
+    }
+
+    /// Get the scalar triple product `a · (b × c)`, which is the signed volume of the
+    /// parallelepiped spanned by the three vectors. It is positive when they form a right
+    /// handed set, so it also tells which way round they are wound.
+    ///
+    /// # Arguments
+    /// * `a` - The first vector
+    /// * `b` - The second vector
+    /// * `c` - The third vector
+    ///
+    /// # Returns
+    /// * `f32` - The triple product
+    pub fn triple_product(a: &Vector3D, b: &Vector3D, c: &Vector3D) -> f32 {
+        a.dot(&b.cross(c))
+    }
+
+    /// Get the point a fraction of the way from this vector to another
+    ///
+    /// # Arguments
+    /// * `other` - The other end of the segment
+    /// * `t` - How far along the segment, where 0 is this vector and 1 is `other`
+    ///
+    /// # Returns
+    /// * `Vector3D` - The point along the segment
+    pub fn lerp(&self, other: &Vector3D, t: f32) -> Vector3D {
+        *self + (*other - *self) * t
+    }
+
+    /// Get the point halfway between this vector and another
+    ///
+    /// # Arguments
+    /// * `other` - The other end of the segment
+    ///
+    /// # Returns
+    /// * `Vector3D` - The midpoint of the two vectors
+    pub fn midpoint(&self, other: &Vector3D) -> Vector3D {
+        (*self + *other) * 0.5
+    }
 }
 
 /// It is handy to have vectors already pre-defined if we don't want to type them out by hand.
@@ -96,6 +271,95 @@ impl Display for Vector3D {
     }
 }
 
+impl Add for Vector3D {
+    type Output = Vector3D;
+
+    /// Add two vectors component by component
+    ///
+    /// # Arguments
+    /// * `other` - The vector to add
+    ///
+    /// # Returns
+    /// * `Vector3D` - The sum
+    fn add(self, other: Vector3D) -> Vector3D {
+        Vector3D::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vector3D {
+    type Output = Vector3D;
+
+    /// Subtract a vector component by component
+    ///
+    /// # Arguments
+    /// * `other` - The vector to subtract
+    ///
+    /// # Returns
+    /// * `Vector3D` - The difference
+    fn sub(self, other: Vector3D) -> Vector3D {
+        Vector3D::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vector3D {
+    type Output = Vector3D;
+
+    /// Scale the vector
+    ///
+    /// # Arguments
+    /// * `scalar` - How much to scale by
+    ///
+    /// # Returns
+    /// * `Vector3D` - The scaled vector
+    fn mul(self, scalar: f32) -> Vector3D {
+        Vector3D::new(self.x * scalar, self.y * scalar, self.z * scalar)
+    }
+}
+
+impl Neg for Vector3D {
+    type Output = Vector3D;
+
+    /// Point the vector the opposite way
+    ///
+    /// # Returns
+    /// * `Vector3D` - The negated vector
+    fn neg(self) -> Vector3D {
+        Vector3D::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Vector3D {
+    /// Add a vector to this one in place
+    ///
+    /// # Arguments
+    /// * `other` - The vector to add
+    fn add_assign(&mut self, other: Vector3D) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vector3D {
+    /// Subtract a vector from this one in place
+    ///
+    /// # Arguments
+    /// * `other` - The vector to subtract
+    fn sub_assign(&mut self, other: Vector3D) {
+        *self = *self - other;
+    }
+}
+
+/// One of the three axes of space, e.g. to pick which coordinate of a vector an operation
+/// should act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// The x-axis
+    X,
+    /// The y-axis
+    Y,
+    /// The z-axis
+    Z,
+}
+
 /// There are a plethora of ways to group vertices together.
 /// I am choosing to group them into triangles to comprise a mesh,
 /// instead of other 2d primitives such as a square. This is because
@@ -109,6 +373,9 @@ pub struct Triangle {
     pub b: Vector3D,
     /// `c` - The third vector in the triangle
     pub c: Vector3D,
+    /// `clipped` - Whether the triangle was made by clipping a bigger one, which is only used
+    /// to highlight clipped triangles when debugging the clipper
+    pub clipped: bool,
 }
 
 impl Clone for Triangle {
@@ -121,6 +388,7 @@ impl Clone for Triangle {
             a: self.a.clone(),
             b: self.b.clone(),
             c: self.c.clone(),
+            clipped: self.clipped,
         }
     }
 }
@@ -135,6 +403,7 @@ impl Default for Triangle {
             a: Vector3D::default(),
             b: Vector3D::default(),
             c: Vector3D::default(),
+            clipped: false,
         }
     }
 }
@@ -156,310 +425,4025 @@ impl Matrix {
     pub fn new(mat: [[f32; 4]; 4]) -> Self {
         Self { mat }
     }
-}
 
-impl Clone for Matrix {
-    /// Clone the matrix
+    /// Create the identity matrix, which leaves every vector as it is. This is the starting
+    /// point for transforms, unlike `Matrix::default()` which is all zeros.
     ///
     /// # Returns
-    /// * `Matrix` - The cloned matrix
-    fn clone(&self) -> Self {
-        Self {
-            mat: self.mat.clone(),
-        }
+    /// * `Matrix` - The identity matrix
+    pub fn identity() -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0], // X
+            [0.0, 1.0, 0.0, 0.0], // Y
+            [0.0, 0.0, 1.0, 0.0], // Z
+            [0.0, 0.0, 0.0, 1.0], // W
+        ])
     }
-}
 
-impl Default for Matrix {
-    /// Create a default matrix with all values set to 0
+    /// Create a perspective projection matrix, which takes points in front of the camera to
+    /// normalized device coordinates. The camera looks down +Z with +X to the right and +Y
+    /// down the screen. After `multiply_matrix_vector` does the divide by w (which is the
+    /// point's z), x and y are from -1 to 1 across the view and z is 0 at the near plane and
+    /// 1 at the far plane.
+    ///
+    /// # Arguments
+    /// * `fov_degrees` - The vertical field of view, in degrees
+    /// * `aspect_ratio` - The height of the display divided by its width
+    /// * `near` - The distance to the near plane
+    /// * `far` - The distance to the far plane
     ///
     /// # Returns
-    /// * `Matrix` - The default matrix
-    fn default() -> Self {
-        Self { mat: [[0.0; 4]; 4] }
+    /// * `Matrix` - The projection matrix
+    pub fn projection(fov_degrees: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let scaling_factor = Matrix::scaling_factor(fov_degrees);
+
+        Self::new([
+            [aspect_ratio * scaling_factor, 0.0, 0.0, 0.0],
+            [0.0, scaling_factor, 0.0, 0.0],
+            [0.0, 0.0, far / (far - near), 1.0],
+            [0.0, 0.0, (-far * near) / (far - near), 0.0],
+        ])
     }
-}
 
-impl Triangle {
-    /// This function is an alternative to a raw if statement since doing the alternative if
-    /// statement would break the code under E0317 (if expressions with else evaluate to `()`)
+    /// Get how much a projection scales x and y by for a field of view, which is 1 for a 90
+    /// degree field of view. Wider fields of view shrink everything to fit more in.
     ///
     /// # Arguments
-    /// * `mat` - The matrix to check
+    /// * `fov_degrees` - The field of view, in degrees
     ///
     /// # Returns
-    /// * `Option<&'static str>` - The warning if the matrix is too big
-    pub fn warn_triangle_size(mat: &[Vector3D; 3]) -> Option<&'static str> {
-        if mat.len() > 3 {
-            Some("Triangle is too big, consider splitting it up")
-        } else {
-            None
-        }
+    /// * `f32` - The scaling factor
+    pub fn scaling_factor(fov_degrees: f32) -> f32 {
+        // tan works in radians, so the field of view has to be converted first
+        1.0 / (Degrees(fov_degrees).to_radians().0 / 2.0).tan()
     }
 
-    /// Create a new triangle
+    /// Create a matrix that moves vectors by an offset. The offset goes in the bottom row, as
+    /// that is the row `multiply_matrix_vector` adds on.
     ///
     /// # Arguments
-    /// * `a` - The first vector in the triangle
-    /// * `b` - The second vector in the triangle
-    /// * `c` - The third vector in the triangle
+    /// * `x` - How far to move along the x axis
+    /// * `y` - How far to move along the y axis
+    /// * `z` - How far to move along the z axis
     ///
     /// # Returns
-    /// * `Triangle` - The new triangle
-    pub fn new(a: Vector3D, b: Vector3D, c: Vector3D) -> Self {
-        Self { a, b, c }
+    /// * `Matrix` - The translation matrix
+    pub fn translation(x: f32, y: f32, z: f32) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [x, y, z, 1.0],
+        ])
     }
 
-    /// Draw the triangle on the screen
+    /// Create a matrix that rotates around the x axis, turning y towards z
     ///
     /// # Arguments
-    /// * `canvas` - The canvas to draw the triangle on
+    /// * `theta` - The angle to rotate by, in radians
     ///
     /// # Returns
-    /// * `()` - Nothing
-    pub fn draw(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) -> () {
-        canvas.draw_line(
-            sdl2::rect::Point::new(self.a.x as i32, self.a.y as i32),
-            sdl2::rect::Point::new(self.b.x as i32, self.b.y as i32),
-        );
-        canvas.draw_line(
-            sdl2::rect::Point::new(self.b.x as i32, self.b.y as i32),
-            sdl2::rect::Point::new(self.c.x as i32, self.c.y as i32),
-        );
-        canvas.draw_line(
-            sdl2::rect::Point::new(self.c.x as i32, self.c.y as i32),
-            sdl2::rect::Point::new(self.a.x as i32, self.a.y as i32),
-        );
-    }
-}
+    /// * `Matrix` - The rotation matrix
+    pub fn rotation_x(theta: f32) -> Self {
+        let (sin, cos) = theta.sin_cos();
 
-/// For convenience purposes, we are also going to create a mesh.
-/// This is not only for convenience but also for optimization because
-/// it lets us store vector positions but not process them until we need them,
-/// following the idea of RAII (resource allocation is initialization)
-pub struct Mesh {
-    /// 'mat' stands for matrix
-    pub mat: Vec<Triangle>,
-}
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, sin, 0.0],
+            [0.0, -sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
 
-impl<W> warn::Warn<W> for Mesh {
-    /// Print a warning to the console
+    /// Create a matrix that rotates around the y axis, turning z towards x
     ///
     /// # Arguments
-    /// * `warning` - The warning to print
+    /// * `theta` - The angle to rotate by, in radians
     ///
     /// # Returns
-    /// * `()` - Nothing
-    fn warn(&mut self, warning: W) -> () {
-        println!("{}", stringify!(warning))
-    }
-}
+    /// * `Matrix` - The rotation matrix
+    pub fn rotation_y(theta: f32) -> Self {
+        let (sin, cos) = theta.sin_cos();
 
-/// We should implement functionality for the Mesh, because we want to set certain buffers,
-/// those being resource allocation warnings if the mesh becomes too big. If that happens,
-/// it should send a warning, advising that the mesh be split up.
-///
-/// This is only for scaling purposes i.e. if we want to implement functionality for the engine
-/// wherein a text file containing various 3d points can be loaded in.
-impl Mesh {
-    /// The amount of vectors that a single mesh can contain before being a burden on the computer
-    /// memory. At which point, send a warning/advisement that the mesh should be split up.
-    const VECTOR_LIMIT: usize = 50;
+        Self::new([
+            [cos, 0.0, -sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
 
-    /// This function is an alternative to a raw if statement since doing the alternative if
-    /// statement would break the code under E0317 (if expressions with else evaluate to `()`)
+    /// Create a matrix that rotates around the z axis, turning x towards y
     ///
     /// # Arguments
-    /// * `mat` - The matrix to check
+    /// * `theta` - The angle to rotate by, in radians
     ///
     /// # Returns
-    /// * `Option<&'static str>` - The warning if the matrix is too big
-    pub fn warn_mesh_size(mat: &Vec<Triangle>) -> Option<&'static str> {
-        if mat.len() > Mesh::VECTOR_LIMIT {
-            Some("Mesh is too big, consider splitting it up")
-        } else {
-            None
-        }
+    /// * `Matrix` - The rotation matrix
+    pub fn rotation_z(theta: f32) -> Self {
+        let (sin, cos) = theta.sin_cos();
+
+        Self::new([
+            [cos, sin, 0.0, 0.0],
+            [-sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
     }
 
-    /// Draw the mesh on the screen
+    /// Build the matrix that places an object in the world: scaling it, then rotating it and
+    /// finally moving it into position. Vectors are multiplied on the left of matrices (see
+    /// `multiply_matrix_vector`), so the scale ends up on the rows of the rotation and the
+    /// translation goes in the bottom row.
     ///
     /// # Arguments
-    /// * `canvas` - The canvas to draw the mesh on
+    /// * `translation` - Where to move the object to
+    /// * `rotation` - How to rotate the object, of which only the top left 3x3 is used
+    /// * `scale` - How much to scale the object along each axis
     ///
     /// # Returns
-    /// * `()` - Nothing
-    pub fn draw(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) -> () {
-        unimplemented!()
+    /// * `Matrix` - The combined matrix
+    pub fn from_trs(translation: Vector3D, rotation: &Matrix, scale: Vector3D) -> Matrix {
+        let r = &rotation.mat;
+
+        Matrix::new([
+            [scale.x * r[0][0], scale.x * r[0][1], scale.x * r[0][2], 0.0],
+            [scale.y * r[1][0], scale.y * r[1][1], scale.y * r[1][2], 0.0],
+            [scale.z * r[2][0], scale.z * r[2][1], scale.z * r[2][2], 0.0],
+            [translation.x, translation.y, translation.z, 1.0],
+        ])
     }
 
-    /// Create a new mesh
+    /// Multiply this matrix by another. As vectors are multiplied on the left, the result
+    /// applies this matrix first and then `other` (e.g. `model.multiply(&projection)`), so a
+    /// whole chain of transforms can be combined once and applied to every vertex.
     ///
     /// # Arguments
-    /// * `mat` - The matrix to create the mesh from
+    /// * `other` - The matrix on the right of the product
     ///
     /// # Returns
-    /// * `Mesh` - The new mesh
-    fn new(mat: Vec<Triangle>) -> Self {
-        Self::warn_mesh_size(&mat);
-        Self { mat }
-    }
-}
-
-/// Multiply a vector by a Matrix
-///
-/// # Arguments
-///
-/// * `i` - The input vector
-/// * `o` - The output vector
-/// * `m` - The matrix to multiply the vector by
-///
-/// # Returns
-/// * `o` - The output vector
-///
-/// # Examples
-/// ```
-/// let mut input_vector = Vector3D::new(1.0, 1.0, 1.0);
-/// let mut output_vector = Vector3D::default();
-/// let matrix = Matrix::default();
-/// multiply_matrix_vector(&input_vector, &mut output_vector, &matrix);
-///
-/// assert_eq!(output_vector, Vector3D::default());
-/// ```
-pub fn multiply_matrix_vector<'a>(
-    i: &'a Vector3D,
-    o: &'a mut Vector3D,
-    m: &'a Matrix,
-) -> &'a mut Vector3D {
-    o.x = i.x * m.mat[0][0] + i.y * m.mat[1][0] + i.z * m.mat[2][0] + m.mat[3][0];
-    o.y = i.x * m.mat[0][1] + i.y * m.mat[1][1] + i.z * m.mat[2][1] + m.mat[3][1];
-    o.z = i.x * m.mat[0][2] + i.y * m.mat[1][2] + i.z * m.mat[2][2] + m.mat[3][2];
+    /// * `Matrix` - The product
+    pub fn multiply(&self, other: &Matrix) -> Matrix {
+        let mut product = Matrix::default();
 
-    let w: f32 = i.x * m.mat[0][3] + i.y * m.mat[1][3] + i.z * m.mat[2][3] + m.mat[3][3];
+        for row in 0..4 {
+            for column in 0..4 {
+                product.mat[row][column] = (0..4)
+                    .map(|i| self.mat[row][i] * other.mat[i][column])
+                    .sum();
+            }
+        }
 
-    if w != 0.0 {
-        o.x /= w;
-        o.y /= w;
-        o.z /= w;
+        product
     }
 
-    o
-}
-
-/// The main function of the engine (also runs the game loop)
-pub fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let screen_size = video_subsystem.display_bounds(0).unwrap();
-
-    let display_width = screen_size.width() as f32 / 1.5;
-    let display_height = screen_size.height() as f32 / 1.5;
+    /// Check whether two matrices are equal apart from floating point error
+    ///
+    /// # Arguments
+    /// * `other` - The matrix to compare against
+    /// * `epsilon` - How far apart each pair of entries can be
+    ///
+    /// # Returns
+    /// * `bool` - Whether all 16 entries are within `epsilon` of each other
+    pub fn approx_eq(&self, other: &Matrix, epsilon: f32) -> bool {
+        self.mat
+            .iter()
+            .flatten()
+            .zip(other.mat.iter().flatten())
+            .all(|(a, b)| (a - b).abs() <= epsilon)
+    }
 
-    let field_of_view: f32 = 90.0;
-    let near_plane: f32 = 0.1;
-    let far_plane: f32 = 1000.0;
-    let aspect_ratio: f32 = display_height / display_width;
-    let scaling_factor: f32 = 1.0 / (field_of_view / 2.0).tan();
-    let mut projection_matrix: Matrix = Matrix::default();
-    projection_matrix.mat = [
-        [aspect_ratio * scaling_factor, 0.0, 0.0, 0.0],
-        [0.0, scaling_factor, 0.0, 0.0],
-        [0.0, 0.0, far_plane / (far_plane - near_plane), 1.0],
-        [
-            0.0,
-            0.0,
-            (-far_plane * near_plane) / (far_plane - near_plane),
-            0.0,
-        ],
-    ];
-
-    let mut model_matrix: [[f32; 4]; 4] = [
-        [1.0, 0.0, 0.0, 0.0], // X
-        [0.0, 1.0, 0.0, 0.0], // Y
-        [0.0, 0.0, 1.0, 0.0], // Z
-        [0.0, 0.0, 0.0, 1.0], // W
-    ];
-
-    let mut cube_mesh = cube::get_cube_mesh();
-
-    projection_matrix.mat[0][0] = aspect_ratio * scaling_factor;
-    projection_matrix.mat[1][1] = scaling_factor;
-    projection_matrix.mat[2][2] = far_plane / (far_plane - near_plane);
-    projection_matrix.mat[3][2] = (-far_plane * near_plane) / (far_plane - near_plane);
-    projection_matrix.mat[2][3] = 1.0;
-    projection_matrix.mat[3][3] = 0.0;
+    /// Multiply a point by the matrix without the perspective divide, so the result is left
+    /// in clip space (e.g. for clipping against the view before dividing)
+    ///
+    /// # Arguments
+    /// * `v` - The point to transform
+    ///
+    /// # Returns
+    /// * `Vector3D` - The x, y and z of the transformed point
+    pub fn transform_point(&self, v: &Vector3D) -> Vector3D {
+        let m = &self.mat;
 
-    let window: sdl2::video::Window = video_subsystem
-        .window(
-            "rust-sdl2 demo",
-            display_width as u32,
-            display_height as u32,
+        Vector3D::new(
+            v.x * m[0][0] + v.y * m[1][0] + v.z * m[2][0] + m[3][0],
+            v.x * m[0][1] + v.y * m[1][1] + v.z * m[2][1] + m[3][1],
+            v.x * m[0][2] + v.y * m[1][2] + v.z * m[2][2] + m[3][2],
         )
-        .position_centered()
-        .build()
-        .unwrap();
+    }
 
-    let mut canvas = window.into_canvas().build().unwrap();
+    /// Get the w a point comes out of the matrix with, which is what `perspective_divide`
+    /// divides by. Points are treated as having a w of 1 going in.
+    ///
+    /// # Arguments
+    /// * `v` - The point to transform
+    ///
+    /// # Returns
+    /// * `f32` - The w of the transformed point
+    pub fn transform_w(&self, v: &Vector3D) -> f32 {
+        let m = &self.mat;
 
-    let black: Color = Color::RGB(0, 0, 0);
-    let white: Color = Color::RGB(255, 255, 255);
-    let background_color: Color = black;
+        v.x * m[0][3] + v.y * m[1][3] + v.z * m[2][3] + m[3][3]
+    }
 
-    let mut event_pump = sdl_context.event_pump().unwrap();
+    /// Get the matrix that undoes this one, with Gauss-Jordan elimination
+    ///
+    /// # Returns
+    /// * `Option<Matrix>` - The inverse, or `None` if the matrix squashes space flat and so
+    ///   can't be undone
+    pub fn inverse(&self) -> Option<Matrix> {
+        let mut left = self.mat;
+        let mut right = Matrix::identity().mat;
 
-    let aspect_ratio = display_height as f32 / display_width as f32;
+        for column in 0..4 {
+            // Swap the row with the largest entry in this column up, to keep the error down
+            let pivot = (column..4)
+                .max_by(|&i, &j| left[i][column].abs().total_cmp(&left[j][column].abs()))
+                .unwrap();
+            if left[pivot][column].abs() <= f32::EPSILON {
+                return None;
+            }
+            left.swap(column, pivot);
+            right.swap(column, pivot);
 
-    'running: loop {
-        canvas.set_draw_color(background_color);
-        canvas.clear();
+            let scale = 1.0 / left[column][column];
+            for k in 0..4 {
+                left[column][k] *= scale;
+                right[column][k] *= scale;
+            }
+
+            for row in (0..4).filter(|&row| row != column) {
+                let factor = left[row][column];
+                for k in 0..4 {
+                    left[row][k] -= factor * left[column][k];
+                    right[row][k] -= factor * right[column][k];
+                }
+            }
+        }
+
+        Some(Matrix::new(right))
+    }
+}
+
+impl Clone for Matrix {
+    /// Clone the matrix
+    ///
+    /// # Returns
+    /// * `Matrix` - The cloned matrix
+    fn clone(&self) -> Self {
+        Self {
+            mat: self.mat.clone(),
+        }
+    }
+}
+
+impl Default for Matrix {
+    /// Create a default matrix with all values set to 0
+    ///
+    /// # Returns
+    /// * `Matrix` - The default matrix
+    fn default() -> Self {
+        Self { mat: [[0.0; 4]; 4] }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The warnings printed on this thread, so tests can check what was warned about
+    static WARNINGS: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Print a warning to the console. Under test it is kept as well, for `take_warnings`.
+///
+/// # Arguments
+/// * `warning` - The warning to print
+///
+/// # Returns
+/// * `()` - Nothing
+fn print_warning<W: Display>(warning: W) -> () {
+    let message = format!("Warning: {}", warning);
+
+    #[cfg(test)]
+    WARNINGS.with(|warnings| warnings.borrow_mut().push(message.clone()));
+
+    eprintln!("{}", message);
+}
+
+/// Get the warnings printed on this thread so far, forgetting them
+///
+/// # Returns
+/// * `Vec<String>` - Each warning, as it was printed
+#[cfg(test)]
+fn take_warnings() -> Vec<String> {
+    WARNINGS.with(|warnings| warnings.take())
+}
+
+impl<W: Display> warn::Warn<W> for Triangle {
+    /// Print a warning to the console
+    ///
+    /// # Arguments
+    /// * `warning` - The warning to print
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn warn(&mut self, warning: W) -> () {
+        print_warning(warning)
+    }
+}
+
+impl Triangle {
+    /// The default for how long an edge of a triangle can be before the triangle is a burden
+    /// to draw and light, as it covers so much that it should be split up. This suits meshes
+    /// in the units of the built-in meshes, so models in other units should pass their own.
+    pub const EDGE_LIMIT: f32 = 100.0;
+
+    /// Check that a triangle is worth keeping. A triangle whose corners are on top of each other
+    /// or in a line has no area, so it can't be drawn and has no normal to light it with. A
+    /// triangle with an edge longer than `edge_limit` should be split up.
+    ///
+    /// # Arguments
+    /// * `mat` - The corners of the triangle
+    /// * `edge_limit` - The longest an edge can be, e.g. `Triangle::EDGE_LIMIT`
+    ///
+    /// # Returns
+    /// * `Option<&'static str>` - The warning if the triangle has no area or is too big
+    pub fn warn_triangle_size(mat: &[Vector3D; 3], edge_limit: f32) -> Option<&'static str> {
+        let [a, b, c] = mat;
+        let longest = (*b - *a)
+            .length()
+            .max((*c - *b).length())
+            .max((*a - *c).length());
+
+        if (*b - *a).cross(&(*c - *a)).length() <= f32::EPSILON {
+            Some("Triangle has no area, its corners are on top of each other or in a line")
+        } else if longest > edge_limit {
+            Some("Triangle is too big, consider splitting it up")
+        } else {
+            None
+        }
+    }
+
+    /// Create a new triangle
+    ///
+    /// # Arguments
+    /// * `a` - The first vector in the triangle
+    /// * `b` - The second vector in the triangle
+    /// * `c` - The third vector in the triangle
+    ///
+    /// # Returns
+    /// * `Triangle` - The new triangle
+    pub fn new(a: Vector3D, b: Vector3D, c: Vector3D) -> Self {
+        Self {
+            a,
+            b,
+            c,
+            clipped: false,
+        }
+    }
+
+    /// Get the area of the triangle, which is half the length of the cross product of two of
+    /// its edges
+    ///
+    /// # Returns
+    /// * `f32` - The area of the triangle
+    pub fn area(&self) -> f32 {
+        0.5 * (self.b - self.a).cross(&(self.c - self.a)).length()
+    }
+
+    /// Get the signed volume of the tetrahedron made by the triangle and the origin. Summing
+    /// this over a closed mesh gives the volume of the mesh, as the parts of each tetrahedron
+    /// that fall outside the mesh are cancelled out by triangles facing the other way.
+    ///
+    /// # Returns
+    /// * `f32` - The signed volume, positive when the triangle faces away from the origin
+    pub fn signed_volume(&self) -> f32 {
+        Vector3D::triple_product(&self.a, &self.b, &self.c) / 6.0
+    }
+
+    /// Clip the triangle against a plane, keeping the part in front of it. The plane is every
+    /// point `p` where `normal · p + d = 0`, and in front is where that is positive. Cutting a
+    /// corner off a triangle leaves a quad, so up to two triangles can come out. Any new
+    /// triangles keep the winding of the original and are marked as clipped.
+    ///
+    /// # Arguments
+    /// * `normal` - The normal of the plane, pointing towards the side that is kept
+    /// * `d` - The offset of the plane along its normal
+    ///
+    /// # Returns
+    /// * `ArrayVec<Triangle, 2>` - Nothing if the triangle is fully behind the plane, the
+    ///   triangle itself if it is fully in front, otherwise the one or two triangles left over
+    pub fn clip_against_plane(&self, normal: &Vector3D, d: f32) -> ArrayVec<Triangle, 2> {
+        let distance = |p: &Vector3D| normal.dot(p) + d;
+        let intersect = |start: &Vector3D, end: &Vector3D| {
+            let (start_distance, end_distance) = (distance(start), distance(end));
+            start.lerp(end, start_distance / (start_distance - end_distance))
+        };
+        let clipped = |a: Vector3D, b: Vector3D, c: Vector3D| Triangle {
+            a,
+            b,
+            c,
+            clipped: true,
+        };
+
+        let vertices = [self.a, self.b, self.c];
+        let inside = vertices.map(|vertex| distance(&vertex) >= 0.0);
+        let mut output = ArrayVec::new();
+
+        match inside.iter().filter(|inside| **inside).count() {
+            0 => {}
+            3 => output.push(self.clone()),
+            count => {
+                // Rotate the vertices (which keeps the winding) so that the one on its own side
+                // of the plane comes first
+                let lonely = (0..3).find(|i| inside[*i] == (count == 1)).unwrap();
+                let p0 = vertices[lonely];
+                let p1 = vertices[(lonely + 1) % 3];
+                let p2 = vertices[(lonely + 2) % 3];
+
+                if count == 1 {
+                    output.push(clipped(p0, intersect(&p0, &p1), intersect(&p0, &p2)));
+                } else {
+                    let q1 = intersect(&p1, &p0);
+                    let q2 = intersect(&p2, &p0);
+
+                    output.push(clipped(q1, p1, p2));
+                    output.push(clipped(q1, p2, q2));
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Get the direction the triangle faces, from the cross product of two of its edges, so
+    /// flipping the winding of the triangle turns it around
+    ///
+    /// # Returns
+    /// * `Vector3D` - The unit normal, or zero if the triangle has no area
+    pub fn normal(&self) -> Vector3D {
+        (self.b - self.a).cross(&(self.c - self.a)).normalize()
+    }
+
+    /// Get how sharply the surface bends between this triangle and one next to it, from the
+    /// angle between their normals. Edges with a large angle are creases that should stay
+    /// sharp.
+    ///
+    /// # Arguments
+    /// * `other` - The triangle on the other side of the shared edge
+    ///
+    /// # Returns
+    /// * `f32` - The angle in radians, from 0 for a flat surface up to π where it folds back
+    ///   on itself
+    pub fn dihedral_angle(&self, other: &Triangle) -> f32 {
+        // Rounding can push the dot product of unit normals just past 1
+        self.normal().dot(&other.normal()).clamp(-1.0, 1.0).acos()
+    }
+
+    /// Get which way the triangle faces relative to a point (e.g. the camera). The result is
+    /// the triangle's normal (scaled by twice its area) dotted with the direction from the
+    /// point to the triangle.
+    ///
+    /// # Arguments
+    /// * `camera` - The point to check against
+    ///
+    /// # Returns
+    /// * `f32` - Negative if the triangle faces the point, positive if it faces away and zero
+    ///   if it is edge-on
+    pub fn facing(&self, camera: &Vector3D) -> f32 {
+        let relative = |v: &Vector3D| *v - *camera;
+
+        Vector3D::triple_product(&relative(&self.a), &relative(&self.b), &relative(&self.c))
+    }
+
+    /// Reverse the winding of the triangle by swapping two of its vertices, which turns its
+    /// normal around
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn flip(&mut self) -> () {
+        std::mem::swap(&mut self.b, &mut self.c);
+    }
+
+    /// Get a copy of the triangle with each of its vertices multiplied by a matrix
+    ///
+    /// # Arguments
+    /// * `m` - The matrix to transform the triangle by
+    ///
+    /// # Returns
+    /// * `Triangle` - The transformed triangle
+    pub fn transformed(&self, m: &Matrix) -> Triangle {
+        let mut transformed = self.clone();
+
+        multiply_matrix_vector(&self.a, &mut transformed.a, m);
+        multiply_matrix_vector(&self.b, &mut transformed.b, m);
+        multiply_matrix_vector(&self.c, &mut transformed.c, m);
+
+        transformed
+    }
+
+    /// Take the triangle from the mesh to the screen, projecting each of its vertices
+    ///
+    /// # Arguments
+    /// * `projection` - The projection matrix
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `Triangle` - The triangle in screen coordinates
+    pub fn project_to_screen(&self, projection: &Matrix, width: f32, height: f32) -> Triangle {
+        Triangle {
+            a: project_to_screen(&self.a, projection, width, height),
+            b: project_to_screen(&self.b, projection, width, height),
+            c: project_to_screen(&self.c, projection, width, height),
+            clipped: self.clipped,
+        }
+    }
+
+    /// Get the pixels a projected triangle could cover, clamped to the screen. Everything that
+    /// walks the pixels of a triangle (e.g. filling it) should loop over these bounds.
+    ///
+    /// # Arguments
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `(i32, i32, i32, i32)` - The smallest x and y and the largest x and y, inclusive. If
+    ///   the triangle is fully off screen the smallest will be greater than the largest.
+    pub fn screen_bounds(&self, width: f32, height: f32) -> (i32, i32, i32, i32) {
+        let xs = [self.a.x, self.b.x, self.c.x];
+        let ys = [self.a.y, self.b.y, self.c.y];
+        let min = |values: [f32; 3]| values.into_iter().fold(f32::INFINITY, f32::min);
+        let max = |values: [f32; 3]| values.into_iter().fold(f32::NEG_INFINITY, f32::max);
+
+        (
+            (min(xs).floor() as i32).max(0),
+            (min(ys).floor() as i32).max(0),
+            (max(xs).ceil() as i32).min(width as i32 - 1),
+            (max(ys).ceil() as i32).min(height as i32 - 1),
+        )
+    }
+
+    /// Get how many pixels the triangle covers once it is projected, which is a cheap way to
+    /// judge how much detail it needs. Triangles that are only partly behind the near plane
+    /// should be clipped first, as their projected area is meaningless.
+    ///
+    /// # Arguments
+    /// * `view` - How the camera sees the triangle, which is in world coordinates
+    ///
+    /// # Returns
+    /// * `f32` - The projected area in pixels, or 0 if the triangle is fully behind the near
+    ///   plane
+    pub fn screen_area(&self, view: &View) -> f32 {
+        // The near plane is only a plane of constant z in view space
+        let in_view = self.transformed(&view.matrix);
+        if [in_view.a.z, in_view.b.z, in_view.c.z]
+            .iter()
+            .all(|z| *z < view.near)
+        {
+            return 0.0;
+        }
+
+        let projected = view.project(&in_view);
+        let (a, b, c) = (projected.a, projected.b, projected.c);
+
+        0.5 * ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs()
+    }
+
+    /// Fill in the triangle on the screen, one row of pixels at a time. Only the pixels whose
+    /// centres are inside the triangle and in front of what has already been drawn are filled,
+    /// so triangles with no area draw nothing. With antialiasing on, the pixels along the
+    /// edges whose centres are just outside are blended in by how much of them is covered.
+    ///
+    /// # Arguments
+    /// * `target` - The target to fill the triangle on
+    /// * `color` - The colour to fill the triangle with
+    /// * `depth_buffer` - The depth of what has been drawn so far, which is the same size as
+    ///   the target so the triangle is clipped to it
+    /// * `config` - How the triangle is filled
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn fill<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        color: Color,
+        depth_buffer: &mut DepthBuffer,
+        config: &RasterConfig,
+    ) -> () {
+        // The depth buffer can be a tile of the screen, so the bounds are clamped to wherever
+        // it is rather than to the top left
+        let (left, top) = depth_buffer.origin();
+        let (min_x, min_y, max_x, max_y) = self.screen_bounds(
+            (left + depth_buffer.width as i32) as f32,
+            (top + depth_buffer.height as i32) as f32,
+        );
+        let (min_x, min_y) = (min_x.max(left), min_y.max(top));
+        let area = raster::edge_function(&self.a, &self.b, self.c.x, self.c.y);
+        // The projected depth changes linearly across the screen, so it can be blended with
+        // the screen space weights as they are
+        let depth_at = |x: f32, y: f32| {
+            (raster::edge_function(&self.b, &self.c, x, y) * self.a.z
+                + raster::edge_function(&self.c, &self.a, x, y) * self.b.z
+                + raster::edge_function(&self.a, &self.b, x, y) * self.c.z)
+                / area
+        };
+
+        target.set_draw_color(color);
+
+        for y in min_y..=max_y {
+            // Draw each run of pixels that pass in the row as one line
+            let mut run_start = None;
+
+            for x in min_x..=max_x + 1 {
+                let (sample_x, sample_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                let inside = x <= max_x && raster::contains(self, sample_x, sample_y);
+                let drawn = inside && depth_buffer.test_and_set(x, y, depth_at(sample_x, sample_y));
+
+                // Edge pixels are only blended over what is behind them, so they don't write
+                // their depth and hide a neighbouring triangle that covers the rest of them
+                if config.antialias && x <= max_x && !inside {
+                    let coverage = raster::coverage(self, x, y);
+
+                    if coverage > 0.0 && depth_buffer.test(x, y, depth_at(sample_x, sample_y), 0.0)
+                    {
+                        target.blend_point(sdl2::rect::Point::new(x, y), coverage);
+                    }
+                }
+
+                match (drawn, run_start) {
+                    (true, None) => run_start = Some(x),
+                    (false, Some(start)) => {
+                        target.draw_line(
+                            sdl2::rect::Point::new(start, y),
+                            sdl2::rect::Point::new(x - 1, y),
+                        );
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Draw the edges of the triangle on the screen, hidden wherever something nearer has
+    /// already been filled in. This is how a wireframe is drawn over filled triangles.
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the triangle on
+    /// * `depth_buffer` - The depth of what has been filled in so far, which isn't changed
+    /// * `bias` - How much closer the edges are treated as being, so they win against the
+    ///   fill they lie on (see `DepthConfig::wireframe_bias`)
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw_depth_tested<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        depth_buffer: &DepthBuffer,
+        bias: f32,
+    ) -> () {
+        for (start, end) in [(&self.a, &self.b), (&self.b, &self.c), (&self.c, &self.a)] {
+            raster::draw_depth_tested_line(target, start, end, depth_buffer, bias);
+        }
+    }
+
+    /// Draw the triangle on the screen
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the triangle on
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(&self, target: &mut T) -> () {
+        target.draw_line(
+            sdl2::rect::Point::new(self.a.x as i32, self.a.y as i32),
+            sdl2::rect::Point::new(self.b.x as i32, self.b.y as i32),
+        );
+        target.draw_line(
+            sdl2::rect::Point::new(self.b.x as i32, self.b.y as i32),
+            sdl2::rect::Point::new(self.c.x as i32, self.c.y as i32),
+        );
+        target.draw_line(
+            sdl2::rect::Point::new(self.c.x as i32, self.c.y as i32),
+            sdl2::rect::Point::new(self.a.x as i32, self.a.y as i32),
+        );
+    }
+}
+
+/// Which triangles of a mesh share edges with each other
+pub struct Adjacency {
+    /// `neighbors` - For each triangle, the triangle across each of its edges (`a` to `b`,
+    /// `b` to `c` and `c` to `a`), if there is exactly one
+    pub neighbors: Vec<[Option<usize>; 3]>,
+    /// `edges` - For each edge, as a pair of welded vertex indices (smallest first), the
+    /// triangles that use it
+    pub edges: HashMap<(usize, usize), Vec<usize>>,
+}
+
+/// Why a mesh couldn't be created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeshError {
+    /// The mesh has more triangles than a single mesh should hold
+    TooManyTriangles {
+        /// `triangles` - The amount of triangles in the mesh
+        triangles: usize,
+        /// `limit` - The most triangles a mesh can have
+        limit: usize,
+    },
+}
+
+impl Display for MeshError {
+    /// Print the error
+    ///
+    /// # Arguments
+    /// * `f` - The formatter
+    ///
+    /// # Returns
+    /// * `fmt::Result` - The result of the formatter
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::TooManyTriangles { triangles, limit } => write!(
+                f,
+                "The mesh has {} triangles, but the limit is {}",
+                triangles, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// For convenience purposes, we are also going to create a mesh.
+/// This is not only for convenience but also for optimization because
+/// it lets us store vector positions but not process them until we need them,
+/// following the idea of RAII (resource allocation is initialization)
+pub struct Mesh {
+    /// 'mat' stands for matrix
+    pub mat: Vec<Triangle>,
+    /// `uvs` - The texture coordinates of the corners of each triangle, in the same order as
+    /// `mat`. This is empty when the mesh hasn't been mapped.
+    pub uvs: Vec<[(f32, f32); 3]>,
+    /// `vector_limit` - How many triangles the mesh can have before it is warned about, which
+    /// is `Mesh::VECTOR_LIMIT` unless the mesh was made with `Mesh::with_limit`
+    pub vector_limit: usize,
+}
+
+impl Clone for Mesh {
+    /// Clone the mesh
+    ///
+    /// # Returns
+    /// * `Mesh` - The cloned mesh
+    fn clone(&self) -> Self {
+        Self {
+            mat: self.mat.clone(),
+            uvs: self.uvs.clone(),
+            vector_limit: self.vector_limit,
+        }
+    }
+}
+
+impl<W: Display> warn::Warn<W> for Mesh {
+    /// Print a warning to the console
+    ///
+    /// # Arguments
+    /// * `warning` - The warning to print
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn warn(&mut self, warning: W) -> () {
+        print_warning(warning)
+    }
+}
+
+/// We should implement functionality for the Mesh, because we want to set certain buffers,
+/// those being resource allocation warnings if the mesh becomes too big. If that happens,
+/// it should send a warning, advising that the mesh be split up.
+///
+/// This is only for scaling purposes i.e. if we want to implement functionality for the engine
+/// wherein a text file containing various 3d points can be loaded in.
+impl Mesh {
+    /// The default amount of vectors that a single mesh can contain before being a burden on the
+    /// computer memory. At which point, send a warning/advisement that the mesh should be split
+    /// up. Meshes loaded from real models will usually need a higher limit (see
+    /// `Mesh::with_limit`).
+    pub const VECTOR_LIMIT: usize = 50;
+
+    /// This function is an alternative to a raw if statement since doing the alternative if
+    /// statement would break the code under E0317 (if expressions with else evaluate to `()`)
+    ///
+    /// # Arguments
+    /// * `mat` - The matrix to check
+    /// * `limit` - How many triangles the matrix can have
+    ///
+    /// # Returns
+    /// * `Option<&'static str>` - The warning if the matrix is too big
+    pub fn warn_mesh_size(mat: &Vec<Triangle>, limit: usize) -> Option<&'static str> {
+        if mat.len() > limit {
+            Some("Mesh is too big, consider splitting it up")
+        } else {
+            None
+        }
+    }
+
+    /// Draw the mesh on the screen in the target's current draw colour, projecting each
+    /// triangle with `Triangle::project_to_screen`
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the mesh on
+    /// * `projection` - The projection matrix
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        projection: &Matrix,
+        width: f32,
+        height: f32,
+    ) -> () {
+        for triangle in &self.mat {
+            triangle
+                .project_to_screen(projection, width, height)
+                .draw(target);
+        }
+    }
+
+    /// Create a new mesh
+    ///
+    /// # Arguments
+    /// * `mat` - The matrix to create the mesh from
+    ///
+    /// # Returns
+    /// * `Mesh` - The new mesh
+    fn new(mat: Vec<Triangle>) -> Self {
+        Self::with_limit(mat, Mesh::VECTOR_LIMIT)
+    }
+
+    /// Create a new mesh that can have more (or fewer) triangles than `Mesh::VECTOR_LIMIT`
+    /// before it is warned about
+    ///
+    /// # Arguments
+    /// * `mat` - The matrix to create the mesh from
+    /// * `vector_limit` - How many triangles the mesh can have
+    ///
+    /// # Returns
+    /// * `Mesh` - The new mesh
+    pub fn with_limit(mat: Vec<Triangle>, vector_limit: usize) -> Self {
+        let mut mesh = Self {
+            mat,
+            uvs: Vec::new(),
+            vector_limit,
+        };
+
+        if let Some(warning) = Self::warn_mesh_size(&mesh.mat, mesh.vector_limit) {
+            mesh.warn(warning);
+        }
+
+        // Checking every triangle is too slow to do on every mesh in a release build
+        if cfg!(debug_assertions) && !mesh.assert_consistent_winding() {
+            mesh.warn("Mesh has inconsistent winding, some triangles are facing the wrong way");
+        }
+
+        mesh
+    }
+
+    /// Create a new mesh, failing instead of warning if it has more than `Mesh::VECTOR_LIMIT`
+    /// triangles
+    ///
+    /// # Arguments
+    /// * `mat` - The matrix to create the mesh from
+    ///
+    /// # Returns
+    /// * `Result<Mesh, MeshError>` - The new mesh, or why it couldn't be created
+    pub fn try_new(mat: Vec<Triangle>) -> Result<Self, MeshError> {
+        Self::try_with_limit(mat, Mesh::VECTOR_LIMIT)
+    }
+
+    /// Create a new mesh with its own triangle limit, failing instead of warning if it is
+    /// over that limit
+    ///
+    /// # Arguments
+    /// * `mat` - The matrix to create the mesh from
+    /// * `vector_limit` - How many triangles the mesh can have
+    ///
+    /// # Returns
+    /// * `Result<Mesh, MeshError>` - The new mesh, or why it couldn't be created
+    pub fn try_with_limit(mat: Vec<Triangle>, vector_limit: usize) -> Result<Self, MeshError> {
+        if Self::warn_mesh_size(&mat, vector_limit).is_some() {
+            return Err(MeshError::TooManyTriangles {
+                triangles: mat.len(),
+                limit: vector_limit,
+            });
+        }
+
+        Ok(Self::with_limit(mat, vector_limit))
+    }
+
+    /// Check that the triangles of the mesh are all wound the same way round. This checks
+    /// directed edges rather than the sign of the signed volume, which only means something
+    /// for closed meshes. Two triangles that share an edge and are wound the same way walk
+    /// along that edge in opposite directions, so on a consistently wound mesh every directed
+    /// edge is used by at most one triangle. This works for any shape, including open meshes
+    /// and meshes made of separate pieces. Meshes are warned about as they are made (in debug
+    /// builds) if this fails.
+    ///
+    /// # Returns
+    /// * `bool` - Whether the winding is consistent
+    pub fn assert_consistent_winding(&self) -> bool {
+        let (_, faces) = self.weld_vertices();
+        let mut directed_edges: HashSet<(usize, usize)> = HashSet::new();
+
+        faces.iter().all(|face| {
+            (0..3).all(|corner| {
+                // Edges whose ends were welded together have no direction to check
+                let edge = (face[corner], face[(corner + 1) % 3]);
+                edge.0 == edge.1 || directed_edges.insert(edge)
+            })
+        })
+    }
+
+    /// Check every triangle of the mesh with `Triangle::warn_triangle_size`, warning once
+    /// with how many failed rather than once for each. This is meant for when a mesh is
+    /// loaded, not for meshes that are rebuilt every frame.
+    ///
+    /// # Arguments
+    /// * `edge_limit` - The longest an edge can be, in the units of the mesh
+    ///
+    /// # Returns
+    /// * `usize` - The amount of triangles that have no area or are too big
+    pub fn warn_triangle_sizes(&mut self, edge_limit: f32) -> usize {
+        let failed = self
+            .mat
+            .iter()
+            .filter(|triangle| {
+                Triangle::warn_triangle_size(&[triangle.a, triangle.b, triangle.c], edge_limit)
+                    .is_some()
+            })
+            .count();
+
+        if failed > 0 {
+            self.warn(format!(
+                "{} triangles have no area or have an edge longer than {}",
+                failed, edge_limit
+            ));
+        }
+
+        failed
+    }
+
+    /// Merge vertices that are in exactly the same place, so that triangles which share a
+    /// corner share an index
+    ///
+    /// # Returns
+    /// * `(Vec<Vector3D>, Vec<[usize; 3]>)` - The unique vertices, and the indices of the
+    ///   corners of each triangle into them
+    pub fn weld_vertices(&self) -> (Vec<Vector3D>, Vec<[usize; 3]>) {
+        let mut indices: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut vertices = Vec::new();
+
+        let faces = self
+            .mat
+            .iter()
+            .map(|triangle| {
+                [triangle.a, triangle.b, triangle.c].map(|vertex| {
+                    *indices
+                        .entry([vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()])
+                        .or_insert_with(|| {
+                            vertices.push(vertex);
+                            vertices.len() - 1
+                        })
+                })
+            })
+            .collect();
+
+        (vertices, faces)
+    }
+
+    /// Work out which triangles share an edge with each other, using welded vertices so that
+    /// triangles only need to touch rather than share the same `Vector3D`s
+    ///
+    /// # Returns
+    /// * `Adjacency` - The neighbours of each triangle
+    pub fn build_adjacency(&self) -> Adjacency {
+        let (_, faces) = self.weld_vertices();
+        let edge_key = |start: usize, end: usize| (start.min(end), start.max(end));
+
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+
+        for (triangle, face) in faces.iter().enumerate() {
+            for corner in 0..3 {
+                edges
+                    .entry(edge_key(face[corner], face[(corner + 1) % 3]))
+                    .or_default()
+                    .push(triangle);
+            }
+        }
+
+        let neighbors = faces
+            .iter()
+            .enumerate()
+            .map(|(triangle, face)| {
+                [0, 1, 2].map(|corner| {
+                    match edges[&edge_key(face[corner], face[(corner + 1) % 3])][..] {
+                        // An edge shared by more than two triangles has no single neighbour
+                        [first, second] if first == triangle => Some(second),
+                        [first, second] if second == triangle => Some(first),
+                        _ => None,
+                    }
+                })
+            })
+            .collect();
+
+        Adjacency { neighbors, edges }
+    }
+
+    /// Count the edges that aren't shared by exactly two triangles - the edges around holes,
+    /// and edges where more than two triangles meet
+    ///
+    /// # Returns
+    /// * `usize` - The amount of boundary edges
+    pub fn boundary_edge_count(&self) -> usize {
+        self.build_adjacency()
+            .edges
+            .values()
+            .filter(|triangles| triangles.len() != 2)
+            .count()
+    }
+
+    /// Check whether the mesh is closed, with every edge shared by exactly two triangles, so it
+    /// can be treated as a solid. Use `boundary_edge_count` to find out how far off it is.
+    ///
+    /// # Returns
+    /// * `bool` - Whether the mesh is watertight
+    pub fn is_watertight(&self) -> bool {
+        self.boundary_edge_count() == 0
+    }
+
+    /// Split the mesh into the separate pieces it is made of, e.g. to turn an imported model
+    /// with several parts into an object for each. Triangles are in the same piece when they
+    /// are joined by a chain of shared corners, using welded vertices so they only have to
+    /// touch. Each piece keeps the limit and texture coordinates of the mesh.
+    ///
+    /// # Returns
+    /// * `Vec<Mesh>` - The pieces, in the order their first triangle appears in the mesh
+    pub fn connected_components(&self) -> Vec<Mesh> {
+        let (vertices, faces) = self.weld_vertices();
+
+        // Union-find over the vertices, where each set ends up being one piece
+        let mut parents: Vec<usize> = (0..vertices.len()).collect();
+        fn root(parents: &mut [usize], mut vertex: usize) -> usize {
+            while parents[vertex] != vertex {
+                // Halve the path on the way up so later lookups are quicker
+                parents[vertex] = parents[parents[vertex]];
+                vertex = parents[vertex];
+            }
+
+            vertex
+        }
+
+        for [a, b, c] in &faces {
+            let a = root(&mut parents, *a);
+            for corner in [*b, *c] {
+                let corner = root(&mut parents, corner);
+                parents[corner] = a;
+            }
+        }
+
+        // The triangles in each piece, by index
+        let mut pieces: HashMap<usize, usize> = HashMap::new();
+        let mut components: Vec<Vec<usize>> = Vec::new();
+
+        for (triangle, face) in faces.iter().enumerate() {
+            let piece = *pieces
+                .entry(root(&mut parents, face[0]))
+                .or_insert_with(|| {
+                    components.push(Vec::new());
+                    components.len() - 1
+                });
+
+            components[piece].push(triangle);
+        }
+
+        let has_uvs = self.uvs.len() == self.mat.len();
+
+        components
+            .into_iter()
+            .map(|triangles| {
+                let mut mesh = Mesh::with_limit(
+                    triangles.iter().map(|i| self.mat[*i].clone()).collect(),
+                    self.vector_limit,
+                );
+                if has_uvs {
+                    mesh.uvs = triangles.iter().map(|i| self.uvs[*i]).collect();
+                }
+
+                mesh
+            })
+            .collect()
+    }
+
+    /// Get the edges that outline the mesh from a point of view, which are the edges between
+    /// a triangle facing the camera and one facing away. Edges on the border of an open mesh
+    /// are included when their triangle faces the camera.
+    ///
+    /// # Arguments
+    /// * `camera` - Where the mesh is being looked at from
+    ///
+    /// # Returns
+    /// * `Vec<(Vector3D, Vector3D)>` - The silhouette edges
+    pub fn silhouette_edges(&self, camera: &Vector3D) -> Vec<(Vector3D, Vector3D)> {
+        let (vertices, _) = self.weld_vertices();
+        let adjacency = self.build_adjacency();
+        let front_facing: Vec<bool> = self
+            .mat
+            .iter()
+            .map(|triangle| triangle.facing(camera) < 0.0)
+            .collect();
+
+        adjacency
+            .edges
+            .iter()
+            .filter(|(_, triangles)| match triangles[..] {
+                [only] => front_facing[only],
+                [first, second] => front_facing[first] != front_facing[second],
+                _ => false,
+            })
+            .map(|((start, end), _)| (vertices[*start], vertices[*end]))
+            .collect()
+    }
+
+    /// Get every edge of the mesh exactly once. Triangles that share an edge would otherwise
+    /// draw it twice, so edges are compared by their end points regardless of direction.
+    ///
+    /// # Returns
+    /// * `Vec<(Vector3D, Vector3D)>` - The unique edges, in the order they are first seen
+    pub fn unique_edges(&self) -> Vec<(Vector3D, Vector3D)> {
+        let key = |v: &Vector3D| [v.x.to_bits(), v.y.to_bits(), v.z.to_bits()];
+
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+
+        for triangle in &self.mat {
+            for (start, end) in [
+                (triangle.a, triangle.b),
+                (triangle.b, triangle.c),
+                (triangle.c, triangle.a),
+            ] {
+                let (first, second) = (key(&start), key(&end));
+                let edge_key = if first <= second {
+                    (first, second)
+                } else {
+                    (second, first)
+                };
+
+                if seen.insert(edge_key) {
+                    edges.push((start, end));
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Remove every triangle with (almost) no area. These are left behind by importing and
+    /// subdividing, cost a draw each and have no sensible normal.
+    ///
+    /// # Arguments
+    /// * `epsilon` - The area at or below which a triangle counts as degenerate
+    ///
+    /// # Returns
+    /// * `usize` - How many triangles were removed
+    pub fn remove_degenerate_triangles(&mut self, epsilon: f32) -> usize {
+        let before = self.mat.len();
+        let keep: Vec<bool> = self
+            .mat
+            .iter()
+            .map(|triangle| triangle.area() > epsilon)
+            .collect();
+
+        // The texture coordinates of a removed triangle go with it
+        if self.uvs.len() == before {
+            let mut keep = keep.iter();
+            self.uvs.retain(|_| *keep.next().unwrap());
+        }
+        let mut keep = keep.iter();
+        self.mat.retain(|_| *keep.next().unwrap());
+
+        before - self.mat.len()
+    }
+
+    /// Estimate how much memory the mesh takes up. Every triangle stores its own copy of each
+    /// vertex, so this grows with the triangle count even when vertices are shared.
+    ///
+    /// # Returns
+    /// * `usize` - The size of the mesh itself plus the triangles and texture coordinates it
+    ///   has room for, in bytes
+    pub fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Mesh>()
+            + self.mat.capacity() * std::mem::size_of::<Triangle>()
+            + self.uvs.capacity() * std::mem::size_of::<[(f32, f32); 3]>()
+    }
+
+    /// Get the total surface area of the mesh
+    ///
+    /// # Returns
+    /// * `f32` - The sum of the areas of every triangle in the mesh
+    pub fn surface_area(&self) -> f32 {
+        self.mat.iter().map(Triangle::area).sum()
+    }
+
+    /// Get the average direction the mesh faces, with bigger triangles counting for more. The
+    /// normals of a closed mesh cancel out, so this is only meaningful for open surfaces.
+    ///
+    /// # Returns
+    /// * `Vector3D` - The area weighted average of the triangle normals, or zero if the mesh
+    ///   has no area
+    pub fn average_normal(&self) -> Vector3D {
+        let total_area = self.surface_area();
+
+        if total_area == 0.0 {
+            return Vector3D::default();
+        }
+
+        self.mat.iter().fold(Vector3D::default(), |sum, triangle| {
+            sum + triangle.normal() * triangle.area()
+        }) * (1.0 / total_area)
+    }
+
+    /// Get the volume enclosed by the mesh. This assumes the mesh is closed (has no holes)
+    /// and that its triangles are wound so that they face outwards, otherwise the result is
+    /// meaningless or negative.
+    ///
+    /// # Returns
+    /// * `f32` - The volume of the mesh
+    pub fn volume(&self) -> f32 {
+        self.mat.iter().map(Triangle::signed_volume).sum()
+    }
+
+    /// Get the smallest box lined up with the axes that contains the whole mesh
+    ///
+    /// # Returns
+    /// * `Option<(Vector3D, Vector3D)>` - The smallest and largest corners of the box, or
+    ///   `None` if the mesh has no triangles
+    pub fn bounding_box(&self) -> Option<(Vector3D, Vector3D)> {
+        let mut vertices = self
+            .mat
+            .iter()
+            .flat_map(|triangle| [triangle.a, triangle.b, triangle.c]);
+        let first = vertices.next()?;
+
+        Some(vertices.fold((first, first), |(min, max), vertex| {
+            (
+                Vector3D::new(
+                    min.x.min(vertex.x),
+                    min.y.min(vertex.y),
+                    min.z.min(vertex.z),
+                ),
+                Vector3D::new(
+                    max.x.max(vertex.x),
+                    max.y.max(vertex.y),
+                    max.z.max(vertex.z),
+                ),
+            )
+        }))
+    }
+
+    /// Get a sphere that contains the whole mesh, using Ritter's algorithm. The sphere is
+    /// not always the smallest possible, but it is found in two passes over the vertices and
+    /// is usually within a few percent.
+    ///
+    /// # Returns
+    /// * `(Vector3D, f32)` - The center and radius of the sphere, which is a point at the
+    ///   origin for an empty mesh
+    pub fn bounding_sphere(&self) -> (Vector3D, f32) {
+        let vertices: Vec<Vector3D> = self
+            .mat
+            .iter()
+            .flat_map(|triangle| [triangle.a, triangle.b, triangle.c])
+            .collect();
+
+        let first = match vertices.first() {
+            Some(vertex) => *vertex,
+            None => return (Vector3D::default(), 0.0),
+        };
+        let farthest_from = |from: &Vector3D| {
+            *vertices
+                .iter()
+                .max_by(|a, b| from.distance(a).total_cmp(&from.distance(b)))
+                .unwrap()
+        };
+
+        // Start with the sphere spanning two vertices that are roughly the furthest apart
+        let y = farthest_from(&first);
+        let z = farthest_from(&y);
+        let mut center = y.midpoint(&z);
+        let mut radius = y.distance(&z) / 2.0;
+
+        // Then grow it just enough to take in any vertex that is still outside
+        for vertex in &vertices {
+            let distance = center.distance(vertex);
+
+            if distance > radius {
+                let new_radius = (radius + distance) / 2.0;
+                let shift = (new_radius - radius) / distance;
+
+                center += (*vertex - center) * shift;
+                radius = new_radius;
+            }
+        }
+
+        (center, radius)
+    }
+
+    /// Find the triangle that is drawn at a pixel. Where triangles overlap the one nearest the
+    /// camera is picked. Everything that is drawn is picked through `Scene::pick`, which
+    /// checks each of its meshes with this.
+    ///
+    /// # Arguments
+    /// * `pixel` - The pixel, in screen coordinates
+    /// * `projection` - The view and projection matrix the mesh is drawn with
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `Option<(usize, Vector3D, f32)>` - The index of the triangle, the point on it (in mesh
+    ///   coordinates) under the pixel and how far in front of the camera it is, if any triangle
+    ///   is hit
+    pub(crate) fn pick(
+        &self,
+        pixel: (i32, i32),
+        projection: &Matrix,
+        width: f32,
+        height: f32,
+    ) -> Option<(usize, Vector3D, f32)> {
+        // Aim for the middle of the pixel rather than its corner
+        let (x, y) = (pixel.0 as f32 + 0.5, pixel.1 as f32 + 0.5);
+        let mut nearest: Option<(usize, Vector3D, f32)> = None;
+
+        for (index, triangle) in self.mat.iter().enumerate() {
+            let projected = triangle.project_to_screen(projection, width, height);
+
+            if !raster::contains(&projected, x, y) {
+                continue;
+            }
+
+            // Screen space weights of each corner, which need correcting for perspective as
+            // the far corners of the triangle are squashed together on the screen
+            let area =
+                raster::edge_function(&projected.a, &projected.b, projected.c.x, projected.c.y);
+            let weights = [
+                raster::edge_function(&projected.b, &projected.c, x, y) / area,
+                raster::edge_function(&projected.c, &projected.a, x, y) / area,
+                raster::edge_function(&projected.a, &projected.b, x, y) / area,
+            ];
+            // The perspective comes from the w each corner was divided by as it was projected,
+            // so it has to come out of the same matrix rather than the mesh's own z
+            let vertices = [triangle.a, triangle.b, triangle.c];
+            let corrected = [0, 1, 2].map(|i| weights[i] / projection.transform_w(&vertices[i]));
+            let total: f32 = corrected.iter().sum();
+
+            let depth = 1.0 / total;
+            let point = Vector3D::new(
+                (0..3).map(|i| vertices[i].x * corrected[i]).sum::<f32>() / total,
+                (0..3).map(|i| vertices[i].y * corrected[i]).sum::<f32>() / total,
+                (0..3).map(|i| vertices[i].z * corrected[i]).sum::<f32>() / total,
+            );
+
+            if depth > 0.0 && nearest.is_none_or(|(_, _, nearest)| depth < nearest) {
+                nearest = Some((index, point, depth));
+            }
+        }
+
+        nearest
+    }
+
+    /// Render the edges of the mesh as ASCII art, which is a quick way to check a mesh looks
+    /// right without opening a window
+    ///
+    /// # Arguments
+    /// * `width` - The amount of characters in each row
+    /// * `height` - The amount of rows
+    /// * `projection` - The projection matrix
+    ///
+    /// # Returns
+    /// * `String` - The rows of the preview separated by newlines, with `#` for the edges
+    pub fn ascii_preview(&self, width: usize, height: usize, projection: &Matrix) -> String {
+        let mut grid = vec![vec![' '; width]; height];
+
+        for (start, end) in self.unique_edges() {
+            let start = project_to_screen(&start, projection, width as f32, height as f32);
+            let end = project_to_screen(&end, projection, width as f32, height as f32);
+
+            // Step along the edge once per character it crosses, capped so that an edge that
+            // projects far off the grid doesn't take forever
+            let steps = ((end.x - start.x).abs().max((end.y - start.y).abs()).ceil() as usize)
+                .min((width + height) * 2);
+
+            for step in 0..=steps {
+                let t = if steps == 0 {
+                    0.0
+                } else {
+                    step as f32 / steps as f32
+                };
+                let x = (start.x + (end.x - start.x) * t).round();
+                let y = (start.y + (end.y - start.y) * t).round();
+
+                if x >= 0.0 && y >= 0.0 && (x as usize) < width && (y as usize) < height {
+                    grid[y as usize][x as usize] = '#';
+                }
+            }
+        }
+
+        grid.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Get the cross section of the mesh where it meets a plane. The plane is every point `p`
+    /// where `plane_normal · p + plane_d = 0`.
+    ///
+    /// # Arguments
+    /// * `plane_normal` - The normal of the plane
+    /// * `plane_d` - The offset of the plane along its normal
+    ///
+    /// # Returns
+    /// * `LineMesh` - A segment for each triangle that crosses the plane, which join up into
+    ///   closed loops for a closed mesh
+    pub fn slice(&self, plane_normal: &Vector3D, plane_d: f32) -> LineMesh {
+        let distance = |p: &Vector3D| plane_normal.dot(p) + plane_d;
+
+        let mut lines = Vec::new();
+
+        for triangle in &self.mat {
+            let mut crossings = Vec::with_capacity(2);
+
+            for (start, end) in [
+                (triangle.a, triangle.b),
+                (triangle.b, triangle.c),
+                (triangle.c, triangle.a),
+            ] {
+                let (start_distance, end_distance) = (distance(&start), distance(&end));
+
+                // Points on the plane count as being in front of it, so that an edge touching
+                // the plane is only counted once by the two edges that share its vertex
+                if (start_distance >= 0.0) != (end_distance >= 0.0) {
+                    let t = start_distance / (start_distance - end_distance);
+                    crossings.push(start.lerp(&end, t));
+                }
+            }
+
+            if let [start, end] = crossings[..] {
+                lines.push((start, end));
+            }
+        }
+
+        LineMesh::new(lines)
+    }
+
+    /// Mirror the mesh across the plane through the origin that is perpendicular to an axis.
+    /// Mirroring turns the winding of every triangle inside out, so each one has two of its
+    /// vertices swapped to keep its normal pointing outwards.
+    ///
+    /// # Arguments
+    /// * `axis` - The axis whose coordinate is negated
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn mirror(&mut self, axis: Axis) -> () {
+        for triangle in self.mat.iter_mut() {
+            for vertex in [&mut triangle.a, &mut triangle.b, &mut triangle.c] {
+                match axis {
+                    Axis::X => vertex.x = -vertex.x,
+                    Axis::Y => vertex.y = -vertex.y,
+                    Axis::Z => vertex.z = -vertex.z,
+                }
+            }
+
+            triangle.flip();
+        }
+    }
+
+    /// Multiply every vertex of the mesh by a matrix, baking the transform into the mesh (e.g.
+    /// to move an imported model into place once rather than every frame)
+    ///
+    /// # Arguments
+    /// * `m` - The matrix to transform the mesh by
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn transform(&mut self, m: &Matrix) -> () {
+        for triangle in &mut self.mat {
+            *triangle = triangle.transformed(m);
+        }
+    }
+
+    /// Move the mesh so the middle of its bounding box is at the origin
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn center(&mut self) -> () {
+        if let Some((min, max)) = self.bounding_box() {
+            let middle = min.midpoint(&max);
+
+            self.transform(&Matrix::translation(-middle.x, -middle.y, -middle.z));
+        }
+    }
+
+    /// Scale the mesh the same amount along every axis so that its longest side is one unit
+    /// long, which makes it fit in a unit cube. The mesh is scaled around the origin, so it
+    /// should usually be centered first.
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn scale_to_unit(&mut self) -> () {
+        if let Some((min, max)) = self.bounding_box() {
+            let size = max - min;
+            let longest = size.x.max(size.y).max(size.z);
+
+            // A mesh that is a single point can't be scaled up to any size
+            if longest > 0.0 {
+                let scale = 1.0 / longest;
+
+                self.transform(&Matrix::from_trs(
+                    Vector3D::default(),
+                    &Matrix::identity(),
+                    Vector3D::new(scale, scale, scale),
+                ));
+            }
+        }
+    }
+
+    /// Map the mesh by projecting it flat along an axis, so the texture is stretched across
+    /// the bounding box of the mesh as seen from that axis. This suits flat or nearly flat
+    /// meshes, e.g. projecting a ground plane along `Axis::Y` maps x and z onto the texture.
+    ///
+    /// # Arguments
+    /// * `axis` - The axis to project along, which the texture is seen down
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn generate_planar_uvs(&mut self, axis: Axis) -> () {
+        let Some((min, max)) = self.bounding_box() else {
+            self.uvs.clear();
+            return;
+        };
+
+        // The two coordinates left over after dropping the axis become u and v
+        let plane = |v: &Vector3D| match axis {
+            Axis::X => (v.z, v.y),
+            Axis::Y => (v.x, v.z),
+            Axis::Z => (v.x, v.y),
+        };
+        let (min_u, min_v) = plane(&min);
+        let (max_u, max_v) = plane(&max);
+
+        // A mesh with no size along u or v is mapped to the edge of the texture
+        let fraction = |value: f32, min: f32, max: f32| {
+            if max > min {
+                (value - min) / (max - min)
+            } else {
+                0.0
+            }
+        };
+        let uv = |v: &Vector3D| {
+            let (u, v) = plane(v);
+
+            (fraction(u, min_u, max_u), fraction(v, min_v, max_v))
+        };
+
+        self.uvs = self
+            .mat
+            .iter()
+            .map(|triangle| [uv(&triangle.a), uv(&triangle.b), uv(&triangle.c)])
+            .collect();
+    }
+
+    /// Map the mesh by projecting it onto a sphere around its center, the way a map of the
+    /// world is wrapped around a globe. This suits round meshes like the icosphere. The
+    /// texture wraps around the y-axis, so there is a seam where u goes from 1 back to 0.
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn generate_spherical_uvs(&mut self) -> () {
+        let Some((min, max)) = self.bounding_box() else {
+            self.uvs.clear();
+            return;
+        };
+        let middle = min.midpoint(&max);
+
+        // Longitude goes around the texture and latitude goes down it, with -y at the top
+        // since y points down on the screen
+        let uv = |v: &Vector3D| {
+            let direction = (*v - middle).normalize();
+
+            (
+                0.5 + direction.z.atan2(direction.x) / std::f32::consts::TAU,
+                0.5 + direction.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI,
+            )
+        };
+
+        self.uvs = self
+            .mat
+            .iter()
+            .map(|triangle| [uv(&triangle.a), uv(&triangle.b), uv(&triangle.c)])
+            .collect();
+    }
+
+    /// Get a copy of the mesh with every vertex multiplied by a matrix
+    ///
+    /// # Arguments
+    /// * `m` - The matrix to transform the mesh by
+    ///
+    /// # Returns
+    /// * `Mesh` - The transformed mesh
+    pub fn transformed(&self, m: &Matrix) -> Mesh {
+        Mesh {
+            mat: self
+                .mat
+                .iter()
+                .map(|triangle| triangle.transformed(m))
+                .collect(),
+            uvs: self.uvs.clone(),
+            vector_limit: self.vector_limit,
+        }
+    }
+
+    /// Flip every triangle of the mesh, which is the quick fix for a mesh that was imported
+    /// inside out
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn flip_all(&mut self) -> () {
+        self.mat.iter_mut().for_each(Triangle::flip);
+    }
+
+    /// Subdivide every triangle of the mesh into four by splitting each edge at its midpoint.
+    /// Each call multiplies the triangle count by four, so the size warning is worth checking
+    /// before keeping the result.
+    ///
+    /// # Returns
+    /// * `Mesh` - The subdivided mesh
+    pub fn subdivide(&self) -> Mesh {
+        let mut mat = Vec::with_capacity(self.mat.len() * 4);
+
+        for triangle in &self.mat {
+            let ab = triangle.a.midpoint(&triangle.b);
+            let bc = triangle.b.midpoint(&triangle.c);
+            let ca = triangle.c.midpoint(&triangle.a);
+
+            mat.push(Triangle::new(triangle.a, ab, ca));
+            mat.push(Triangle::new(ab, triangle.b, bc));
+            mat.push(Triangle::new(ca, bc, triangle.c));
+            mat.push(Triangle::new(ab, bc, ca));
+        }
+
+        Mesh::with_limit(mat, self.vector_limit)
+    }
+
+    /// Subdivide a mesh a number of times in a row, starting from the mesh itself
+    ///
+    /// # Arguments
+    /// * `level` - How many times to subdivide
+    ///
+    /// # Returns
+    /// * `Mesh` - The subdivided mesh (a plain copy when `level` is 0)
+    pub fn subdivide_levels(&self, level: u32) -> Mesh {
+        (0..level).fold(self.clone(), |mesh, _| mesh.subdivide())
+    }
+}
+
+/// Not everything that should be drawn is a surface, e.g. skeletons or graphs are better
+/// represented as a list of separate line segments.
+pub struct LineMesh {
+    /// `lines` - The start and end of each line segment
+    pub lines: Vec<(Vector3D, Vector3D)>,
+}
+
+impl LineMesh {
+    /// Create a new line mesh
+    ///
+    /// # Arguments
+    /// * `lines` - The start and end of each line segment
+    ///
+    /// # Returns
+    /// * `LineMesh` - The new line mesh
+    pub fn new(lines: Vec<(Vector3D, Vector3D)>) -> Self {
+        Self { lines }
+    }
+
+    /// Draw the line mesh on the screen, projecting each segment the same way as a triangle
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the line mesh on
+    /// * `projection` - The projection matrix
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        projection: &Matrix,
+        width: f32,
+        height: f32,
+    ) -> () {
+        for (start, end) in &self.lines {
+            let start = project_to_screen(start, projection, width, height);
+            let end = project_to_screen(end, projection, width, height);
+
+            target.draw_line(
+                sdl2::rect::Point::new(start.x as i32, start.y as i32),
+                sdl2::rect::Point::new(end.x as i32, end.y as i32),
+            );
+        }
+    }
+}
+
+/// Point clouds (e.g. from a scanner) have no connectivity at all, so each vertex is drawn
+/// on its own.
+pub struct PointCloud {
+    /// `points` - The points in the cloud
+    pub points: Vec<Vector3D>,
+}
+
+impl PointCloud {
+    /// Create a new point cloud
+    ///
+    /// # Arguments
+    /// * `points` - The points in the cloud
+    ///
+    /// # Returns
+    /// * `PointCloud` - The new point cloud
+    pub fn new(points: Vec<Vector3D>) -> Self {
+        Self { points }
+    }
+
+    /// Draw the point cloud on the screen, projecting each point the same way as a triangle
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the point cloud on
+    /// * `projection` - The projection matrix
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        projection: &Matrix,
+        width: f32,
+        height: f32,
+    ) -> () {
+        for point in &self.points {
+            let point = project_to_screen(point, projection, width, height);
+
+            target.draw_point(sdl2::rect::Point::new(point.x as i32, point.y as i32));
+        }
+    }
+}
+
+/// Multiply a vector by a Matrix
+///
+/// # Arguments
+///
+/// * `i` - The input vector
+/// * `o` - The output vector
+/// * `m` - The matrix to multiply the vector by
+///
+/// # Returns
+/// * `o` - The output vector
+///
+/// # Examples
+/// ```
+/// let mut input_vector = Vector3D::new(1.0, 1.0, 1.0);
+/// let mut output_vector = Vector3D::default();
+/// let matrix = Matrix::default();
+/// multiply_matrix_vector(&input_vector, &mut output_vector, &matrix);
+///
+/// assert_eq!(output_vector, Vector3D::default());
+/// ```
+pub fn multiply_matrix_vector<'a>(
+    i: &'a Vector3D,
+    o: &'a mut Vector3D,
+    m: &'a Matrix,
+) -> &'a mut Vector3D {
+    *o = perspective_divide(&m.transform_point(i), m.transform_w(i));
+
+    o
+}
+
+/// Clip a triangle against the near plane before it is projected. Corners behind the camera
+/// or very close to it have a w that is negative or almost zero, and dividing by it throws
+/// them to the wrong side of the screen or off to infinity, so they are cut off first.
+///
+/// # Arguments
+/// * `tri` - The triangle in view space, where the camera looks along +z
+/// * `near` - The z of the near plane in view space (see `View::near`)
+///
+/// # Returns
+/// * `Vec<Triangle>` - Nothing if the triangle is entirely behind the plane, the triangle as
+///   it is if it is entirely in front, or one or two clipped triangles if it straddles it
+pub fn clip_triangle_near(tri: &Triangle, near: f32) -> Vec<Triangle> {
+    tri.clip_against_plane(&Vector3D::new(0.0, 0.0, 1.0), -near)
+        .into_iter()
+        .collect()
+}
+
+/// Clip a line against the near plane before it is projected, for the same reason as
+/// `clip_triangle_near`
+///
+/// # Arguments
+/// * `start` - The start of the line in view space, where the camera looks along +z
+/// * `end` - The end of the line in view space
+/// * `near` - The z of the near plane in view space (see `View::near`)
+///
+/// # Returns
+/// * `Option<(Vector3D, Vector3D)>` - The part of the line in front of the plane, going the
+///   same way, or `None` if it is entirely behind it
+pub fn clip_line_near(start: &Vector3D, end: &Vector3D, near: f32) -> Option<(Vector3D, Vector3D)> {
+    let crossing = || start.lerp(end, (near - start.z) / (end.z - start.z));
+
+    match (start.z >= near, end.z >= near) {
+        (true, true) => Some((*start, *end)),
+        (true, false) => Some((*start, crossing())),
+        (false, true) => Some((crossing(), *end)),
+        (false, false) => None,
+    }
+}
+
+/// Divide a vector by its w, taking it from clip space to normalized device coordinates where
+/// anything on screen is between -1 and 1
+///
+/// # Arguments
+/// * `v` - The vector in clip space
+/// * `w` - The w that came out of the matrix with the vector
+///
+/// # Returns
+/// * `Vector3D` - The divided vector, or the vector as it is if `w` is zero
+pub fn perspective_divide(v: &Vector3D, w: f32) -> Vector3D {
+    if w != 0.0 {
+        Vector3D::new(v.x / w, v.y / w, v.z / w)
+    } else {
+        *v
+    }
+}
+
+/// Take a vector all the way to the screen: project it and then scale it into view. The
+/// matrix is applied as it is, so it should take the vector into view space as well as
+/// project it, e.g. `View::view_projection`, unless the vector is already in view space.
+///
+/// # Arguments
+/// * `v` - The vector to project
+/// * `projection` - The projection matrix, or the view and projection matrix
+/// * `width` - The width of the display
+/// * `height` - The height of the display
+///
+/// # Returns
+/// * `Vector3D` - The vector in screen coordinates
+pub fn project_to_screen(v: &Vector3D, projection: &Matrix, width: f32, height: f32) -> Vector3D {
+    let mut projected = Vector3D::default();
+
+    multiply_matrix_vector(v, &mut projected, projection);
+
+    ndc_to_screen(&projected, &Viewport::full(width, height))
+}
+
+/// Scale a projected vector into view. The projection leaves x and y between -1 and 1 (its
+/// normalized device coordinates), which are stretched to fill the viewport.
+///
+/// # Arguments
+/// * `v` - The projected vector
+/// * `viewport` - The part of the screen to map the vector into
+///
+/// # Returns
+/// * `Vector3D` - The vector in screen coordinates, with its depth left as it is
+pub fn ndc_to_screen(v: &Vector3D, viewport: &Viewport) -> Vector3D {
+    Vector3D::new(
+        viewport.x + (v.x + 1.0) * 0.5 * viewport.width,
+        viewport.y + (v.y + 1.0) * 0.5 * viewport.height,
+        v.z,
+    )
+}
+
+/// Cap how many triangles are drawn in a frame, skipping the rest, e.g. to find which
+/// triangle makes a frame slow
+///
+/// # Arguments
+/// * `triangles` - The triangles that would be drawn, in drawing order
+/// * `cap` - The most triangles to draw, or `None` to draw them all
+///
+/// # Returns
+/// * `impl Iterator<Item = T>` - The triangles that should be drawn
+pub fn cap_triangles<T>(
+    triangles: impl Iterator<Item = T>,
+    cap: Option<usize>,
+) -> impl Iterator<Item = T> {
+    triangles.take(cap.unwrap_or(usize::MAX))
+}
+
+/// The main function of the engine (also runs the game loop)
+pub fn main() {
+    let run_config: RunConfig = match RunConfig::from_args(std::env::args().skip(1)) {
+        Ok(run_config) => run_config,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+
+    let sdl_context = sdl2::init().unwrap();
+    let video_subsystem = sdl_context.video().unwrap();
+    let screen_size = video_subsystem.display_bounds(0).unwrap();
+
+    // The window takes up two thirds of the screen unless its size is set in the environment
+    let window_width = display::dimension_from_env("ENGINE_WIDTH").unwrap_or_else(|| {
+        display::window_dimension(screen_size.width(), display::DEFAULT_WINDOW_SIZE.0)
+    });
+    let window_height = display::dimension_from_env("ENGINE_HEIGHT").unwrap_or_else(|| {
+        display::window_dimension(screen_size.height(), display::DEFAULT_WINDOW_SIZE.1)
+    });
+
+    let title: &str = "rust-sdl2 demo";
+    let window: sdl2::video::Window = video_subsystem
+        .window(title, window_width, window_height)
+        .position_centered()
+        .allow_highdpi()
+        .build()
+        .unwrap();
+
+    // Render at the size of the drawable area, which is bigger than the window on high-DPI
+    // displays
+    let display_size: DisplaySize = DisplaySize::from_window(&window);
+    let display_width = display_size.width();
+    let display_height = display_size.height();
+
+    let field_of_view: f32 = 90.0;
+    let near_plane: f32 = 0.1;
+    let far_plane: f32 = 1000.0;
+    let projection_matrix: Matrix = Matrix::projection(
+        field_of_view,
+        display_size.aspect_ratio(),
+        near_plane,
+        far_plane,
+    );
+
+    let skybox: Option<Skybox> = match &run_config.skybox {
+        Some(folder) => match Skybox::load(folder) {
+            Ok(skybox) => Some(skybox),
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let mut model_matrix: Matrix = Matrix::identity();
+    // How far the cube has turned, in radians, and how fast it turns, in radians per second
+    let mut spin: f32 = 0.0;
+    let spin_speed: f32 = 0.5;
+    // Edits to the cube are made on top of the spin, and can be undone and redone
+    let mut transforms: Vec<Transform> = vec![Transform::default()];
+    let mut history: TransformHistory = TransformHistory::default();
+
+    // Keep the original around so that reverting a subdivision is a recompute rather than an
+    // approximation of the previous level
+    let original_mesh = match &run_config.model {
+        Some(path) => match Mesh::from_obj(path) {
+            // The model is left at its own size, and the camera is moved to frame it below
+            Ok(mut mesh) => {
+                mesh.warn_triangle_sizes(Triangle::EDGE_LIMIT);
+                mesh
+            }
+            Err(error) => {
+                eprintln!("Couldn't load `{}`: {}", path.display(), error);
+                return;
+            }
+        },
+        None => cube::get_cube_mesh(),
+    };
+    // The mesh spins around the middle of its bounding box
+    let pivot: Vector3D = original_mesh
+        .bounding_box()
+        .map_or(Vector3D::default(), |(min, max)| min.midpoint(&max));
+    let mut subdivision_level: u32 = 0;
+    let mut cube_mesh = original_mesh.clone();
+
+    let mut canvas = window.into_canvas().build().unwrap();
+    let texture_creator = canvas.texture_creator();
+    // The software backend draws here. The canvas can't be post-processed directly, so with
+    // the SDL backend each frame is read back into it for bloom and shown from there.
+    let mut software_target: TextureTarget = match TextureTarget::new(
+        &texture_creator,
+        display_width as u32,
+        display_height as u32,
+    ) {
+        Ok(software_target) => software_target,
+        Err(error) => {
+            eprintln!("Couldn't create the software target: {}", error);
+            return;
+        }
+    };
+    let mut backend: Backend = Backend::default();
+
+    let black: Color = Color::RGB(0, 0, 0);
+    let white: Color = Color::RGB(255, 255, 255);
+    // Debugging aid for the clipper: triangles made by clipping are drawn in this colour
+    let clipped_highlight: Color = Color::RGB(255, 0, 255);
+    let mut highlight_clipped: bool = false;
+    // Draws edges thinner the further away they are, instead of one pixel wide
+    let wireframe: WireframeConfig = WireframeConfig::default();
+    let mut thick_wireframe: bool = false;
+    // Fills the triangles in under the edges, depth tested so the nearest ones are shown
+    let mut filled: bool = false;
+    // At most this many triangles are drawn each frame, for finding where drawing gets slow
+    let mut triangle_cap: Option<usize> = None;
+    let fill_color: Color = Color::RGB(70, 70, 90);
+    let mut depth_buffer: DepthBuffer =
+        DepthBuffer::new(display_width as u32, display_height as u32);
+    let depth_config: DepthConfig = DepthConfig::default();
+    // Blends the edges of filled triangles by how much of each pixel they cover
+    let mut raster_config: RasterConfig = RasterConfig::default();
+    // Antialiased edges are blended with what has already been drawn, so the cube is filled
+    // from the back to the front in this order
+    let mut draw_list: DrawList = DrawList::default();
+    // The triangle under the last mouse click is drawn in this colour
+    let picked_highlight: Color = Color::RGB(255, 200, 0);
+    let mut picked: Option<PickResult> = None;
+    let background: Background = Background::Gradient {
+        top: Color::RGB(40, 60, 110),
+        bottom: black,
+    };
+    let grid: Grid = Grid::default();
+    // Props around the cube, sitting on the grid. They are drawn through the scene, so the
+    // small cubes follow the `filled` toggle while the sphere is always filled.
+    let mut scene: Scene = Scene::default();
+    let prop_cube = cube::get_cube_mesh().transformed(&Matrix::from_trs(
+        Vector3D::default(),
+        &Matrix::identity(),
+        Vector3D::new(0.25, 0.25, 0.25),
+    ));
+    let prop_transforms: Vec<Matrix> = [(-1.5, 0.0), (-1.5, 1.5), (2.25, 0.0), (2.25, 1.5)]
+        .iter()
+        .map(|(x, z)| Matrix::translation(*x, grid.height - 0.25, *z))
+        .collect();
+    let sphere = primitives::icosphere(0.3, 0).transformed(&Matrix::translation(
+        0.5,
+        grid.height - 0.3,
+        2.5,
+    ));
+    // The sphere drops onto the grid over the first second, speeding up as it falls
+    let mut sphere_drop: Timeline = Timeline::new(easing::ease_in_quad);
+    sphere_drop.add_keyframe(
+        0.0,
+        Transform {
+            translation: Vector3D::new(0.0, -1.5, 0.0),
+            ..Transform::default()
+        },
+    );
+    sphere_drop.add_keyframe(1.0, Transform::default());
+    match scene
+        .add_instanced(prop_cube, prop_transforms)
+        .and_then(|_| scene.add_animated(sphere, sphere_drop))
+    {
+        Ok(sphere) => scene.objects[sphere].render_mode = Some(RenderMode::Filled),
+        Err(error) => eprintln!("Couldn't add the props to the scene: {}", error),
+    }
+    // The cube is drawn on its own, but kept in the scene as well so that clicking picks
+    // from the cube and the props alike
+    let cube_object: Option<usize> = match scene.add_undrawn(cube_mesh.clone()) {
+        Ok(object) => Some(object),
+        Err(error) => {
+            eprintln!("Couldn't add the cube to the scene: {}", error);
+            None
+        }
+    };
+    let mut light: Light = Light::default();
+    let mut camera: Camera = match &run_config.camera {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|config| Camera::from_config_string(&config))
+        {
+            Ok(camera) => camera,
+            Err(error) => {
+                eprintln!(
+                    "Couldn't load the camera from `{}`: {}",
+                    path.display(),
+                    error
+                );
+                return;
+            }
+        },
+        None => {
+            let mut camera = Camera::default();
+            // A loaded model can be any size, so the camera backs off until all of it is in view
+            if let (Some(_), Some(bbox)) = (&run_config.model, original_mesh.bounding_box()) {
+                camera.frame_bounds(bbox, field_of_view);
+            }
+
+            camera
+        }
+    };
+    // Dragging with the right mouse button turns the camera, starting from the way it faces
+    let mut orientation: Orientation = Orientation::looking_along(&camera.forward());
+    let mut cull: CullConfig = CullConfig::default();
+
+    let mut event_pump = sdl_context.event_pump().unwrap();
+
+    let mut stats: RenderStats = RenderStats::default();
+    let mut hud: Hud = Hud::default();
+    let input_config: InputConfig = InputConfig::default();
+    let mut input_state: InputState = InputState::default();
+    let mut frame_times: FrameTimes = FrameTimes::default();
+    let mut stats_log =
+        run_config
+            .stats_csv
+            .as_ref()
+            .and_then(|path| match StatsLog::create(path) {
+                Ok(stats_log) => Some(stats_log),
+                Err(error) => {
+                    eprintln!("Couldn't create the stats log: {}", error);
+                    None
+                }
+            });
+
+    let mut frame_timer: FrameTimer = FrameTimer::default();
+    let mut stage_timer: StageTimer<SystemClock> = StageTimer::new(SystemClock);
+
+    // Everything that wants to hear about each finished frame and its stats
+    let mut frame_callbacks: Vec<FrameCallback> = Vec::new();
+    if stats_log.is_some() {
+        frame_callbacks.push(Box::new(|frame, stats| {
+            if let Some(Err(error)) = stats_log
+                .as_mut()
+                .map(|stats_log| stats_log.record(frame, stats))
+            {
+                eprintln!(
+                    "Couldn't write to the stats log, so it has been stopped: {}",
+                    error
+                );
+                stats_log = None;
+            }
+        }));
+    }
+    if run_config.profile {
+        frame_callbacks.push(Box::new(|frame, stats| {
+            if frame.is_multiple_of(FRAME_RATE_CAP) {
+                println!("{}", stats::stage_breakdown(stats));
+            }
+        }));
+    }
+
+    'running: loop {
+        let frame_start = Instant::now();
+        // Everything that moves is scaled by how long the last frame took
+        let dt = frame_timer.tick_at(frame_start);
+        scene.advance(dt);
+        orientation.update();
+        camera.look(&orientation);
+        apply_input(&mut camera, &input_state, dt);
+
+        let view: View = View::new(
+            &camera,
+            &projection_matrix,
+            near_plane,
+            display_width,
+            display_height,
+        );
+        let view_projection: Matrix = view.view_projection();
+        // The skybox takes pixels back out through the view and projection
+        let inverse_view_projection: Matrix =
+            view_projection.inverse().unwrap_or_else(Matrix::identity);
+
+        for event in event_pump.poll_iter() {
+            input_state.handle_event(&input_config, &event);
 
-        for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. }
                 | Event::KeyDown {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(Keycode::Equals),
+                    ..
+                } => {
+                    let subdivided = cube_mesh.subdivide();
+
+                    // A mesh that is too big has already been warned about as it was made,
+                    // so it is just thrown away
+                    if Mesh::warn_mesh_size(&subdivided.mat, subdivided.vector_limit).is_none() {
+                        subdivision_level += 1;
+                        cube_mesh = subdivided;
+                        draw_list.invalidate();
+                        picked = None;
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Minus),
+                    ..
+                } if subdivision_level > 0 => {
+                    subdivision_level -= 1;
+                    cube_mesh = original_mesh.subdivide_levels(subdivision_level);
+                    draw_list.invalidate();
+                    picked = None;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::C),
+                    ..
+                } => highlight_clipped = !highlight_clipped,
+                Event::KeyDown {
+                    keycode: Some(Keycode::T),
+                    ..
+                } => thick_wireframe = !thick_wireframe,
+                Event::KeyDown {
+                    keycode: Some(Keycode::F),
+                    ..
+                } => filled = !filled,
+                Event::KeyDown {
+                    keycode: Some(Keycode::M),
+                    ..
+                } => raster_config.antialias = !raster_config.antialias,
+                Event::KeyDown {
+                    keycode: Some(Keycode::LeftBracket),
+                    ..
+                } => {
+                    let cap = triangle_cap.unwrap_or(cube_mesh.mat.len());
+                    triangle_cap = Some(cap.saturating_sub(1));
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::RightBracket),
+                    ..
+                } => {
+                    // Raising the cap past every triangle takes it away
+                    triangle_cap = triangle_cap
+                        .map(|cap| cap + 1)
+                        .filter(|cap| *cap < cube_mesh.mat.len());
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Backslash),
+                    ..
+                } => triangle_cap = None,
+                Event::KeyDown {
+                    keycode: Some(Keycode::B),
+                    ..
+                } => backend.toggle(),
+                Event::KeyDown {
+                    keycode: Some(Keycode::V),
+                    ..
+                } => cull.show_back_faces = !cull.show_back_faces,
+                Event::KeyDown {
+                    keycode: Some(Keycode::F1),
+                    ..
+                } => hud.toggle(),
+                // Print the camera so it can be saved and loaded back with `--camera`
+                Event::KeyDown {
+                    keycode: Some(Keycode::P),
+                    ..
+                } => print!("{}", camera.to_config_string()),
+                Event::KeyDown {
+                    keycode: Some(keycode @ (Keycode::Comma | Keycode::Period)),
+                    ..
+                } => {
+                    let factor = if keycode == Keycode::Period {
+                        1.1
+                    } else {
+                        1.0 / 1.1
+                    };
+                    let mut transform = transforms[0];
+                    transform.scale = transform.scale * factor;
+
+                    history.apply(&mut transforms, 0, transform);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Z),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    history.undo(&mut transforms);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Y),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    history.redo(&mut transforms);
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode @ (Keycode::I | Keycode::J | Keycode::K | Keycode::L)),
+                    ..
+                } => light.turn_with_key(keycode),
+                Event::MouseMotion {
+                    mousestate,
+                    xrel,
+                    yrel,
+                    ..
+                } if mousestate.right() => apply_mouse_look(&mut orientation, xrel, yrel),
+                Event::MouseButtonDown {
+                    mouse_btn: MouseButton::Left,
+                    x,
+                    y,
+                    ..
+                } => {
+                    picked = scene.pick(
+                        display_size.to_drawable(x, y),
+                        &view_projection,
+                        display_width,
+                        display_height,
+                    );
+                }
                 _ => {}
             }
         }
 
-        // Set the drawing color to white
-        canvas.set_draw_color(white);
+        // Each stage is timed from the end of the one before, so they add up to the total
+        stage_timer.start_frame();
+
+        // Spin the cube around its centre
+        spin += spin_speed * dt;
+        model_matrix = Matrix::translation(-pivot.x, -pivot.y, -pivot.z)
+            .multiply(&transforms[0].matrix())
+            .multiply(&Matrix::rotation_y(spin))
+            .multiply(&Matrix::rotation_x(spin * 0.5))
+            .multiply(&Matrix::translation(pivot.x, pivot.y, pivot.z));
+        let world_mesh = cube_mesh.transformed(&model_matrix);
+        if let Some(object) = cube_object {
+            scene.objects[object].mesh = world_mesh.clone();
+        }
+        stats.transform = stage_timer.lap();
+
+        // Only the triangles facing the camera are drawn, as the rest are behind them
+        let visible: Vec<bool> = world_mesh
+            .mat
+            .iter()
+            .map(|triangle| cull.is_triangle_visible(triangle, &camera.position))
+            .collect();
+        stats.cull = stage_timer.lap();
+
+        // What is left is clipped against the near plane in view space, so nothing behind the
+        // camera gets projected. Each piece keeps the index of the triangle it came from, for
+        // picking and lighting.
+        let visible_triangles: Vec<(usize, Triangle)> = cap_triangles(
+            world_mesh
+                .mat
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| visible[*index])
+                .flat_map(|(index, triangle)| {
+                    view.clip_triangle(triangle)
+                        .into_iter()
+                        .map(move |clipped| (index, clipped))
+                }),
+            triangle_cap,
+        )
+        .collect();
+        let visible_mesh = Mesh {
+            mat: visible_triangles
+                .iter()
+                .map(|(_, triangle)| triangle.clone())
+                .collect(),
+            uvs: Vec::new(),
+            vector_limit: world_mesh.vector_limit,
+        };
+        // Back faces are kept aside to be drawn in the debug colour if they are shown
+        let back_face_mesh = Mesh {
+            mat: cull
+                .back_faces(&world_mesh.mat, &visible)
+                .flat_map(|triangle| view.clip_triangle(triangle))
+                .collect(),
+            uvs: Vec::new(),
+            vector_limit: world_mesh.vector_limit,
+        };
+        stats.clip = stage_timer.lap();
+        stats.triangle_cap = triangle_cap;
+
+        // Draw the background and the grid, then the cube, then the highlighted triangles over
+        // the top of it. Projecting happens as the mesh is drawn, so it is timed as part of
+        // rasterizing.
+        let mut target = backend.target(&mut canvas, &mut software_target.framebuffer);
+        match &skybox {
+            Some(skybox) => skybox.draw(
+                &mut target,
+                &inverse_view_projection,
+                display_width as u32,
+                display_height as u32,
+            ),
+            None => background.draw(&mut target, display_width as u32, display_height as u32),
+        }
+        grid.draw(&mut target, &camera.position, &background, &view);
+        depth_buffer.clear();
+        if filled {
+            // The order is kept in the cube's own space, where only the camera moves, so it is
+            // only sorted again once the camera has moved far enough around the cube
+            let camera_in_mesh = model_matrix.inverse().map_or(camera.position, |inverse| {
+                inverse.transform_point(&camera.position)
+            });
+            let mut rank = vec![0; cube_mesh.mat.len()];
+            for (position, index) in draw_list
+                .order(&cube_mesh, &camera_in_mesh)
+                .iter()
+                .enumerate()
+            {
+                rank[*index] = position;
+            }
+            let mut back_to_front: Vec<&(usize, Triangle)> = visible_triangles.iter().collect();
+            back_to_front.sort_by_key(|(index, _)| rank[*index]);
+
+            let filled_triangles: Vec<(Triangle, Color)> = back_face_mesh
+                .mat
+                .iter()
+                .map(|triangle| (view.project(triangle), cull.back_face_color))
+                .chain(back_to_front.into_iter().map(|(index, triangle)| {
+                    (
+                        view.project(triangle),
+                        light.shade(&world_mesh.mat[*index].normal()),
+                    )
+                }))
+                .collect();
+
+            // The software framebuffer can be split into tiles and filled a tile at a time,
+            // while the canvas has to be drawn on one triangle after another
+            match &mut target {
+                BackendTarget::Software(framebuffer) => tiles::fill_tiled(
+                    framebuffer,
+                    &mut depth_buffer,
+                    &filled_triangles,
+                    &raster_config,
+                ),
+                BackendTarget::Sdl(_) => {
+                    for (triangle, color) in &filled_triangles {
+                        triangle.fill(&mut target, *color, &mut depth_buffer, &raster_config);
+                    }
+                }
+            }
+        }
+        if !filled {
+            target.set_draw_color(cull.back_face_color);
+            back_face_mesh.draw(&mut target, &view.projection, display_width, display_height);
+        }
+        target.set_draw_color(white);
+        if filled && !thick_wireframe {
+            // The edges sit right on the fill, so they are depth tested with a bias to hide
+            // the ones behind nearer triangles without flickering on their own
+            for triangle in &visible_mesh.mat {
+                view.project(triangle).draw_depth_tested(
+                    &mut target,
+                    &depth_buffer,
+                    depth_config.wireframe_bias,
+                );
+            }
+        } else if thick_wireframe {
+            wireframe.draw(
+                &mut target,
+                &visible_mesh,
+                &view.projection,
+                display_width,
+                display_height,
+            );
+        } else {
+            visible_mesh.draw(&mut target, &view.projection, display_width, display_height);
+        }
+
+        scene.raster_config = raster_config;
+        scene.render_mode = if filled {
+            RenderMode::Filled
+        } else {
+            RenderMode::Wireframe
+        };
+        let scene_triangles = scene.draw(&mut target, &view, &mut depth_buffer);
+
+        if highlight_clipped {
+            target.set_draw_color(clipped_highlight);
+            for (_, triangle) in visible_triangles
+                .iter()
+                .filter(|(_, triangle)| triangle.clipped)
+            {
+                view.project(triangle).draw(&mut target);
+            }
+        }
+        // The picked triangle can be on the cube or on any of the props, and is only shown
+        // while it faces the camera
+        if let Some(triangle) = picked
+            .and_then(|picked| scene.picked_triangle(&picked))
+            .filter(|triangle| cull.is_triangle_visible(triangle, &camera.position))
+        {
+            target.set_draw_color(picked_highlight);
+            for clipped in view.clip_triangle(&triangle) {
+                view.project(&clipped).draw(&mut target);
+            }
+        }
+        stats.rasterize = stage_timer.lap();
+        stats.triangles = visible_triangles.len() + scene_triangles;
+
+        let bloom = |framebuffer: &mut Framebuffer| post::bloom(framebuffer, 0.8, 1.5);
+        let post_process: Option<&dyn Fn(&mut Framebuffer)> =
+            if run_config.bloom { Some(&bloom) } else { None };
+        let shown = match backend {
+            Backend::Sdl if post_process.is_some() => canvas
+                .read_pixels(None, PixelFormatEnum::RGBA32)
+                .and_then(|pixels| {
+                    software_target.framebuffer.load_rgba_bytes(&pixels);
+                    software_target.present(&mut canvas, post_process)
+                }),
+            Backend::Sdl => Ok(()),
+            Backend::Software => software_target.present(&mut canvas, post_process),
+        };
+        if let Err(error) = shown {
+            eprintln!("Couldn't show the frame: {}", error);
+        }
+
+        canvas.present();
+        stats.present = stage_timer.lap();
+        stats.total = stage_timer.total();
+
+        hud.draw(&mut canvas, title, &stats, &light);
+        for callback in frame_callbacks.iter_mut() {
+            callback(frame_times.frames, &stats);
+        }
+        frame_times.record(&stats);
+
+        if run_config.is_done(frame_times.frames) {
+            break 'running;
+        }
+
+        // Only sleep for what is left of the frame, so slow frames don't fall further behind
+        if !run_config.no_sleep {
+            let frame_budget = Duration::from_secs(1) / FRAME_RATE_CAP;
+            if let Some(rest) = frame_budget.checked_sub(frame_start.elapsed()) {
+                ::std::thread::sleep(rest);
+            }
+        }
+    }
+
+    // The stats log is borrowed by its callback until the callbacks are gone
+    drop(frame_callbacks);
+    if let Some(Err(error)) = stats_log.as_mut().map(StatsLog::flush) {
+        eprintln!("Couldn't write to the stats log: {}", error);
+    }
+
+    if run_config.frames.is_some() {
+        println!(
+            "{} frames, {:.3} ms per frame on average",
+            frame_times.frames,
+            frame_times.average().as_secs_f64() * 1000.0
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use target::RecordingTarget;
+
+    /// A 90 degree projection for a 200 by 150 display
+    fn test_projection() -> Matrix {
+        Matrix::projection(90.0, 150.0 / 200.0, 0.1, 100.0)
+    }
+
+    /// How a camera sees a 200 by 150 display, with the test projection
+    fn test_view(camera: &Camera) -> View {
+        View::new(camera, &test_projection(), 0.1, 200.0, 150.0)
+    }
+
+    /// Apply a matrix to a point
+    fn apply(m: &Matrix, v: Vector3D) -> Vector3D {
+        let mut output = Vector3D::default();
+        multiply_matrix_vector(&v, &mut output, m);
+
+        output
+    }
+
+    /// Check that two vectors are within rounding of each other
+    fn assert_close(actual: Vector3D, expected: Vector3D) {
+        assert!(
+            (actual - expected).length() < 1e-5,
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn cube_has_eighteen_unique_edges() {
+        assert_eq!(cube::get_cube_mesh().unique_edges().len(), 18);
+    }
+
+    #[test]
+    fn reverting_a_subdivision_restores_the_triangle_count() {
+        let original = cube::get_cube_mesh();
+        let subdivided = original.subdivide().subdivide();
+
+        assert_eq!(subdivided.mat.len(), original.mat.len() * 16);
+        assert_eq!(
+            original.subdivide_levels(1).mat.len(),
+            original.mat.len() * 4
+        );
+        assert_eq!(original.subdivide_levels(0).mat.len(), original.mat.len());
+    }
+
+    #[test]
+    fn unit_cube_has_area_six_and_volume_one() {
+        let cube = cube::get_cube_mesh();
+
+        assert!((cube.surface_area() - 6.0).abs() < 1e-5);
+        assert!((cube.volume() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn line_mesh_draws_a_line_per_segment() {
+        let lines = LineMesh::new(vec![
+            (Vector3D::new(0.0, 0.0, 0.0), Vector3D::new(1.0, 0.0, 0.0)),
+            (Vector3D::new(1.0, 0.0, 0.0), Vector3D::new(1.0, 1.0, 0.0)),
+            (Vector3D::new(1.0, 1.0, 0.0), Vector3D::new(0.0, 1.0, 0.0)),
+        ]);
+        let mut target = RecordingTarget::new();
+
+        lines.draw(&mut target, &test_projection(), 200.0, 150.0);
+
+        assert_eq!(target.lines.len(), 3);
+        assert_eq!(target.draw_calls(), 3);
+    }
+
+    #[test]
+    fn point_cloud_draws_a_point_per_vertex() {
+        let cloud = PointCloud::new(vec![
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(0.5, 0.5, 0.5),
+        ]);
+        let mut target = RecordingTarget::new();
+
+        cloud.draw(&mut target, &test_projection(), 200.0, 150.0);
+
+        assert_eq!(target.points.len(), 2);
+        assert_eq!(target.draw_calls(), 2);
+    }
+
+    #[test]
+    fn mirroring_the_cube_negates_x_and_keeps_normals_outward() {
+        let original = cube::get_cube_mesh();
+        let mut mirrored = original.clone();
+        mirrored.mirror(Axis::X);
+
+        let middle = Vector3D::new(-0.5, 0.5, 0.5);
+        for (before, after) in original.mat.iter().zip(&mirrored.mat) {
+            let mut before_x = [before.a.x, before.b.x, before.c.x].map(|x| -x);
+            let mut after_x = [after.a.x, after.b.x, after.c.x];
+            before_x.sort_by(f32::total_cmp);
+            after_x.sort_by(f32::total_cmp);
+            assert_eq!(before_x, after_x);
+
+            let centroid = (after.a + after.b + after.c) * (1.0 / 3.0);
+            assert!(after.normal().dot(&(centroid - middle)) > 0.0);
+        }
+        assert!((mirrored.volume() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn clipping_a_straddling_triangle_marks_the_pieces_as_clipped() {
+        let near = 0.5;
+        // One corner in front of the near plane, then two
+        for triangle in [
+            Triangle::new(
+                Vector3D::new(0.0, 0.0, 2.0),
+                Vector3D::new(1.0, 0.0, -1.0),
+                Vector3D::new(0.0, 1.0, -1.0),
+            ),
+            Triangle::new(
+                Vector3D::new(0.0, 0.0, 2.0),
+                Vector3D::new(1.0, 0.0, 2.0),
+                Vector3D::new(0.0, 1.0, -1.0),
+            ),
+        ] {
+            let pieces = clip_triangle_near(&triangle, near);
+
+            assert!(!pieces.is_empty());
+            for piece in &pieces {
+                assert!(piece.clipped);
+                assert!([piece.a, piece.b, piece.c]
+                    .iter()
+                    .all(|v| v.z >= near - 1e-5));
+                assert!(
+                    piece
+                        .project_to_screen(&test_projection(), 200.0, 150.0)
+                        .clipped
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn clipping_leaves_triangles_in_front_unmarked() {
+        let triangle = Triangle::new(
+            Vector3D::new(0.0, 0.0, 2.0),
+            Vector3D::new(1.0, 0.0, 2.0),
+            Vector3D::new(0.0, 1.0, 3.0),
+        );
+
+        let pieces = clip_triangle_near(&triangle, 0.5);
+
+        assert_eq!(pieces.len(), 1);
+        assert!(!pieces[0].clipped);
+        assert!(clip_triangle_near(&triangle, 5.0).is_empty());
+    }
+
+    #[test]
+    fn triangle_over_half_the_screen_covers_half_the_pixels() {
+        // With a 90 degree field of view the screen spans 4 units to either side and 3 units
+        // up and down at the origin, which is 3 units in front of the default camera
+        let triangle = Triangle::new(
+            Vector3D::new(-4.0, -3.0, 0.0),
+            Vector3D::new(4.0, -3.0, 0.0),
+            Vector3D::new(4.0, 3.0, 0.0),
+        );
+
+        let area = triangle.screen_area(&test_view(&Camera::default()));
+
+        assert!((area - 200.0 * 150.0 / 2.0).abs() < 150.0, "{}", area);
+    }
+
+    #[test]
+    fn triangle_behind_the_near_plane_has_no_screen_area() {
+        let triangle = Triangle::new(
+            Vector3D::new(0.0, 0.0, -5.0),
+            Vector3D::new(1.0, 0.0, -5.0),
+            Vector3D::new(0.0, 1.0, -5.0),
+        );
+
+        assert_eq!(triangle.screen_area(&test_view(&Camera::default())), 0.0);
+        // The near plane moves with the camera
+        let camera = Camera::new(
+            Vector3D::new(0.0, 0.0, -8.0),
+            Vector3D::default(),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+        assert!(triangle.screen_area(&test_view(&camera)) > 0.0);
+    }
+
+    #[test]
+    fn bounding_sphere_contains_every_corner_of_the_cube() {
+        let (center, radius) = cube::get_cube_mesh().bounding_sphere();
+
+        for corner in 0..8 {
+            let corner = Vector3D::new(
+                (corner & 1) as f32,
+                (corner >> 1 & 1) as f32,
+                (corner >> 2 & 1) as f32,
+            );
+            assert!((corner - center).length() <= radius + 1e-5);
+        }
+        // Ritter's sphere is never more than a little bigger than the smallest one
+        assert!(radius < 3.0_f32.sqrt() / 2.0 * 1.2);
+    }
+
+    #[test]
+    fn triple_product_of_the_basis_is_one() {
+        let x = Vector3D::new(1.0, 0.0, 0.0);
+        let y = Vector3D::new(0.0, 1.0, 0.0);
+        let z = Vector3D::new(0.0, 0.0, 1.0);
+
+        assert_eq!(Vector3D::triple_product(&x, &y, &z), 1.0);
+        assert_eq!(Vector3D::triple_product(&y, &x, &z), -1.0);
+    }
+
+    #[test]
+    fn ascii_preview_of_the_cube_fills_the_requested_grid() {
+        let preview = cube::get_cube_mesh().ascii_preview(40, 20, &test_projection());
+        let rows: Vec<&str> = preview.split('\n').collect();
+
+        assert_eq!(rows.len(), 20);
+        assert!(rows.iter().all(|row| row.chars().count() == 40));
+        assert!(preview.contains('#'));
+        assert!(preview.chars().all(|c| c == '#' || c == ' ' || c == '\n'));
+    }
+
+    #[test]
+    fn slicing_the_cube_through_its_middle_makes_a_closed_loop() {
+        let slice = cube::get_cube_mesh().slice(&Vector3D::new(0.0, 1.0, 0.0), -0.5);
+        let key =
+            |v: &Vector3D| [v.x, v.y, v.z].map(|coordinate| (coordinate * 1000.0).round() as i32);
+
+        // Every end of a segment is shared with exactly one other segment
+        let mut ends: HashMap<[i32; 3], usize> = HashMap::new();
+        for (start, end) in &slice.lines {
+            assert!((start.y - 0.5).abs() < 1e-6 && (end.y - 0.5).abs() < 1e-6);
+            *ends.entry(key(start)).or_default() += 1;
+            *ends.entry(key(end)).or_default() += 1;
+        }
+
+        assert_eq!(slice.lines.len(), 8);
+        assert!(ends.values().all(|&count| count == 2));
+
+        // Walking from segment to segment comes back around to the start
+        let mut remaining = slice.lines.clone();
+        let (first, mut at) = remaining.remove(0);
+        while let Some(index) = remaining
+            .iter()
+            .position(|(start, end)| key(start) == key(&at) || key(end) == key(&at))
+        {
+            let (start, end) = remaining.remove(index);
+            at = if key(&start) == key(&at) { end } else { start };
+        }
+        assert!(remaining.is_empty());
+        assert_eq!(key(&at), key(&first));
+    }
+
+    /// A triangle lying across the plane x = 0, with one corner on the positive side
+    fn triangle_across_x() -> Triangle {
+        Triangle::new(
+            Vector3D::new(1.0, 0.0, 0.0),
+            Vector3D::new(-1.0, 1.0, 0.0),
+            Vector3D::new(-1.0, -1.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn clip_against_plane_keeps_a_triangle_in_front() {
+        let triangle = triangle_across_x();
+
+        let output = triangle.clip_against_plane(&Vector3D::new(1.0, 0.0, 0.0), 2.0);
+
+        assert_eq!(output.len(), 1);
+        assert_eq!(
+            [output[0].a, output[0].b, output[0].c],
+            [triangle.a, triangle.b, triangle.c]
+        );
+        assert!(!output[0].clipped);
+    }
+
+    #[test]
+    fn clip_against_plane_drops_a_triangle_behind() {
+        let output = triangle_across_x().clip_against_plane(&Vector3D::new(1.0, 0.0, 0.0), -2.0);
+
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn clip_against_plane_with_one_vertex_inside_leaves_one_triangle() {
+        let triangle = triangle_across_x();
+
+        let output = triangle.clip_against_plane(&Vector3D::new(1.0, 0.0, 0.0), 0.0);
+
+        assert_eq!(output.len(), 1);
+        assert!(output[0].clipped);
+        assert_eq!(output[0].a, Vector3D::new(1.0, 0.0, 0.0));
+        assert_eq!(output[0].b, Vector3D::new(0.0, 0.5, 0.0));
+        assert_eq!(output[0].c, Vector3D::new(0.0, -0.5, 0.0));
+        // The winding (and so the facing) of the original is kept
+        assert!(output[0].normal().dot(&triangle.normal()) > 0.999);
+        assert!((output[0].area() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clip_against_plane_with_two_vertices_inside_leaves_two_triangles() {
+        let triangle = triangle_across_x();
+
+        let output = triangle.clip_against_plane(&Vector3D::new(-1.0, 0.0, 0.0), 0.0);
+
+        assert_eq!(output.len(), 2);
+        for piece in &output {
+            assert!(piece.clipped);
+            assert!([piece.a, piece.b, piece.c].iter().all(|v| v.x <= 0.0));
+            assert!(piece.normal().dot(&triangle.normal()) > 0.999);
+        }
+        // The two pieces make up everything but the corner that was cut off
+        let area: f32 = output.iter().map(Triangle::area).sum();
+        assert!((area - (triangle.area() - 0.5)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn cube_has_consistent_winding() {
+        assert!(cube::get_cube_mesh().assert_consistent_winding());
+    }
+
+    #[test]
+    fn flipped_triangle_breaks_the_winding() {
+        let mut cube = cube::get_cube_mesh();
+        cube.mat[5].flip();
+
+        assert!(!cube.assert_consistent_winding());
+    }
+
+    #[test]
+    fn mixed_winding_is_warned_about_as_the_mesh_is_made() {
+        let mut triangles = cube::get_cube_mesh().mat;
+        take_warnings();
+
+        Mesh::new(triangles.clone());
+        assert!(take_warnings().is_empty());
+
+        triangles[5].flip();
+        Mesh::new(triangles);
+        assert_eq!(
+            take_warnings(),
+            vec![
+                "Warning: Mesh has inconsistent winding, some triangles are facing the wrong way"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn separate_pieces_can_each_be_consistent() {
+        let mut pieces = cube::get_cube_mesh();
+        pieces.mat.extend(
+            cube::get_cube_mesh()
+                .transformed(&Matrix::translation(3.0, 0.0, 0.0))
+                .mat,
+        );
+
+        assert!(pieces.assert_consistent_winding());
+    }
+
+    #[test]
+    fn trs_moves_the_origin_to_the_translation() {
+        let translation = Vector3D::new(1.0, -2.0, 3.0);
+        let trs = Matrix::from_trs(
+            translation,
+            &Matrix::rotation_y(0.8),
+            Vector3D::new(2.0, 3.0, 4.0),
+        );
+
+        assert_close(apply(&trs, Vector3D::default()), translation);
+    }
+
+    #[test]
+    fn trs_scales_then_rotates_then_translates() {
+        let translation = Vector3D::new(1.0, -2.0, 3.0);
+        let rotation = Matrix::rotation_z(std::f32::consts::FRAC_PI_2);
+        let scale = Vector3D::new(2.0, 3.0, 4.0);
+        let trs = Matrix::from_trs(translation, &rotation, scale);
+        let unit_x = Vector3D::new(1.0, 0.0, 0.0);
+
+        let expected = apply(&rotation, unit_x * scale.x) + translation;
+        assert_close(apply(&trs, unit_x), expected);
+        assert!(trs.approx_eq(
+            &Matrix::new([
+                [scale.x, 0.0, 0.0, 0.0],
+                [0.0, scale.y, 0.0, 0.0],
+                [0.0, 0.0, scale.z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ])
+            .multiply(&rotation)
+            .multiply(&Matrix::translation(
+                translation.x,
+                translation.y,
+                translation.z
+            )),
+            1e-6
+        ));
+    }
+
+    #[test]
+    fn every_cube_triangle_has_three_neighbours() {
+        let cube = cube::get_cube_mesh();
+        let adjacency = cube.build_adjacency();
+
+        assert_eq!(adjacency.neighbors.len(), 12);
+        assert_eq!(adjacency.edges.len(), 18);
+        for (triangle, neighbors) in adjacency.neighbors.iter().enumerate() {
+            for neighbor in neighbors {
+                let neighbor = neighbor.expect("every cube edge is shared");
+
+                assert_ne!(neighbor, triangle);
+                assert!(adjacency.neighbors[neighbor].contains(&Some(triangle)));
+            }
+        }
+        assert_eq!(cube.boundary_edge_count(), 0);
+    }
+
+    #[test]
+    fn lone_triangle_has_no_neighbours() {
+        let mesh = Mesh::new(vec![Triangle::new(
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(1.0, 0.0, 0.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        )]);
+        let adjacency = mesh.build_adjacency();
+
+        assert_eq!(adjacency.neighbors, vec![[None; 3]]);
+        assert_eq!(mesh.boundary_edge_count(), 3);
+    }
+
+    #[test]
+    fn face_on_cube_silhouette_is_its_outline() {
+        let edges = cube::get_cube_mesh().silhouette_edges(&Vector3D::new(0.5, 0.5, -5.0));
+        let corner = |x: f32, y: f32| Vector3D::new(x, y, 0.0);
+        let outline = [
+            (corner(0.0, 0.0), corner(1.0, 0.0)),
+            (corner(1.0, 0.0), corner(1.0, 1.0)),
+            (corner(1.0, 1.0), corner(0.0, 1.0)),
+            (corner(0.0, 1.0), corner(0.0, 0.0)),
+        ];
+
+        assert_eq!(edges.len(), 4);
+        for (start, end) in outline {
+            assert!(
+                edges
+                    .iter()
+                    .any(|edge| *edge == (start, end) || *edge == (end, start)),
+                "{:?} to {:?} is missing",
+                start,
+                end
+            );
+        }
+    }
+
+    #[test]
+    fn removing_degenerate_triangles_keeps_the_valid_ones() {
+        let valid = cube::get_cube_mesh().mat;
+        let point = Vector3D::new(1.0, 2.0, 3.0);
+        let degenerate = [
+            // Every corner in the same place
+            Triangle::new(point, point, point),
+            // Corners in a line
+            Triangle::new(
+                Vector3D::new(0.0, 0.0, 0.0),
+                Vector3D::new(1.0, 1.0, 1.0),
+                Vector3D::new(2.0, 2.0, 2.0),
+            ),
+            // Far too thin to see
+            Triangle::new(
+                Vector3D::new(0.0, 0.0, 0.0),
+                Vector3D::new(1.0, 0.0, 0.0),
+                Vector3D::new(0.5, 1e-7, 0.0),
+            ),
+        ];
+        let mut mixed = Vec::new();
+        for (index, triangle) in valid.iter().enumerate() {
+            mixed.push(triangle.clone());
+            if index % 4 == 0 {
+                mixed.push(degenerate[index / 4].clone());
+            }
+        }
+        let mut mesh = Mesh::new(mixed);
+
+        assert_eq!(mesh.remove_degenerate_triangles(1e-6), 3);
+        assert_eq!(mesh.mat.len(), valid.len());
+        for (kept, original) in mesh.mat.iter().zip(&valid) {
+            assert_eq!(
+                [kept.a, kept.b, kept.c],
+                [original.a, original.b, original.c]
+            );
+        }
+        assert_eq!(mesh.remove_degenerate_triangles(1e-6), 0);
+    }
+
+    #[test]
+    fn screen_bounds_are_clamped_to_the_screen() {
+        let inside = Triangle::new(
+            Vector3D::new(10.2, 20.7, 0.5),
+            Vector3D::new(30.5, 25.0, 0.5),
+            Vector3D::new(15.0, 40.1, 0.5),
+        );
+        assert_eq!(inside.screen_bounds(200.0, 150.0), (10, 20, 31, 41));
+
+        let partly_off = Triangle::new(
+            Vector3D::new(-50.5, 100.0, 0.5),
+            Vector3D::new(250.0, 120.0, 0.5),
+            Vector3D::new(80.0, 300.0, 0.5),
+        );
+        assert_eq!(partly_off.screen_bounds(200.0, 150.0), (0, 100, 199, 149));
+
+        let (min_x, _, max_x, _) = Triangle::new(
+            Vector3D::new(-30.0, 10.0, 0.5),
+            Vector3D::new(-10.0, 10.0, 0.5),
+            Vector3D::new(-20.0, 30.0, 0.5),
+        )
+        .screen_bounds(200.0, 150.0);
+        assert!(min_x > max_x);
+    }
 
-        // Draw the cube
-        for triangle in cube_mesh.mat.iter_mut() {
-            let mut tri_projected: Triangle = Triangle::default();
-            let mut tri_translated: Triangle = triangle.clone();
+    #[test]
+    fn approx_eq_allows_only_the_given_error() {
+        let matrix = Matrix::new([
+            [0.25, 0.0, -0.5, 0.0],
+            [0.0, 0.125, 0.0, 0.0],
+            [0.5, 0.0, 0.25, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let mut nudged = matrix.clone();
+        for row in nudged.mat.iter_mut() {
+            for entry in row.iter_mut() {
+                *entry += 1e-7;
+            }
+        }
 
-            // Add depth to the triangle
-            tri_translated.a.z = triangle.a.z + 3.0;
-            tri_translated.b.z = triangle.b.z + 3.0;
-            tri_translated.c.z = triangle.c.z + 3.0;
+        assert!(matrix.approx_eq(&nudged, 1e-6));
+        assert!(nudged.approx_eq(&matrix, 1e-6));
+        assert!(!matrix.approx_eq(&nudged, 1e-9));
+        assert!(matrix.approx_eq(&matrix, 0.0));
 
-            multiply_matrix_vector(&tri_translated.a, &mut tri_projected.a, &projection_matrix);
-            multiply_matrix_vector(&tri_translated.b, &mut tri_projected.b, &projection_matrix);
-            multiply_matrix_vector(&tri_translated.c, &mut tri_projected.c, &projection_matrix);
+        // A single entry out of tolerance is enough to fail
+        let mut one_off = matrix.clone();
+        one_off.mat[3][2] = 1e-3;
+        assert!(!matrix.approx_eq(&one_off, 1e-6));
+    }
 
-            // Scale into view
-            tri_projected.a.x += 1.0;
-            tri_projected.a.y += 1.0;
-            tri_projected.b.x += 1.0;
-            tri_projected.b.y += 1.0;
-            tri_projected.c.x += 1.0;
-            tri_projected.c.y += 1.0;
+    #[test]
+    fn two_triangle_mesh_draws_three_lines_each() {
+        let corner = |x: f32, y: f32| Vector3D::new(x, y, 3.0);
+        let square = Mesh::new(vec![
+            Triangle::new(corner(-1.0, -1.0), corner(-1.0, 1.0), corner(1.0, 1.0)),
+            Triangle::new(corner(-1.0, -1.0), corner(1.0, 1.0), corner(1.0, -1.0)),
+        ]);
+        let mut target = RecordingTarget::new();
+        target.set_draw_color(Color::RGB(0, 255, 0));
 
-            tri_projected.a.x *= 0.5 * display_width;
-            tri_projected.a.y *= 0.5 * display_height;
-            tri_projected.b.x *= 0.5 * display_width;
-            tri_projected.b.y *= 0.5 * display_height;
-            tri_projected.c.x *= 0.5 * display_width;
-            tri_projected.c.y *= 0.5 * display_height;
+        square.draw(&mut target, &test_projection(), 200.0, 150.0);
 
-            tri_projected.draw(&mut canvas);
+        assert_eq!(target.lines.len(), 6);
+        // The screen is 8 by 6 units across where the square is, so it spans 25 pixels either
+        // side of the middle, give or take rounding
+        let near = |value: i32, options: [i32; 2]| options.iter().any(|o| (value - o).abs() <= 1);
+        for (start, end, color) in &target.lines {
+            assert_eq!(*color, Color::RGB(0, 255, 0));
+            for point in [start, end] {
+                assert!(near(point.x(), [75, 125]), "{:?}", point);
+                assert!(near(point.y(), [50, 100]), "{:?}", point);
+            }
         }
+    }
 
-        canvas.present();
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+    #[test]
+    fn dot_of_orthogonal_and_parallel_vectors() {
+        let x = Vector3D::new(1.0, 0.0, 0.0);
+        let y = Vector3D::new(0.0, 1.0, 0.0);
+
+        assert_eq!(x.dot(&y), 0.0);
+        assert_eq!(
+            Vector3D::new(3.0, -2.0, 5.0).dot(&Vector3D::new(2.0, 3.0, 0.0)),
+            0.0
+        );
+        assert_eq!(x.dot(&(x * 4.0)), 4.0);
+        assert_eq!(x.dot(&(x * -4.0)), -4.0);
+        assert_eq!(
+            Vector3D::new(1.0, 2.0, 3.0).dot(&Vector3D::new(4.0, 5.0, 6.0)),
+            32.0
+        );
+    }
+
+    #[test]
+    fn cross_follows_the_right_hand_rule() {
+        let x = Vector3D::new(1.0, 0.0, 0.0);
+        let y = Vector3D::new(0.0, 1.0, 0.0);
+        let z = Vector3D::new(0.0, 0.0, 1.0);
+
+        assert_eq!(x.cross(&y), z);
+        assert_eq!(y.cross(&z), x);
+        assert_eq!(z.cross(&x), y);
+        assert_eq!(y.cross(&x), -z);
+
+        // Parallel vectors have no area between them
+        assert_eq!(x.cross(&(x * 3.0)), Vector3D::default());
+
+        let (a, b) = (Vector3D::new(1.0, 2.0, 3.0), Vector3D::new(-4.0, 5.0, 0.5));
+        let cross = a.cross(&b);
+        assert!(cross.dot(&a).abs() < 1e-5 && cross.dot(&b).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hadamard_multiplies_each_component() {
+        let a = Vector3D::new(2.0, 3.0, 4.0);
+
+        assert_eq!(
+            a.hadamard(&Vector3D::new(5.0, 6.0, 7.0)),
+            Vector3D::new(10.0, 18.0, 28.0)
+        );
+        assert_eq!(a.hadamard(&Vector3D::new(1.0, 1.0, 1.0)), a);
+        assert_eq!(
+            a.hadamard(&Vector3D::new(-1.0, 0.0, 0.5)),
+            Vector3D::new(-2.0, 0.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn length_of_a_three_four_five_triangle() {
+        assert_eq!(Vector3D::new(3.0, 4.0, 0.0).length(), 5.0);
+        assert_eq!(Vector3D::new(0.0, -3.0, 4.0).length(), 5.0);
+        assert_eq!(Vector3D::new(2.0, 3.0, 6.0).length(), 7.0);
+        assert_eq!(Vector3D::default().length(), 0.0);
+    }
+
+    #[test]
+    fn normalizing_gives_a_unit_vector() {
+        assert_eq!(
+            Vector3D::new(3.0, 4.0, 0.0).normalize(),
+            Vector3D::new(0.6, 0.8, 0.0)
+        );
+
+        for v in [
+            Vector3D::new(1e-3, 0.0, 0.0),
+            Vector3D::new(-7.0, 2.5, 100.0),
+            Vector3D::new(1e6, 1e6, -1e6),
+        ] {
+            assert!((v.normalize().length() - 1.0).abs() < 1e-6, "{:?}", v);
+            assert!(v.normalize().dot(&v) > 0.0);
+        }
+    }
+
+    #[test]
+    fn normalizing_the_zero_vector_gives_zero() {
+        let normalized = Vector3D::default().normalize();
+
+        assert_eq!(normalized, Vector3D::default());
+        assert!(!normalized.x.is_nan());
+    }
+
+    #[test]
+    fn flipping_negates_the_normal_and_twice_restores_it() {
+        let original = Triangle::new(
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(2.0, 0.5, 0.0),
+            Vector3D::new(0.5, 1.0, 1.0),
+        );
+        let mut triangle = original.clone();
+
+        triangle.flip();
+        assert_close(triangle.normal(), -original.normal());
+
+        triangle.flip();
+        assert_eq!(
+            [triangle.a, triangle.b, triangle.c],
+            [original.a, original.b, original.c]
+        );
+    }
+
+    #[test]
+    fn flipping_a_whole_mesh_turns_it_inside_out() {
+        let mut cube = cube::get_cube_mesh();
+        cube.flip_all();
+
+        assert!((cube.volume() + 1.0).abs() < 1e-5);
+        for (flipped, original) in cube.mat.iter().zip(&cube::get_cube_mesh().mat) {
+            assert_close(flipped.normal(), -original.normal());
+        }
+
+        cube.flip_all();
+        assert!((cube.volume() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn operators_match_component_arithmetic() {
+        let a = Vector3D::new(1.5, -2.0, 3.0);
+        let b = Vector3D::new(0.5, 4.0, -1.0);
+
+        assert_eq!(a + b, Vector3D::new(a.x + b.x, a.y + b.y, a.z + b.z));
+        assert_eq!(a - b, Vector3D::new(a.x - b.x, a.y - b.y, a.z - b.z));
+        assert_eq!(a * 2.5, Vector3D::new(a.x * 2.5, a.y * 2.5, a.z * 2.5));
+        assert_eq!(-a, Vector3D::new(-a.x, -a.y, -a.z));
+
+        let mut sum = a;
+        sum += b;
+        assert_eq!(sum, a + b);
+
+        let mut difference = a;
+        difference -= b;
+        assert_eq!(difference, a - b);
+
+        // The operands are copied, so they can still be used afterwards
+        assert_eq!(a, Vector3D::new(1.5, -2.0, 3.0));
+        assert_eq!(b, Vector3D::new(0.5, 4.0, -1.0));
+    }
+
+    #[test]
+    fn pushing_a_triangle_back_with_operators() {
+        let triangle = cube::get_cube_mesh().mat[0].clone();
+        let offset = Vector3D::new(0.0, 0.0, 3.0);
+        let moved = triangle.a + offset;
+
+        assert_eq!(moved.x, triangle.a.x);
+        assert_eq!(moved.y, triangle.a.y);
+        assert_eq!(moved.z, triangle.a.z + 3.0);
+        assert_eq!(moved - offset, triangle.a);
+    }
+
+    #[test]
+    fn multiplying_by_the_identity_changes_nothing() {
+        let matrix = Matrix::from_trs(
+            Vector3D::new(1.0, 2.0, 3.0),
+            &Matrix::rotation_x(0.3),
+            Vector3D::new(2.0, 1.0, 0.5),
+        );
+
+        assert!(matrix.multiply(&Matrix::identity()).approx_eq(&matrix, 0.0));
+        assert!(Matrix::identity().multiply(&matrix).approx_eq(&matrix, 0.0));
+    }
+
+    #[test]
+    fn multiplication_is_associative() {
+        let a = Matrix::rotation_y(0.7);
+        let b = Matrix::translation(1.0, -2.0, 0.5);
+        let c = test_projection();
+
+        assert!(a
+            .multiply(&b)
+            .multiply(&c)
+            .approx_eq(&a.multiply(&b.multiply(&c)), 1e-5));
+    }
+
+    #[test]
+    fn known_pair_gives_the_expected_product() {
+        let a = Matrix::new([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 10.0, 11.0, 12.0],
+            [13.0, 14.0, 15.0, 16.0],
+        ]);
+        let b = Matrix::new([
+            [2.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 3.0, 0.0],
+            [1.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert!(a.multiply(&b).approx_eq(
+            &Matrix::new([
+                [6.0, 2.0, 9.0, 5.0],
+                [18.0, 6.0, 21.0, 13.0],
+                [30.0, 10.0, 33.0, 21.0],
+                [42.0, 14.0, 45.0, 29.0],
+            ]),
+            0.0
+        ));
+    }
+
+    #[test]
+    fn identity_leaves_vectors_unchanged() {
+        for v in [
+            Vector3D::default(),
+            Vector3D::new(1.0, 0.0, 0.0),
+            Vector3D::new(-3.5, 2.25, 100.0),
+            Vector3D::new(1e-4, -1e4, 0.5),
+        ] {
+            assert_close(apply(&Matrix::identity(), v), v);
+        }
+        assert!(Matrix::identity().approx_eq(
+            &Matrix::new([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]),
+            0.0
+        ));
+    }
+
+    #[test]
+    fn quarter_turn_about_z_takes_x_to_y() {
+        let x = Vector3D::new(1.0, 0.0, 0.0);
+        let y = Vector3D::new(0.0, 1.0, 0.0);
+        let z = Vector3D::new(0.0, 0.0, 1.0);
+        let quarter = std::f32::consts::FRAC_PI_2;
+
+        assert_close(apply(&Matrix::rotation_z(quarter), x), y);
+        assert_close(apply(&Matrix::rotation_x(quarter), y), z);
+        assert_close(apply(&Matrix::rotation_y(quarter), z), x);
+        // Turning about an axis leaves the axis where it is
+        assert_close(apply(&Matrix::rotation_z(quarter), z), z);
+    }
+
+    #[test]
+    fn full_turn_is_the_identity() {
+        let full = std::f32::consts::TAU;
+
+        for rotation in [
+            Matrix::rotation_x(full),
+            Matrix::rotation_y(full),
+            Matrix::rotation_z(full),
+        ] {
+            assert!(rotation.approx_eq(&Matrix::identity(), 1e-5));
+        }
+    }
+
+    #[test]
+    fn translation_offsets_vectors() {
+        let translation = Matrix::translation(1.0, -2.0, 3.0);
+
+        assert_eq!(
+            apply(&translation, Vector3D::new(4.0, 5.0, 6.0)),
+            Vector3D::new(5.0, 3.0, 9.0)
+        );
+        assert_eq!(
+            apply(&translation, Vector3D::default()),
+            Vector3D::new(1.0, -2.0, 3.0)
+        );
+        // The offset is in the bottom row, where `multiply_matrix_vector` reads it
+        assert_eq!(translation.mat[3], [1.0, -2.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn cube_memory_is_its_triangles_plus_overhead() {
+        let mut cube = cube::get_cube_mesh();
+
+        assert_eq!(
+            cube.memory_bytes(),
+            std::mem::size_of::<Mesh>() + 12 * std::mem::size_of::<Triangle>()
+        );
+
+        // Texture coordinates are counted as well
+        cube.uvs = vec![[(0.0, 0.0); 3]; 12];
+        assert_eq!(
+            cube.memory_bytes(),
+            std::mem::size_of::<Mesh>()
+                + 12 * std::mem::size_of::<Triangle>()
+                + 12 * std::mem::size_of::<[(f32, f32); 3]>()
+        );
+    }
+
+    #[test]
+    fn projection_diagonal_for_a_known_fov_and_aspect() {
+        // A 60 degree view scales by 1 / tan(30 degrees), the square root of 3
+        let projection = Matrix::projection(60.0, 0.5, 1.0, 11.0);
+        let scaling = 3.0f32.sqrt();
+
+        assert!((projection.mat[0][0] - 0.5 * scaling).abs() < 1e-5);
+        assert!((projection.mat[1][1] - scaling).abs() < 1e-5);
+        assert!((projection.mat[2][2] - 1.1).abs() < 1e-6);
+        assert_eq!(projection.mat[2][3], 1.0);
+        assert!((projection.mat[3][2] + 1.1).abs() < 1e-6);
+        assert_eq!(projection.mat[3][3], 0.0);
+    }
+
+    #[test]
+    fn projection_maps_near_and_far_to_zero_and_one() {
+        let projection = Matrix::projection(90.0, 0.75, 0.5, 50.0);
+
+        assert!(apply(&projection, Vector3D::new(0.0, 0.0, 0.5)).z.abs() < 1e-6);
+        assert!((apply(&projection, Vector3D::new(0.0, 0.0, 50.0)).z - 1.0).abs() < 1e-5);
+        // The edge of a 90 degree view is as far to the side as it is in front
+        assert_close(
+            apply(&projection, Vector3D::new(2.0, 2.0, 2.0)),
+            Vector3D::new(
+                0.75,
+                1.0,
+                apply(&projection, Vector3D::new(0.0, 0.0, 2.0)).z,
+            ),
+        );
+    }
+
+    #[test]
+    fn ninety_degree_fov_scales_by_one() {
+        assert!((Matrix::scaling_factor(90.0) - 1.0).abs() < 1e-6);
+        // Treating the degrees as radians would have given 1 / tan(45) instead
+        assert!((Matrix::scaling_factor(90.0) - 1.0 / 45.0f32.tan()).abs() > 0.1);
+        assert!((Matrix::scaling_factor(60.0) - 3.0f32.sqrt()).abs() < 1e-5);
+        assert!(Matrix::scaling_factor(120.0) < 1.0);
+    }
+
+    #[test]
+    fn cube_is_watertight_until_a_face_is_removed() {
+        let mut cube = cube::get_cube_mesh();
+        assert!(cube.is_watertight());
+
+        // Taking away the north face leaves a square hole
+        cube.mat.drain(4..6);
+        assert!(!cube.is_watertight());
+        assert_eq!(cube.boundary_edge_count(), 4);
+    }
+
+    #[test]
+    fn edge_shared_by_three_triangles_is_not_watertight() {
+        let mut cube = cube::get_cube_mesh();
+        let fin = Triangle::new(
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(1.0, 1.0, 0.0),
+            Vector3D::new(0.5, 0.5, -1.0),
+        );
+        cube.mat.push(fin);
+
+        assert!(!cube.is_watertight());
+    }
+
+    #[test]
+    fn normal_of_axis_aligned_triangles() {
+        let triangle = |a: (f32, f32, f32), b: (f32, f32, f32), c: (f32, f32, f32)| {
+            Triangle::new(
+                Vector3D::new(a.0, a.1, a.2),
+                Vector3D::new(b.0, b.1, b.2),
+                Vector3D::new(c.0, c.1, c.2),
+            )
+        };
+
+        assert_eq!(
+            triangle((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)).normal(),
+            Vector3D::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            triangle((0.0, 0.0, 0.0), (0.0, 3.0, 0.0), (2.0, 0.0, 0.0)).normal(),
+            Vector3D::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            triangle((0.0, 5.0, 0.0), (0.0, 5.0, 1.0), (1.0, 5.0, 0.0)).normal(),
+            Vector3D::new(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn ndc_centre_maps_to_the_viewport_centre() {
+        let viewport = Viewport::new(300.0, 40.0, 200.0, 100.0);
+
+        assert_close(
+            ndc_to_screen(&Vector3D::new(0.0, 0.0, 0.5), &viewport),
+            Vector3D::new(400.0, 90.0, 0.5),
+        );
+        assert_close(
+            ndc_to_screen(&Vector3D::new(-1.0, -1.0, 0.0), &viewport),
+            Vector3D::new(300.0, 40.0, 0.0),
+        );
+        assert_close(
+            ndc_to_screen(&Vector3D::new(1.0, 1.0, 0.0), &viewport),
+            Vector3D::new(500.0, 140.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn full_viewport_matches_the_old_mapping() {
+        let v = Vector3D::new(0.25, -0.5, 0.3);
+
+        assert_close(
+            ndc_to_screen(&v, &Viewport::full(200.0, 150.0)),
+            Vector3D::new(125.0, 37.5, 0.3),
+        );
+    }
+
+    #[test]
+    fn triangle_cap_limits_what_is_submitted() {
+        let cube = cube::get_cube_mesh();
+        let projection = test_projection();
+        let draw = |cap: Option<usize>| {
+            let mut target = RecordingTarget::new();
+            Mesh::new(cap_triangles(cube.mat.iter().cloned(), cap).collect()).draw(
+                &mut target,
+                &projection,
+                200.0,
+                150.0,
+            );
+            target.lines.len()
+        };
+
+        assert_eq!(draw(Some(5)), 5 * 3);
+        assert_eq!(draw(Some(0)), 0);
+        assert_eq!(draw(None), cube.mat.len() * 3);
+        assert_eq!(draw(Some(100)), cube.mat.len() * 3);
+    }
+
+    #[test]
+    fn dihedral_angle_of_flat_and_square_edges() {
+        let cube = cube::get_cube_mesh();
+        let (south, east, north) = (&cube.mat[0], &cube.mat[2], &cube.mat[4]);
+
+        // The two halves of one face are coplanar
+        assert!(south.dihedral_angle(&cube.mat[1]).abs() < 1e-3);
+        assert!((south.dihedral_angle(east) - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+        assert!((east.dihedral_angle(south) - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+        assert!((south.dihedral_angle(north) - std::f32::consts::PI).abs() < 1e-3);
+    }
+
+    #[test]
+    fn transforming_by_the_identity_leaves_the_cube_alone() {
+        let cube = cube::get_cube_mesh();
+        let mut transformed = cube.clone();
+        transformed.transform(&Matrix::identity());
+
+        for (before, after) in cube.mat.iter().zip(&transformed.mat) {
+            for (before, after) in [before.a, before.b, before.c]
+                .into_iter()
+                .zip([after.a, after.b, after.c])
+            {
+                assert_close(after, before);
+            }
+        }
+    }
+
+    #[test]
+    fn transforming_by_a_translation_shifts_every_vertex() {
+        let cube = cube::get_cube_mesh();
+        let offset = Vector3D::new(1.5, -2.0, 4.0);
+        let mut moved = cube.clone();
+        moved.transform(&Matrix::translation(offset.x, offset.y, offset.z));
+        let copy = cube.transformed(&Matrix::translation(offset.x, offset.y, offset.z));
+
+        for ((before, after), copy) in cube.mat.iter().zip(&moved.mat).zip(&copy.mat) {
+            for ((before, after), copy) in [before.a, before.b, before.c]
+                .into_iter()
+                .zip([after.a, after.b, after.c])
+                .zip([copy.a, copy.b, copy.c])
+            {
+                assert_close(after, before + offset);
+                assert_close(copy, after);
+            }
+        }
+    }
+
+    #[test]
+    fn cube_bounding_box_is_the_unit_cube() {
+        let (min, max) = cube::get_cube_mesh().bounding_box().unwrap();
+
+        assert_close(min, Vector3D::new(0.0, 0.0, 0.0));
+        assert_close(max, Vector3D::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn translated_cube_bounding_box_moves_with_it() {
+        let cube = cube::get_cube_mesh().transformed(&Matrix::translation(-3.0, 2.0, 0.5));
+        let (min, max) = cube.bounding_box().unwrap();
+
+        assert_close(min, Vector3D::new(-3.0, 2.0, 0.5));
+        assert_close(max, Vector3D::new(-2.0, 3.0, 1.5));
+        assert!(Mesh::new(Vec::new()).bounding_box().is_none());
+    }
+
+    #[test]
+    fn framing_a_large_model_puts_all_of_it_on_screen() {
+        // A model far bigger than the unit cube, and off to one side, as it was loaded
+        let mesh = cube::get_cube_mesh().transformed(&Matrix::from_trs(
+            Vector3D::new(40.0, -10.0, 25.0),
+            &Matrix::identity(),
+            Vector3D::new(30.0, 20.0, 10.0),
+        ));
+        let mut camera = Camera::default();
+        camera.frame_bounds(mesh.bounding_box().unwrap(), 90.0);
+        let view = test_view(&camera);
+
+        for triangle in &mesh.mat {
+            let projected = triangle.project_to_screen(&view.view_projection(), 200.0, 150.0);
+            for corner in [projected.a, projected.b, projected.c] {
+                assert!((0.0..=200.0).contains(&corner.x), "{:?}", corner);
+                assert!((0.0..=150.0).contains(&corner.y), "{:?}", corner);
+            }
+        }
+    }
+
+    #[test]
+    fn centering_makes_the_bounding_box_symmetric() {
+        let mut mesh = cube::get_cube_mesh().transformed(&Matrix::from_trs(
+            Vector3D::new(7.0, -3.0, 12.0),
+            &Matrix::identity(),
+            Vector3D::new(2.0, 1.0, 4.0),
+        ));
+        mesh.center();
+        let (min, max) = mesh.bounding_box().unwrap();
+
+        assert_close(min, -max);
+        assert_close(max, Vector3D::new(1.0, 0.5, 2.0));
+    }
+
+    #[test]
+    fn scaling_to_unit_makes_the_longest_side_one() {
+        let mut mesh = cube::get_cube_mesh().transformed(&Matrix::from_trs(
+            Vector3D::new(7.0, -3.0, 12.0),
+            &Matrix::identity(),
+            Vector3D::new(2.0, 1.0, 4.0),
+        ));
+        mesh.center();
+        mesh.scale_to_unit();
+        let (min, max) = mesh.bounding_box().unwrap();
+        let size = max - min;
+
+        assert!((size.x.max(size.y).max(size.z) - 1.0).abs() < 1e-5);
+        assert_close(size, Vector3D::new(0.5, 0.25, 1.0));
+        assert_close(min, -max);
+    }
+
+    #[test]
+    fn average_normal_of_a_flat_grid_is_its_face_normal() {
+        let flat = primitives::GrayImage::new(4, 3, vec![0; 12]);
+        let grid = primitives::from_heightmap(&flat, 1.0);
+        let normal = grid.mat[0].normal();
+
+        assert_eq!(grid.mat.len(), 12);
+        assert_close(normal, Vector3D::new(0.0, 1.0, 0.0));
+        assert_close(grid.average_normal(), normal);
+    }
+
+    #[test]
+    fn average_normal_of_a_closed_cube_is_zero() {
+        assert!(cube::get_cube_mesh().average_normal().length() < 1e-5);
+        assert_close(Mesh::new(Vec::new()).average_normal(), Vector3D::default());
+    }
+
+    /// Copies of a triangle spread out along x, so they don't share edges
+    fn spread_triangles(count: usize) -> Vec<Triangle> {
+        (0..count)
+            .map(|i| {
+                cube::get_cube_mesh().mat[0].transformed(&Matrix::translation(
+                    i as f32 * 2.0,
+                    0.0,
+                    0.0,
+                ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn oversized_mesh_prints_the_real_warning() {
+        take_warnings();
+
+        Mesh::new(spread_triangles(Mesh::VECTOR_LIMIT));
+        assert!(take_warnings().is_empty());
+
+        Mesh::new(spread_triangles(Mesh::VECTOR_LIMIT + 1));
+        assert_eq!(
+            take_warnings(),
+            vec!["Warning: Mesh is too big, consider splitting it up".to_string()]
+        );
+    }
+
+    #[test]
+    fn warnings_are_formatted_with_display() {
+        take_warnings();
+        let mut mesh = cube::get_cube_mesh();
+
+        mesh.warn(format!("{} triangles", 12));
+        mesh.mat[0].warn(7);
+        assert_eq!(
+            take_warnings(),
+            vec![
+                "Warning: 12 triangles".to_string(),
+                "Warning: 7".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn transform_then_divide_matches_the_old_multiply() {
+        let projection = test_projection();
+        let v = Vector3D::new(0.4, -1.2, 2.5);
+        let m = &projection.mat;
+        // The old multiply, written out in full
+        let w = v.x * m[0][3] + v.y * m[1][3] + v.z * m[2][3] + m[3][3];
+        let old = Vector3D::new(
+            (v.x * m[0][0] + v.y * m[1][0] + v.z * m[2][0] + m[3][0]) / w,
+            (v.x * m[0][1] + v.y * m[1][1] + v.z * m[2][1] + m[3][1]) / w,
+            (v.x * m[0][2] + v.y * m[1][2] + v.z * m[2][2] + m[3][2]) / w,
+        );
+
+        assert_close(
+            perspective_divide(&projection.transform_point(&v), projection.transform_w(&v)),
+            old,
+        );
+        assert_close(apply(&projection, v), old);
+    }
+
+    #[test]
+    fn skipping_the_divide_keeps_clip_space() {
+        let projection = test_projection();
+        let v = Vector3D::new(1.0, 1.0, 2.0);
+        let clip = projection.transform_point(&v);
+        let (near, far) = (0.1, 100.0);
+        let q = far / (far - near);
+
+        // 90 degrees has a scaling factor of 1, so only the aspect ratio changes x
+        assert_close(clip, Vector3D::new(0.75, 1.0, 2.0 * q - near * q));
+        assert!((projection.transform_w(&v) - 2.0).abs() < 1e-6);
+        // A w of zero is left alone rather than dividing by it
+        assert_close(perspective_divide(&clip, 0.0), clip);
+    }
+
+    #[test]
+    fn fifty_one_triangles_warn() {
+        take_warnings();
+        let mesh = Mesh::new(spread_triangles(51));
+
+        assert_eq!(mesh.mat.len(), 51);
+        assert_eq!(
+            take_warnings(),
+            vec!["Warning: Mesh is too big, consider splitting it up".to_string()]
+        );
+    }
+
+    #[test]
+    fn try_new_fails_past_the_limit() {
+        take_warnings();
+
+        assert!(Mesh::try_new(spread_triangles(50)).is_ok());
+        let error = Mesh::try_new(spread_triangles(51)).err().unwrap();
+        assert_eq!(
+            error,
+            MeshError::TooManyTriangles {
+                triangles: 51,
+                limit: Mesh::VECTOR_LIMIT,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "The mesh has 51 triangles, but the limit is 50"
+        );
+        assert!(Mesh::try_with_limit(spread_triangles(51), 100).is_ok());
+        // Failing is instead of warning, not as well as
+        assert!(take_warnings().is_empty());
+    }
+
+    #[test]
+    fn removing_degenerate_triangles_keeps_their_uvs_in_step() {
+        let good = cube::get_cube_mesh().mat[0].clone();
+        let flat = Triangle::new(good.a, good.a, good.b);
+        let mut mesh = Mesh::new(vec![flat.clone(), good, flat]);
+        mesh.uvs = vec![[(0.0, 0.0); 3], [(0.5, 0.5); 3], [(1.0, 1.0); 3]];
+
+        assert_eq!(mesh.remove_degenerate_triangles(1e-6), 2);
+        assert_eq!(mesh.uvs, vec![[(0.5, 0.5); 3]]);
+    }
+
+    #[test]
+    fn planar_uvs_map_the_xz_plane_across_the_bounding_box() {
+        let flat = primitives::GrayImage::new(5, 3, vec![0; 15]);
+        let mut grid = primitives::from_heightmap(&flat, 1.0)
+            .transformed(&Matrix::translation(-2.0, 0.5, 10.0));
+        grid.generate_planar_uvs(Axis::Y);
+
+        assert_eq!(grid.uvs.len(), grid.mat.len());
+        for (triangle, uvs) in grid.mat.iter().zip(&grid.uvs) {
+            for (corner, (u, v)) in [triangle.a, triangle.b, triangle.c].iter().zip(uvs) {
+                // The grid runs from x -2 to 2 and z 10 to 12
+                assert!((0.0..=1.0).contains(u) && (0.0..=1.0).contains(v));
+                assert!((u - (corner.x + 2.0) / 4.0).abs() < 1e-5);
+                assert!((v - (corner.z - 10.0) / 2.0).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn spherical_uvs_stay_on_the_texture() {
+        let mut sphere = primitives::icosphere(1.0, 1);
+        sphere.generate_spherical_uvs();
+
+        assert_eq!(sphere.uvs.len(), sphere.mat.len());
+        for (u, v) in sphere.uvs.iter().flatten() {
+            assert!((0.0..=1.0).contains(u) && (0.0..=1.0).contains(v));
+        }
+
+        let mut empty = Mesh::new(Vec::new());
+        empty.generate_spherical_uvs();
+        assert!(empty.uvs.is_empty());
+    }
+
+    #[test]
+    fn triangle_size_check_passes_a_valid_triangle() {
+        let good = &cube::get_cube_mesh().mat[0];
+
+        assert_eq!(
+            Triangle::warn_triangle_size(&[good.a, good.b, good.c], Triangle::EDGE_LIMIT),
+            None
+        );
+    }
+
+    #[test]
+    fn triangle_size_check_catches_degenerate_triangles() {
+        let (a, b) = (Vector3D::new(0.0, 0.0, 0.0), Vector3D::new(1.0, 1.0, 1.0));
+        let warning =
+            Some("Triangle has no area, its corners are on top of each other or in a line");
+
+        assert_eq!(
+            Triangle::warn_triangle_size(&[a, a, a], Triangle::EDGE_LIMIT),
+            warning
+        );
+        assert_eq!(
+            Triangle::warn_triangle_size(&[a, b, b * 3.0], Triangle::EDGE_LIMIT),
+            warning
+        );
+    }
+
+    #[test]
+    fn triangle_size_check_catches_oversized_triangles() {
+        let corners = [
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(150.0, 0.0, 0.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        ];
+
+        assert_eq!(
+            Triangle::warn_triangle_size(&corners, Triangle::EDGE_LIMIT),
+            Some("Triangle is too big, consider splitting it up")
+        );
+        assert_eq!(Triangle::warn_triangle_size(&corners, 200.0), None);
+
+        let mut mesh = Mesh::new(vec![Triangle::new(corners[0], corners[1], corners[2])]);
+        take_warnings();
+        assert_eq!(mesh.warn_triangle_sizes(Triangle::EDGE_LIMIT), 1);
+        assert_eq!(take_warnings().len(), 1);
+    }
+
+    #[test]
+    fn custom_limit_changes_when_the_warning_fires() {
+        take_warnings();
+
+        let mesh = Mesh::with_limit(spread_triangles(80), 100);
+        assert_eq!(mesh.vector_limit, 100);
+        assert!(take_warnings().is_empty());
+
+        Mesh::with_limit(spread_triangles(11), 10);
+        assert_eq!(take_warnings().len(), 1);
+
+        // The default limit still applies to `new`
+        assert_eq!(
+            Mesh::new(spread_triangles(11)).vector_limit,
+            Mesh::VECTOR_LIMIT
+        );
+        assert!(take_warnings().is_empty());
+    }
+
+    #[test]
+    fn subdividing_keeps_the_custom_limit() {
+        let mesh = Mesh::with_limit(cube::get_cube_mesh().mat, 1000);
+
+        assert_eq!(mesh.subdivide().vector_limit, 1000);
+        assert_eq!(mesh.transformed(&Matrix::identity()).vector_limit, 1000);
+    }
+
+    #[test]
+    fn two_disjoint_cubes_are_two_components() {
+        let mut mat = cube::get_cube_mesh().mat;
+        mat.extend(
+            cube::get_cube_mesh()
+                .transformed(&Matrix::translation(3.0, 0.0, 0.0))
+                .mat,
+        );
+        let mut mesh = Mesh::with_limit(mat, 100);
+        mesh.uvs = (0..24).map(|i| [(i as f32, 0.0); 3]).collect();
+
+        let components = mesh.connected_components();
+
+        assert_eq!(components.len(), 2);
+        for (index, component) in components.iter().enumerate() {
+            assert_eq!(component.mat.len(), 12);
+            assert_eq!(component.vector_limit, 100);
+            assert!(component.is_watertight());
+            assert_eq!(component.uvs[0], [((index * 12) as f32, 0.0); 3]);
+        }
+        assert!(components[1].bounding_box().unwrap().0.x > 2.0);
+    }
+
+    #[test]
+    fn cubes_touching_at_a_corner_are_one_component() {
+        let mut mat = cube::get_cube_mesh().mat;
+        mat.extend(
+            cube::get_cube_mesh()
+                .transformed(&Matrix::translation(1.0, 1.0, 1.0))
+                .mat,
+        );
+
+        assert_eq!(Mesh::with_limit(mat, 100).connected_components().len(), 1);
+        assert!(Mesh::new(Vec::new()).connected_components().is_empty());
+    }
+
+    #[test]
+    fn near_clipping_keeps_a_triangle_in_front_unchanged() {
+        let triangle = Triangle::new(
+            Vector3D::new(-1.0, 0.0, 1.0),
+            Vector3D::new(1.0, 0.0, 2.0),
+            Vector3D::new(0.0, 1.0, 4.0),
+        );
+
+        let pieces = clip_triangle_near(&triangle, 0.1);
+
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(
+            [pieces[0].a, pieces[0].b, pieces[0].c],
+            [triangle.a, triangle.b, triangle.c]
+        );
+    }
+
+    #[test]
+    fn near_clipping_drops_a_triangle_behind() {
+        let behind = Triangle::new(
+            Vector3D::new(-1.0, 0.0, -1.0),
+            Vector3D::new(1.0, 0.0, 0.05),
+            Vector3D::new(0.0, 1.0, -4.0),
+        );
+
+        assert!(clip_triangle_near(&behind, 0.1).is_empty());
+    }
+
+    #[test]
+    fn near_clipping_splits_a_straddling_triangle() {
+        let near = 1.0;
+        let one_in_front = Triangle::new(
+            Vector3D::new(0.0, 0.0, 3.0),
+            Vector3D::new(1.0, 0.0, -1.0),
+            Vector3D::new(0.0, 1.0, -1.0),
+        );
+        let two_in_front = Triangle::new(
+            Vector3D::new(0.0, 0.0, 3.0),
+            Vector3D::new(1.0, 0.0, 3.0),
+            Vector3D::new(0.0, 1.0, -1.0),
+        );
+
+        for (triangle, expected) in [(one_in_front, 1), (two_in_front, 2)] {
+            let pieces = clip_triangle_near(&triangle, near);
+
+            assert_eq!(pieces.len(), expected);
+            for piece in &pieces {
+                assert!([piece.a, piece.b, piece.c]
+                    .iter()
+                    .all(|v| v.z >= near - 1e-5));
+                // The pieces face the same way as the triangle they were cut from
+                assert!(piece.normal().dot(&triangle.normal()) > 0.99);
+            }
+        }
     }
 }
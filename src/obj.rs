@@ -0,0 +1,449 @@
+//! Contains loading meshes from and saving them to Wavefront `.obj` files.
+//! Only vertex positions (`v`) and faces (`f`) are read; everything else in
+//! the file (normals, texture coordinates, materials, groups...) is skipped.
+
+use crate::import::MeshLoadError;
+use crate::{Mesh, Triangle, Vector3D};
+use core::fmt::{self, Display};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+/// The reasons an `.obj` file can't be loaded
+#[derive(Debug)]
+pub enum ObjError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// A number couldn't be parsed
+    InvalidNumber {
+        /// `line` - The line the number is on, counting from 1
+        line: usize,
+        /// `text` - The text that should have been a number
+        text: String,
+    },
+    /// A vertex has fewer than three coordinates
+    MissingCoordinates {
+        /// `line` - The line the vertex is on, counting from 1
+        line: usize,
+    },
+    /// A face has fewer than three vertices
+    TooFewFaceVertices {
+        /// `line` - The line the face is on, counting from 1
+        line: usize,
+        /// `count` - The amount of vertices the face has
+        count: usize,
+    },
+    /// A face uses a vertex that hasn't been defined
+    IndexOutOfRange {
+        /// `line` - The line the face is on, counting from 1
+        line: usize,
+        /// `index` - The index as it is written in the file
+        index: i64,
+        /// `vertices` - The amount of vertices defined before the face
+        vertices: usize,
+    },
+}
+
+impl Display for ObjError {
+    /// Print the error
+    ///
+    /// # Arguments
+    /// * `f` - The formatter
+    ///
+    /// # Returns
+    /// * `fmt::Result` - The result of the formatter
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(error) => write!(f, "Couldn't read the file: {}", error),
+            ObjError::InvalidNumber { line, text } => {
+                write!(f, "Line {}: `{}` isn't a valid number", line, text)
+            }
+            ObjError::MissingCoordinates { line } => {
+                write!(f, "Line {}: a vertex needs x, y and z coordinates", line)
+            }
+            ObjError::TooFewFaceVertices { line, count } => write!(
+                f,
+                "Line {}: a face needs at least 3 vertices, but this one has {}",
+                line, count
+            ),
+            ObjError::IndexOutOfRange {
+                line,
+                index,
+                vertices,
+            } => write!(
+                f,
+                "Line {}: the face uses vertex {}, but only {} vertices have been defined",
+                line, index, vertices
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    /// Wrap an IO error
+    ///
+    /// # Arguments
+    /// * `error` - The error to wrap
+    ///
+    /// # Returns
+    /// * `ObjError` - The wrapped error
+    fn from(error: io::Error) -> Self {
+        ObjError::Io(error)
+    }
+}
+
+/// Reads an `.obj` file one line at a time, remembering the vertices seen so far so that the
+/// faces after them can be turned into triangles
+#[derive(Default)]
+pub struct ObjParser {
+    /// `vertices` - Every vertex defined so far, in order
+    vertices: Vec<Vector3D>,
+    /// `line` - The number of the last line parsed, counting from 1
+    line: usize,
+}
+
+impl ObjParser {
+    /// Parse the next line of the file
+    ///
+    /// # Arguments
+    /// * `text` - The line
+    ///
+    /// # Returns
+    /// * `Result<Vec<Triangle>, ObjError>` - The triangles of the line if it is a face (a
+    ///   polygon is split into a fan of triangles around its first vertex), nothing for any
+    ///   other line, or why the line couldn't be read
+    pub fn parse_line(&mut self, text: &str) -> Result<Vec<Triangle>, ObjError> {
+        self.line += 1;
+
+        let mut parts = text.split_whitespace();
+
+        match parts.next() {
+            Some("v") => {
+                let coordinates = parts
+                    .take(3)
+                    .map(|part| self.number(part))
+                    .collect::<Result<Vec<f32>, ObjError>>()?;
+
+                match coordinates[..] {
+                    [x, y, z] => self.vertices.push(Vector3D::new(x, y, z)),
+                    _ => return Err(ObjError::MissingCoordinates { line: self.line }),
+                }
+
+                Ok(Vec::new())
+            }
+            Some("f") => {
+                let corners = parts
+                    .map(|part| self.vertex(part))
+                    .collect::<Result<Vec<Vector3D>, ObjError>>()?;
+
+                if corners.len() < 3 {
+                    return Err(ObjError::TooFewFaceVertices {
+                        line: self.line,
+                        count: corners.len(),
+                    });
+                }
+
+                Ok(corners
+                    .windows(2)
+                    .skip(1)
+                    .map(|pair| Triangle::new(corners[0], pair[0], pair[1]))
+                    .collect())
+            }
+            // Comments, blank lines and everything that isn't supported
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Parse a number
+    ///
+    /// # Arguments
+    /// * `text` - The text of the number
+    ///
+    /// # Returns
+    /// * `Result<T, ObjError>` - The number, or an error saying where it is
+    fn number<T: std::str::FromStr>(&self, text: &str) -> Result<T, ObjError> {
+        text.parse().map_err(|_| ObjError::InvalidNumber {
+            line: self.line,
+            text: text.to_string(),
+        })
+    }
+
+    /// Look up the vertex a corner of a face uses. Corners are written as `v`, `v/vt`,
+    /// `v/vt/vn` or `v//vn`, where only `v` matters here. Negative indices count back from
+    /// the last vertex.
+    ///
+    /// # Arguments
+    /// * `text` - The corner
+    ///
+    /// # Returns
+    /// * `Result<Vector3D, ObjError>` - The vertex, or why it couldn't be found
+    fn vertex(&self, text: &str) -> Result<Vector3D, ObjError> {
+        let index: i64 = self.number(text.split('/').next().unwrap_or(text))?;
+        let count = self.vertices.len() as i64;
+        let position = if index < 0 { count + index } else { index - 1 };
+
+        if (0..count).contains(&position) {
+            Ok(self.vertices[position as usize])
+        } else {
+            Err(ObjError::IndexOutOfRange {
+                line: self.line,
+                index,
+                vertices: self.vertices.len(),
+            })
+        }
+    }
+}
+
+/// Read the triangles of an `.obj` file one at a time as they are parsed, so a large file
+/// never has to be held in memory all at once. Only the vertices are kept. The iterator stops
+/// after the first error.
+///
+/// # Arguments
+/// * `reader` - The contents of the file
+///
+/// # Returns
+/// * `impl Iterator<Item = Result<Triangle, MeshLoadError>>` - Each triangle in the order
+///   it is in the file, or why the file couldn't be read
+pub fn obj_triangles<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Triangle, MeshLoadError>> {
+    let mut lines = reader.lines();
+    let mut parser = ObjParser::default();
+    let mut pending = VecDeque::new();
+    let mut failed = false;
+
+    std::iter::from_fn(move || loop {
+        if let Some(triangle) = pending.pop_front() {
+            return Some(Ok(triangle));
+        }
+        if failed {
+            return None;
+        }
+
+        match lines
+            .next()?
+            .map_err(ObjError::from)
+            .and_then(|line| parser.parse_line(&line))
+        {
+            Ok(triangles) => pending.extend(triangles),
+            Err(error) => {
+                failed = true;
+                return Some(Err(error.into()));
+            }
+        }
+    })
+}
+
+impl Mesh {
+    /// Load a mesh from a Wavefront `.obj` file
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// * `Result<Mesh, ObjError>` - The mesh, or why it couldn't be loaded
+    pub fn from_obj<P: AsRef<Path>>(path: P) -> Result<Mesh, ObjError> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut parser = ObjParser::default();
+        let mut mat = Vec::new();
+
+        for line in reader.lines() {
+            mat.append(&mut parser.parse_line(&line?)?);
+        }
+
+        Ok(Mesh::new(mat))
+    }
+
+    /// Save the mesh as a Wavefront `.obj` file. Vertices shared by several triangles are
+    /// only written once.
+    ///
+    /// # Arguments
+    /// * `writer` - Where to write the file
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Whether the file could be written
+    pub fn to_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let (vertices, faces) = self.weld_vertices();
+
+        for vertex in &vertices {
+            writeln!(writer, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+        }
+        for [a, b, c] in faces {
+            // OBJ indices count from 1
+            writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+        }
+
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get the path of a file in the `assets` folder
+    fn asset(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(name)
+    }
+
+    /// Parse every line of some `.obj` text
+    fn parse(text: &str) -> Result<Vec<Triangle>, ObjError> {
+        let mut parser = ObjParser::default();
+        let mut triangles = Vec::new();
+
+        for line in text.lines() {
+            triangles.append(&mut parser.parse_line(line)?);
+        }
+
+        Ok(triangles)
+    }
+
+    #[test]
+    fn loads_the_tetrahedron() {
+        let mesh = Mesh::from_obj(asset("tetrahedron.obj")).unwrap();
+
+        assert_eq!(mesh.mat.len(), 4);
+        assert!((mesh.volume() - 8.0 / 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn loads_the_quad_cube_as_triangles() {
+        let mesh = Mesh::from_obj(asset("cube.obj")).unwrap();
+
+        assert_eq!(mesh.mat.len(), 12);
+        assert!((mesh.volume() - 1.0).abs() < 1e-5);
+        assert_eq!(mesh.boundary_edge_count(), 0);
+    }
+
+    #[test]
+    fn polygons_are_split_into_a_fan() {
+        let triangles =
+            parse("v 0 0 0\nv 1 0 0\nv 2 1 0\nv 1 2 0\nv 0 1 0\nf 1 2 3 4 5\n").unwrap();
+
+        assert_eq!(triangles.len(), 3);
+        for triangle in &triangles {
+            assert_eq!(triangle.a, Vector3D::new(0.0, 0.0, 0.0));
+        }
+        assert_eq!(triangles[2].c, Vector3D::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn negative_and_slashed_indices_are_understood() {
+        let triangles = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nf -3/1 -2/1/1 3//1\n").unwrap();
+
+        assert_eq!(triangles.len(), 1);
+        assert_eq!(
+            [triangles[0].a, triangles[0].b, triangles[0].c],
+            [
+                Vector3D::new(0.0, 0.0, 0.0),
+                Vector3D::new(1.0, 0.0, 0.0),
+                Vector3D::new(0.0, 1.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_lines_say_where_they_are() {
+        let error = |text: &str| parse(text).err().map(|error| error.to_string());
+
+        assert_eq!(
+            error("# comment\nv 1 x 0"),
+            Some("Line 2: `x` isn't a valid number".to_string())
+        );
+        assert_eq!(
+            error("v 1 2"),
+            Some("Line 1: a vertex needs x, y and z coordinates".to_string())
+        );
+        assert_eq!(
+            error("v 0 0 0\nv 1 0 0\nf 1 2"),
+            Some("Line 3: a face needs at least 3 vertices, but this one has 2".to_string())
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1 2 3"),
+            Some(
+                "Line 2: the face uses vertex 2, but only 1 vertices have been defined".to_string()
+            )
+        );
+        assert!(matches!(
+            Mesh::from_obj(asset("missing.obj")),
+            Err(ObjError::Io(_))
+        ));
+    }
+
+    /// A reader that fails the test if anything tries to read from it
+    struct Unreadable;
+
+    impl io::Read for Unreadable {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            panic!("the loader read further than it needed to");
+        }
+    }
+
+    #[test]
+    fn streaming_yields_triangles_one_at_a_time() {
+        let text = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nf 1 2 3\nf 2 4 3\n";
+        let mut triangles = obj_triangles(io::Cursor::new(text));
+
+        let first = triangles.next().unwrap().unwrap();
+        assert_eq!(first.c, Vector3D::new(0.0, 1.0, 0.0));
+        let second = triangles.next().unwrap().unwrap();
+        assert_eq!(second.b, Vector3D::new(1.0, 1.0, 0.0));
+        assert!(triangles.next().is_none());
+    }
+
+    #[test]
+    fn streaming_only_reads_as_far_as_it_has_to() {
+        let text: &[u8] = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let reader = BufReader::new(io::Read::chain(text, Unreadable));
+        let mut triangles = obj_triangles(reader);
+
+        // Anything past the first face would reach the unreadable part
+        assert!(triangles.next().unwrap().is_ok());
+    }
+
+    #[test]
+    fn streaming_stops_after_an_error() {
+        let text = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nf 1 2 9\nf 3 2 1\n";
+        let results: Vec<_> = obj_triangles(io::Cursor::new(text)).collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(MeshLoadError::Obj(ObjError::IndexOutOfRange {
+                line: 5,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn exported_cube_reimports_the_same() {
+        let cube = crate::cube::get_cube_mesh();
+        let mut exported = Vec::new();
+        cube.to_obj(&mut exported).unwrap();
+        let text = String::from_utf8(exported).unwrap();
+
+        // Each corner is only written once
+        assert_eq!(
+            text.lines().filter(|line| line.starts_with("v ")).count(),
+            8
+        );
+        assert_eq!(
+            text.lines().filter(|line| line.starts_with("f ")).count(),
+            12
+        );
+
+        let reimported = Mesh::new(parse(&text).unwrap());
+        assert_eq!(reimported.mat.len(), cube.mat.len());
+        assert_eq!(reimported.bounding_box(), cube.bounding_box());
+        for (after, before) in reimported.mat.iter().zip(&cube.mat) {
+            assert_eq!([after.a, after.b, after.c], [before.a, before.b, before.c]);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Contains post-processing effects, which are done to the finished frame
+//! in a software framebuffer before it is shown
+
+use crate::framebuffer::Framebuffer;
+use sdl2::pixels::Color;
+
+/// How far the bloom blur reaches from each bright pixel, in pixels
+const BLOOM_RADIUS: usize = 4;
+
+/// Make bright parts of the frame glow. Pixels brighter than the threshold are blurred with a
+/// Gaussian (done as a horizontal pass and then a vertical one, which is the same as a 2D blur
+/// but much cheaper) and the blur is added back on top of the frame.
+///
+/// # Arguments
+/// * `framebuffer` - The frame to add the glow to
+/// * `threshold` - How bright a pixel has to be to glow, from 0 to 1
+/// * `intensity` - How strong the glow is, where 1 adds the blur back as it is
+///
+/// # Returns
+/// * `()` - Nothing
+pub fn bloom(framebuffer: &mut Framebuffer, threshold: f32, intensity: f32) -> () {
+    let (width, height) = (framebuffer.width as usize, framebuffer.height as usize);
+    let kernel = gaussian_kernel(BLOOM_RADIUS, BLOOM_RADIUS as f32 / 2.0);
+
+    let bright: Vec<[f32; 3]> = framebuffer
+        .pixels
+        .iter()
+        .map(|color| {
+            let luminance =
+                (0.2126 * color.r as f32 + 0.7152 * color.g as f32 + 0.0722 * color.b as f32)
+                    / 255.0;
+
+            if luminance > threshold {
+                [color.r as f32, color.g as f32, color.b as f32]
+            } else {
+                [0.0; 3]
+            }
+        })
+        .collect();
+
+    let blurred = blur_pass(&bright, width, height, &kernel, (1, 0));
+    let blurred = blur_pass(&blurred, width, height, &kernel, (0, 1));
+
+    for (pixel, glow) in framebuffer.pixels.iter_mut().zip(blurred) {
+        let add = |channel: u8, glow: f32| (channel as f32 + glow * intensity).min(255.0) as u8;
+
+        *pixel = Color::RGBA(
+            add(pixel.r, glow[0]),
+            add(pixel.g, glow[1]),
+            add(pixel.b, glow[2]),
+            pixel.a,
+        );
+    }
+}
+
+/// Get the weights of a one dimensional Gaussian blur
+///
+/// # Arguments
+/// * `radius` - How many pixels the blur reaches on each side
+/// * `sigma` - The standard deviation of the Gaussian, in pixels
+///
+/// # Returns
+/// * `Vec<f32>` - The `2 * radius + 1` weights from left to right, which add up to 1
+fn gaussian_kernel(radius: usize, sigma: f32) -> Vec<f32> {
+    let weights: Vec<f32> = (0..=radius * 2)
+        .map(|i| {
+            let x = i as f32 - radius as f32;
+            (-(x * x) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let total: f32 = weights.iter().sum();
+
+    weights.into_iter().map(|weight| weight / total).collect()
+}
+
+/// Blur an image in one direction. Pixels past the edge of the image count as black.
+///
+/// # Arguments
+/// * `pixels` - The red, green and blue of each pixel, row by row from the top left
+/// * `width` - The amount of pixels in each row
+/// * `height` - The amount of rows
+/// * `kernel` - The weights of the blur
+/// * `step` - The direction to blur in, `(1, 0)` for across and `(0, 1)` for down
+///
+/// # Returns
+/// * `Vec<[f32; 3]>` - The blurred pixels
+fn blur_pass(
+    pixels: &[[f32; 3]],
+    width: usize,
+    height: usize,
+    kernel: &[f32],
+    step: (isize, isize),
+) -> Vec<[f32; 3]> {
+    let radius = (kernel.len() / 2) as isize;
+    let mut blurred = vec![[0.0; 3]; pixels.len()];
+
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let mut sum = [0.0; 3];
+
+            for (i, weight) in kernel.iter().enumerate() {
+                let offset = i as isize - radius;
+                let (sample_x, sample_y) = (x + step.0 * offset, y + step.1 * offset);
+
+                if sample_x < 0
+                    || sample_y < 0
+                    || sample_x >= width as isize
+                    || sample_y >= height as isize
+                {
+                    continue;
+                }
+
+                let sample = pixels[sample_y as usize * width + sample_x as usize];
+                for channel in 0..3 {
+                    sum[channel] += sample[channel] * weight;
+                }
+            }
+
+            blurred[y as usize * width + x as usize] = sum;
+        }
+    }
+
+    blurred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::RenderTarget;
+    use sdl2::rect::Point;
+
+    #[test]
+    fn bright_pixel_glows_onto_its_neighbours() {
+        let mut framebuffer = Framebuffer::new(15, 15);
+        framebuffer.set(7, 7, Color::RGB(255, 255, 255));
+
+        bloom(&mut framebuffer, 0.5, 4.0);
+
+        let brightness = |x: i32, y: i32| framebuffer.get(x, y).unwrap().r;
+        assert_eq!(brightness(7, 7), 255);
+        for (x, y) in [(6, 7), (8, 7), (7, 6), (7, 8), (6, 6)] {
+            assert!(brightness(x, y) > 0, "({}, {})", x, y);
+        }
+        // The glow fades with distance and doesn't reach past the blur
+        assert!(brightness(8, 7) > brightness(9, 7));
+        assert!(brightness(9, 7) > brightness(10, 7));
+        assert_eq!(brightness(7 + BLOOM_RADIUS as i32 + 1, 7), 0);
+        assert_eq!(brightness(0, 0), 0);
+    }
+
+    #[test]
+    fn dim_pixels_do_not_glow() {
+        let mut framebuffer = Framebuffer::new(9, 9);
+        framebuffer.set_draw_color(Color::RGB(60, 60, 60));
+        framebuffer.draw_point(Point::new(4, 4));
+
+        bloom(&mut framebuffer, 0.5, 4.0);
+
+        assert_eq!(framebuffer.get(4, 4), Some(Color::RGB(60, 60, 60)));
+        assert_eq!(framebuffer.get(5, 4), Some(Color::RGB(0, 0, 0)));
+    }
+
+    #[test]
+    fn kernel_adds_up_to_one() {
+        let kernel = gaussian_kernel(BLOOM_RADIUS, 2.0);
+
+        assert_eq!(kernel.len(), BLOOM_RADIUS * 2 + 1);
+        assert!((kernel.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!(kernel[BLOOM_RADIUS] > kernel[BLOOM_RADIUS + 1]);
+        assert_eq!(kernel[0], kernel[BLOOM_RADIUS * 2]);
+    }
+}
@@ -0,0 +1,210 @@
+//! Contains generators for meshes that are built procedurally rather than
+//! being written out by hand like the cube
+
+use crate::{Mesh, Triangle, Vector3D};
+
+/// A single channel (grayscale) image, e.g. a heightmap
+pub struct GrayImage {
+    /// `width` - The amount of pixels in each row
+    pub width: u32,
+    /// `height` - The amount of rows
+    pub height: u32,
+    /// `pixels` - The intensity of each pixel, row by row from the top left
+    pub pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    /// Create a new image
+    ///
+    /// # Arguments
+    /// * `width` - The amount of pixels in each row
+    /// * `height` - The amount of rows
+    /// * `pixels` - The intensity of each pixel, row by row from the top left. This must hold
+    ///   exactly `width * height` pixels.
+    ///
+    /// # Returns
+    /// * `GrayImage` - The new image
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            (width * height) as usize,
+            "Image has the wrong amount of pixels for its size"
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Get the intensity of a pixel
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    ///
+    /// # Returns
+    /// * `u8` - The intensity of the pixel
+    pub fn get(&self, x: u32, y: u32) -> u8 {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+/// Build a terrain mesh from a heightmap. Every pixel becomes a vertex on a grid with one unit
+/// between neighbours, laid out across the XZ plane with the pixel intensity as its height.
+/// Each square between four neighbouring vertices is split into two triangles facing up.
+///
+/// # Arguments
+/// * `image` - The heightmap, which doesn't need to be square
+/// * `scale` - The height of a fully white pixel
+///
+/// # Returns
+/// * `Mesh` - The terrain, which is empty if the image is less than 2 pixels in either direction
+pub fn from_heightmap(image: &GrayImage, scale: f32) -> Mesh {
+    let vertex =
+        |x: u32, z: u32| Vector3D::new(x as f32, image.get(x, z) as f32 / 255.0 * scale, z as f32);
+
+    let mut mat = Vec::new();
+
+    for z in 0..image.height.saturating_sub(1) {
+        for x in 0..image.width.saturating_sub(1) {
+            let near_left = vertex(x, z);
+            let near_right = vertex(x + 1, z);
+            let far_left = vertex(x, z + 1);
+            let far_right = vertex(x + 1, z + 1);
+
+            mat.push(Triangle::new(near_left, far_left, far_right));
+            mat.push(Triangle::new(near_left, far_right, near_right));
+        }
+    }
+
+    Mesh::new(mat)
+}
+
+/// Build a sphere out of triangles that are all close to the same size, which avoids the
+/// pinching at the poles of a sphere built from latitude and longitude. It starts as an
+/// icosahedron and each subdivision splits every triangle into four, pushing the new vertices
+/// out onto the sphere.
+///
+/// # Arguments
+/// * `radius` - The radius of the sphere, which is centred on the origin
+/// * `subdivisions` - How many times to subdivide the icosahedron
+///
+/// # Returns
+/// * `Mesh` - The sphere, with `20 * 4^subdivisions` triangles facing outwards
+pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let vertices = [
+        Vector3D::new(-1.0, t, 0.0),
+        Vector3D::new(1.0, t, 0.0),
+        Vector3D::new(-1.0, -t, 0.0),
+        Vector3D::new(1.0, -t, 0.0),
+        Vector3D::new(0.0, -1.0, t),
+        Vector3D::new(0.0, 1.0, t),
+        Vector3D::new(0.0, -1.0, -t),
+        Vector3D::new(0.0, 1.0, -t),
+        Vector3D::new(t, 0.0, -1.0),
+        Vector3D::new(t, 0.0, 1.0),
+        Vector3D::new(-t, 0.0, -1.0),
+        Vector3D::new(-t, 0.0, 1.0),
+    ];
+    let faces: [[usize; 3]; 20] = [
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    let onto_sphere = |mut mesh: Mesh| {
+        for triangle in mesh.mat.iter_mut() {
+            for vertex in [&mut triangle.a, &mut triangle.b, &mut triangle.c] {
+                *vertex = vertex.normalize() * radius;
+            }
+        }
+
+        mesh
+    };
+
+    let icosahedron = onto_sphere(Mesh::new(
+        faces
+            .iter()
+            .map(|[a, b, c]| Triangle::new(vertices[*a], vertices[*b], vertices[*c]))
+            .collect(),
+    ));
+
+    (0..subdivisions).fold(icosahedron, |mesh, _| onto_sphere(mesh.subdivide()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_heightmap_makes_a_flat_grid() {
+        let image = GrayImage::new(3, 3, vec![128; 9]);
+
+        let mesh = from_heightmap(&image, 2.0);
+
+        assert_eq!(mesh.mat.len(), 8);
+        for triangle in &mesh.mat {
+            for vertex in [triangle.a, triangle.b, triangle.c] {
+                assert!((vertex.y - 128.0 / 255.0 * 2.0).abs() < 1e-6);
+            }
+            assert!(triangle.normal().y.abs() > 0.999);
+        }
+    }
+
+    #[test]
+    fn heightmap_does_not_need_to_be_square() {
+        let image = GrayImage::new(4, 2, vec![0; 8]);
+
+        assert_eq!(from_heightmap(&image, 1.0).mat.len(), 6);
+    }
+
+    #[test]
+    fn icosphere_triangle_count_and_radius() {
+        for subdivisions in 0..4 {
+            let sphere = icosphere(2.5, subdivisions);
+
+            assert_eq!(sphere.mat.len(), 20 * 4usize.pow(subdivisions));
+            for triangle in &sphere.mat {
+                for vertex in [triangle.a, triangle.b, triangle.c] {
+                    assert!((vertex.length() - 2.5).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn icosphere_is_closed_and_faces_outwards() {
+        let sphere = icosphere(1.0, 2);
+
+        assert_eq!(sphere.boundary_edge_count(), 0);
+        for triangle in &sphere.mat {
+            let centroid = (triangle.a + triangle.b + triangle.c) * (1.0 / 3.0);
+            assert!(triangle.normal().dot(&centroid) > 0.0);
+        }
+
+        // The volume closes in on a true sphere's as it is subdivided
+        let sphere_volume = 4.0 / 3.0 * std::f32::consts::PI;
+        assert!(sphere.volume() < sphere_volume);
+        assert!(sphere.volume() > sphere_volume * 0.95);
+    }
+}
@@ -0,0 +1,438 @@
+//! Contains the building blocks for drawing triangles and lines pixel by pixel,
+//! such as working out how much of a pixel a triangle covers
+
+use crate::depth::DepthBuffer;
+use crate::target::RenderTarget;
+use crate::vertex::Interpolate;
+use crate::{Triangle, Vector3D};
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+
+/// Where inside a pixel coverage is sampled. The samples are on a rotated grid, so that
+/// edges that are nearly horizontal or vertical still cross a different amount of them.
+const SAMPLES: [(f32, f32); 4] = [
+    (0.375, 0.125),
+    (0.875, 0.375),
+    (0.125, 0.625),
+    (0.625, 0.875),
+];
+
+/// Settings for filling triangles
+#[derive(Debug, Clone, Copy)]
+pub struct RasterConfig {
+    /// `antialias` - Whether pixels along the edges of a triangle are blended with what is
+    /// behind them by how much of the pixel the triangle covers
+    pub antialias: bool,
+}
+
+impl Default for RasterConfig {
+    /// Create a default config, which doesn't antialias
+    ///
+    /// # Returns
+    /// * `RasterConfig` - The default config
+    fn default() -> Self {
+        Self { antialias: false }
+    }
+}
+
+/// Get which side of the line through `a` and `b` a point is on
+///
+/// # Arguments
+/// * `a` - The start of the line
+/// * `b` - The end of the line
+/// * `x` - The x coordinate of the point
+/// * `y` - The y coordinate of the point
+///
+/// # Returns
+/// * `f32` - Twice the signed area of the triangle `a`, `b`, point
+pub fn edge_function(a: &Vector3D, b: &Vector3D, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// Check whether a point is inside a triangle on the screen, whichever way round the
+/// triangle is wound
+///
+/// # Arguments
+/// * `triangle` - The triangle, in screen coordinates
+/// * `x` - The x coordinate of the point
+/// * `y` - The y coordinate of the point
+///
+/// # Returns
+/// * `bool` - Whether the point is inside, which is never the case for a zero-area triangle
+pub fn contains(triangle: &Triangle, x: f32, y: f32) -> bool {
+    let ab = edge_function(&triangle.a, &triangle.b, x, y);
+    let bc = edge_function(&triangle.b, &triangle.c, x, y);
+    let ca = edge_function(&triangle.c, &triangle.a, x, y);
+
+    let area = edge_function(&triangle.a, &triangle.b, triangle.c.x, triangle.c.y);
+
+    area != 0.0 && ((ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0))
+}
+
+/// Get how much of a pixel a triangle covers, by checking a few points spread across it
+///
+/// # Arguments
+/// * `triangle` - The triangle, in screen coordinates
+/// * `x` - The column of the pixel
+/// * `y` - The row of the pixel
+///
+/// # Returns
+/// * `f32` - The fraction of the pixel that is covered, from 0 to 1
+pub fn coverage(triangle: &Triangle, x: i32, y: i32) -> f32 {
+    let covered = SAMPLES
+        .iter()
+        .filter(|(dx, dy)| contains(triangle, x as f32 + dx, y as f32 + dy))
+        .count();
+
+    covered as f32 / SAMPLES.len() as f32
+}
+
+/// Blend a colour over what is behind it by how much of the pixel it covers
+///
+/// # Arguments
+/// * `color` - The colour being drawn
+/// * `background` - The colour already in the pixel
+/// * `coverage` - How much of the pixel is covered, from 0 to 1
+///
+/// # Returns
+/// * `Color` - The blended colour
+pub fn blend(color: Color, background: Color, coverage: f32) -> Color {
+    background.lerp(&color, coverage.clamp(0.0, 1.0))
+}
+
+/// Clip a line to a rectangle of pixels starting at the origin, so that drawing it only walks
+/// the pixels that can be seen. This is what keeps lines with far off screen ends (e.g. ones
+/// that were projected from just in front of the camera) from taking forever to draw.
+///
+/// # Arguments
+/// * `start` - The start of the line
+/// * `end` - The end of the line
+/// * `width` - The width of the rectangle
+/// * `height` - The height of the rectangle
+///
+/// # Returns
+/// * `Option<((i64, i64), (i64, i64))>` - The part of the line inside the rectangle, going
+///   the same way, or `None` if none of it is inside
+pub fn clip_line(
+    start: (i64, i64),
+    end: (i64, i64),
+    width: u32,
+    height: u32,
+) -> Option<((i64, i64), (i64, i64))> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    // Liang-Barsky: walk the line as `start + (end - start) * t` and narrow `t` down to the
+    // range that is inside all four edges
+    let (max_x, max_y) = (width as i64 - 1, height as i64 - 1);
+    let (x0, y0) = (start.0 as f64, start.1 as f64);
+    let (dx, dy) = (end.0 as f64 - x0, end.1 as f64 - y0);
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+
+    for (p, q) in [
+        (-dx, x0),
+        (dx, max_x as f64 - x0),
+        (-dy, y0),
+        (dy, max_y as f64 - y0),
+    ] {
+        if p == 0.0 {
+            // Parallel to this edge, so it is either all inside or all outside of it
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+
+    // Rounding can put a clipped end half a pixel outside, so it is pulled back in
+    let point = |t: f64| {
+        (
+            ((x0 + dx * t).round() as i64).clamp(0, max_x),
+            ((y0 + dy * t).round() as i64).clamp(0, max_y),
+        )
+    };
+
+    Some((point(t0), point(t1)))
+}
+
+/// Draw a line one pixel at a time, only where it passes the depth test against what has
+/// already been drawn. The depth is blended along the line on the screen, which is right for
+/// projected depth as it changes linearly across the screen.
+///
+/// # Arguments
+/// * `target` - The target to draw the line on, in its current draw colour
+/// * `start` - The start of the line, in screen coordinates with its projected depth
+/// * `end` - The end of the line, in screen coordinates with its projected depth
+/// * `depth_buffer` - The depth of what has been drawn so far, which isn't changed
+/// * `bias` - How much closer the line is treated as being, in normalized depth units
+///
+/// # Returns
+/// * `()` - Nothing
+pub fn draw_depth_tested_line<T: RenderTarget>(
+    target: &mut T,
+    start: &Vector3D,
+    end: &Vector3D,
+    depth_buffer: &DepthBuffer,
+    bias: f32,
+) -> () {
+    let Some((from, to)) = clip_line(
+        (start.x as i64, start.y as i64),
+        (end.x as i64, end.y as i64),
+        depth_buffer.width,
+        depth_buffer.height,
+    ) else {
+        return;
+    };
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_squared = dx * dx + dy * dy;
+    let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let x = (from.0 as f32 + (to.0 - from.0) as f32 * t).round() as i32;
+        let y = (from.1 as f32 + (to.1 - from.1) as f32 * t).round() as i32;
+        // How far along the whole line the pixel is, as clipping may have cut the ends off
+        let along = if length_squared > 0.0 {
+            (((x as f32 - start.x) * dx + (y as f32 - start.y) * dy) / length_squared)
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let depth = start.z + (end.z - start.z) * along;
+
+        if depth_buffer.test(x, y, depth, bias) {
+            target.draw_point(Point::new(x, y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::depth::DepthConfig;
+    use crate::framebuffer::Framebuffer;
+    use crate::target::RecordingTarget;
+
+    /// A triangle across most of a 40 by 30 screen, sloping away in depth
+    fn sloped_triangle() -> Triangle {
+        Triangle::new(
+            Vector3D::new(2.0, 2.0, 0.3),
+            Vector3D::new(37.0, 5.0, 0.6),
+            Vector3D::new(8.0, 27.0, 0.45),
+        )
+    }
+
+    /// Draw the edges of a triangle depth tested, returning how many pixels were drawn
+    fn draw_edges(triangle: &Triangle, depth_buffer: &DepthBuffer, bias: f32) -> usize {
+        let mut target = RecordingTarget::new();
+
+        for (start, end) in [
+            (triangle.a, triangle.b),
+            (triangle.b, triangle.c),
+            (triangle.c, triangle.a),
+        ] {
+            draw_depth_tested_line(&mut target, &start, &end, depth_buffer, bias);
+        }
+
+        target.points.len()
+    }
+
+    #[test]
+    fn biased_wireframe_passes_over_its_own_fill() {
+        let triangle = sloped_triangle();
+        let mut framebuffer = Framebuffer::new(40, 30);
+        let mut depth_buffer = DepthBuffer::new(40, 30);
+        let unobstructed = draw_edges(&triangle, &depth_buffer, 0.0);
+
+        triangle.fill(
+            &mut framebuffer,
+            Color::RGB(255, 0, 0),
+            &mut depth_buffer,
+            &RasterConfig::default(),
+        );
+
+        assert!(unobstructed > 0);
+        assert!(framebuffer.pixels.contains(&Color::RGB(255, 0, 0)));
+        assert_eq!(
+            draw_edges(
+                &triangle,
+                &depth_buffer,
+                DepthConfig::default().wireframe_bias
+            ),
+            unobstructed
+        );
+    }
+
+    #[test]
+    fn biased_wireframe_is_still_hidden_behind_nearer_fill() {
+        let triangle = sloped_triangle();
+        let mut framebuffer = Framebuffer::new(40, 30);
+        let mut depth_buffer = DepthBuffer::new(40, 30);
+        let nearer = Triangle::new(
+            Vector3D::new(-10.0, -10.0, 0.1),
+            Vector3D::new(80.0, -10.0, 0.1),
+            Vector3D::new(-10.0, 80.0, 0.1),
+        );
+
+        nearer.fill(
+            &mut framebuffer,
+            Color::RGB(0, 255, 0),
+            &mut depth_buffer,
+            &RasterConfig::default(),
+        );
+
+        assert_eq!(
+            draw_edges(
+                &triangle,
+                &depth_buffer,
+                DepthConfig::default().wireframe_bias
+            ),
+            0
+        );
+    }
+
+    /// A triangle whose long edge cuts diagonally through the pixels along it, just missing
+    /// the centres of the pixels outside it
+    fn diagonal_triangle() -> Triangle {
+        Triangle::new(
+            Vector3D::new(0.0, 0.0, 0.5),
+            Vector3D::new(20.75, 0.0, 0.5),
+            Vector3D::new(0.0, 20.75, 0.5),
+        )
+    }
+
+    #[test]
+    fn edge_pixel_is_partly_covered() {
+        let triangle = diagonal_triangle();
+
+        // Pixel (10, 10) straddles the edge x + y = 20.75
+        let edge = coverage(&triangle, 10, 10);
+        assert!(edge > 0.0 && edge < 1.0, "{}", edge);
+        assert_eq!(coverage(&triangle, 2, 2), 1.0);
+        assert_eq!(coverage(&triangle, 15, 15), 0.0);
+    }
+
+    #[test]
+    fn antialiased_fill_blends_edge_pixels() {
+        let triangle = diagonal_triangle();
+        let mut depth_buffer = DepthBuffer::new(30, 30);
+        let mut target = RecordingTarget::new();
+
+        triangle.fill(
+            &mut target,
+            Color::RGB(255, 255, 255),
+            &mut depth_buffer,
+            &RasterConfig { antialias: true },
+        );
+
+        assert!(!target.blended.is_empty());
+        for (_, coverage) in &target.blended {
+            assert!(*coverage > 0.0 && *coverage < 1.0);
+        }
+
+        let mut aliased = RecordingTarget::new();
+        triangle.fill(
+            &mut aliased,
+            Color::RGB(255, 255, 255),
+            &mut DepthBuffer::new(30, 30),
+            &RasterConfig::default(),
+        );
+        assert!(aliased.blended.is_empty());
+    }
+
+    #[test]
+    fn blend_mixes_by_coverage() {
+        let (white, black) = (Color::RGB(255, 255, 255), Color::RGB(0, 0, 0));
+
+        assert_eq!(blend(white, black, 0.0), black);
+        assert_eq!(blend(white, black, 0.5), Color::RGB(128, 128, 128));
+        assert_eq!(blend(white, black, 1.0), white);
+    }
+
+    #[test]
+    fn fill_sets_interior_pixels_and_no_others() {
+        let mut framebuffer = Framebuffer::new(20, 20);
+        let mut depth_buffer = DepthBuffer::new(20, 20);
+        let triangle = Triangle::new(
+            Vector3D::new(2.0, 2.0, 0.5),
+            Vector3D::new(2.0, 12.0, 0.5),
+            Vector3D::new(12.0, 2.0, 0.5),
+        );
+        let red = Color::RGB(255, 0, 0);
+
+        triangle.fill(
+            &mut framebuffer,
+            red,
+            &mut depth_buffer,
+            &RasterConfig::default(),
+        );
+
+        for (x, y) in [(3, 3), (5, 5), (2, 10), (10, 2), (6, 4)] {
+            assert_eq!(framebuffer.get(x, y), Some(red), "({}, {})", x, y);
+        }
+        for (x, y) in [(1, 1), (0, 5), (8, 8), (11, 11), (15, 3), (3, 15)] {
+            assert_eq!(
+                framebuffer.get(x, y),
+                Some(Color::RGB(0, 0, 0)),
+                "({}, {})",
+                x,
+                y
+            );
+        }
+        // Exactly the pixels whose centres are inside: 10 + 9 + ... + 1
+        let filled = framebuffer
+            .pixels
+            .iter()
+            .filter(|&&pixel| pixel == red)
+            .count();
+        assert_eq!(filled, 55);
+    }
+
+    #[test]
+    fn degenerate_and_off_screen_triangles_are_safe_to_fill() {
+        let mut framebuffer = Framebuffer::new(20, 20);
+        let mut depth_buffer = DepthBuffer::new(20, 20);
+        let config = RasterConfig { antialias: true };
+        let point = Vector3D::new(5.0, 5.0, 0.5);
+        let line = Triangle::new(
+            Vector3D::new(1.0, 1.0, 0.5),
+            Vector3D::new(9.0, 9.0, 0.5),
+            Vector3D::new(17.0, 17.0, 0.5),
+        );
+
+        Triangle::new(point, point, point).fill(
+            &mut framebuffer,
+            Color::WHITE,
+            &mut depth_buffer,
+            &config,
+        );
+        line.fill(&mut framebuffer, Color::WHITE, &mut depth_buffer, &config);
+        assert!(framebuffer
+            .pixels
+            .iter()
+            .all(|&pixel| pixel == Color::RGB(0, 0, 0)));
+
+        // Only the top left of this one is on screen
+        Triangle::new(
+            Vector3D::new(-10.0, -10.0, 0.5),
+            Vector3D::new(-10.0, 30.0, 0.5),
+            Vector3D::new(30.0, -10.0, 0.5),
+        )
+        .fill(
+            &mut framebuffer,
+            Color::WHITE,
+            &mut depth_buffer,
+            &RasterConfig::default(),
+        );
+        assert_eq!(framebuffer.get(0, 0), Some(Color::WHITE));
+        assert_eq!(framebuffer.get(19, 19), Some(Color::RGB(0, 0, 0)));
+    }
+}
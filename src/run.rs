@@ -0,0 +1,372 @@
+//! Contains how the main loop is run - either until the window is closed at
+//! a steady frame rate, or for a fixed amount of frames as fast as possible
+//! so the renderer can be benchmarked
+
+use crate::stats::RenderStats;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How many frames a second the loop is held to when it sleeps
+pub const FRAME_RATE_CAP: u32 = 60;
+
+/// Something the main loop calls once every frame is finished, with how many frames have
+/// been finished before it and the frame's stats. This is where per-stage timings are
+/// picked up, e.g. to log or print them.
+pub type FrameCallback<'a> = Box<dyn FnMut(u32, &RenderStats) + 'a>;
+
+/// How the main loop should run, read from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunConfig {
+    /// `frames` - How many frames to run before stopping, or `None` to run until the window
+    /// is closed
+    pub frames: Option<u32>,
+    /// `no_sleep` - Whether to skip the sleep at the end of each frame and run as fast as
+    /// possible
+    pub no_sleep: bool,
+    /// `stats_csv` - The file to log the stats of every frame to, if any
+    pub stats_csv: Option<PathBuf>,
+    /// `bloom` - Whether bright parts of the frame should glow
+    pub bloom: bool,
+    /// `model` - The `.obj` file to show instead of the cube, if any
+    pub model: Option<PathBuf>,
+    /// `skybox` - The folder of skybox images to draw behind the scene instead of the
+    /// background, if any
+    pub skybox: Option<PathBuf>,
+    /// `camera` - The file to load the camera from, as written by
+    /// `Camera::to_config_string`, if any
+    pub camera: Option<PathBuf>,
+    /// `profile` - Whether to print how long each stage of a frame took, once a second
+    pub profile: bool,
+}
+
+impl RunConfig {
+    /// Create a new run config
+    ///
+    /// # Arguments
+    /// * `frames` - How many frames to run before stopping, if any
+    /// * `no_sleep` - Whether to skip the sleep at the end of each frame
+    ///
+    /// # Returns
+    /// * `RunConfig` - The new run config
+    pub fn new(frames: Option<u32>, no_sleep: bool) -> Self {
+        Self {
+            frames,
+            no_sleep,
+            stats_csv: None,
+            bloom: false,
+            model: None,
+            skybox: None,
+            camera: None,
+            profile: false,
+        }
+    }
+
+    /// Create a config that runs exactly `n` frames without sleeping, for benchmarking
+    ///
+    /// # Arguments
+    /// * `n` - How many frames to run
+    ///
+    /// # Returns
+    /// * `RunConfig` - The benchmarking config
+    pub fn run_frames(n: u32) -> Self {
+        Self::new(Some(n), true)
+    }
+
+    /// Read the config from command line arguments. `--frames <n>` runs `n` frames without
+    /// sleeping, `--no-sleep` turns the sleep off on its own, `--stats-csv <path>` logs
+    /// the stats of every frame to a CSV file, `--bloom` turns on the bloom post effect,
+    /// `--model <path>` shows a model from an `.obj` file instead of the cube,
+    /// `--skybox <folder>` draws a skybox from the images in a folder behind the scene,
+    /// `--camera <path>` starts the camera where a saved camera config puts it and
+    /// `--profile` prints how long each stage of a frame took once a second.
+    ///
+    /// # Arguments
+    /// * `args` - The arguments, without the program name
+    ///
+    /// # Returns
+    /// * `Result<RunConfig, String>` - The config, or why the arguments couldn't be read
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = RunConfig::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--frames" => {
+                    let frames = args
+                        .next()
+                        .ok_or_else(|| "`--frames` needs an amount of frames".to_string())?;
+                    let frames = frames
+                        .parse()
+                        .map_err(|_| format!("Invalid amount of frames: `{}`", frames))?;
+
+                    config.frames = Some(frames);
+                    config.no_sleep = true;
+                }
+                "--stats-csv" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| "`--stats-csv` needs a file path".to_string())?;
+
+                    config.stats_csv = Some(PathBuf::from(path));
+                }
+                "--model" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| "`--model` needs a file path".to_string())?;
+
+                    config.model = Some(PathBuf::from(path));
+                }
+                "--skybox" => {
+                    let folder = args
+                        .next()
+                        .ok_or_else(|| "`--skybox` needs a folder".to_string())?;
+
+                    config.skybox = Some(PathBuf::from(folder));
+                }
+                "--camera" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| "`--camera` needs a file path".to_string())?;
+
+                    config.camera = Some(PathBuf::from(path));
+                }
+                "--no-sleep" => config.no_sleep = true,
+                "--bloom" => config.bloom = true,
+                "--profile" => config.profile = true,
+                _ => return Err(format!("Unknown argument `{}`", arg)),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Check whether the loop should stop after a frame
+    ///
+    /// # Arguments
+    /// * `frames_run` - How many frames have been run so far
+    ///
+    /// # Returns
+    /// * `bool` - Whether the set amount of frames has been run
+    pub fn is_done(&self, frames_run: u32) -> bool {
+        self.frames.is_some_and(|frames| frames_run >= frames)
+    }
+}
+
+impl Default for RunConfig {
+    /// Create a config that runs until the window is closed, sleeping each frame
+    ///
+    /// # Returns
+    /// * `RunConfig` - The default run config
+    fn default() -> Self {
+        Self::new(None, false)
+    }
+}
+
+/// The frame times collected over a run, for reporting once it is over
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameTimes {
+    /// `frames` - How many frames have been recorded
+    pub frames: u32,
+    /// `total` - The time taken by every recorded frame added together
+    pub total: Duration,
+}
+
+impl FrameTimes {
+    /// Record a finished frame
+    ///
+    /// # Arguments
+    /// * `stats` - The stats of the frame
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn record(&mut self, stats: &RenderStats) -> () {
+        self.frames += 1;
+        self.total += stats.total;
+    }
+
+    /// Get the average time a frame took
+    ///
+    /// # Returns
+    /// * `Duration` - The average frame time, or zero if no frames were recorded
+    pub fn average(&self) -> Duration {
+        if self.frames == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.frames
+        }
+    }
+}
+
+/// Measures how much time passes between frames, so that motion can be scaled by it and stay
+/// the same speed however fast frames are drawn
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTimer {
+    /// `max_dt` - The longest step a single frame can take, in seconds. After a stall (e.g.
+    /// the window being dragged) motion carries on from where it was rather than jumping.
+    pub max_dt: f32,
+    /// `last` - When the last frame started, if there has been one
+    last: Option<Instant>,
+    /// `dt` - The time between the last two frames, in seconds
+    dt: f32,
+    /// `elapsed` - Every step added together, in seconds
+    elapsed: f32,
+}
+
+impl FrameTimer {
+    /// Create a new frame timer
+    ///
+    /// # Arguments
+    /// * `max_dt` - The longest step a single frame can take, in seconds
+    ///
+    /// # Returns
+    /// * `FrameTimer` - The new frame timer, which hasn't seen a frame yet
+    pub fn new(max_dt: f32) -> Self {
+        Self {
+            max_dt,
+            last: None,
+            dt: 0.0,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Start a new frame now
+    ///
+    /// # Returns
+    /// * `f32` - The time since the last frame started, in seconds
+    pub fn tick(&mut self) -> f32 {
+        self.tick_at(Instant::now())
+    }
+
+    /// Start a new frame at a given instant
+    ///
+    /// # Arguments
+    /// * `now` - When the frame started
+    ///
+    /// # Returns
+    /// * `f32` - The time since the last frame started, in seconds, which is zero for the
+    ///   first frame and at most `max_dt`
+    pub fn tick_at(&mut self, now: Instant) -> f32 {
+        self.dt = self.last.map_or(0.0, |last| {
+            now.saturating_duration_since(last)
+                .as_secs_f32()
+                .min(self.max_dt)
+        });
+        self.last = Some(now);
+        self.elapsed += self.dt;
+
+        self.dt
+    }
+
+    /// Get the time between the last two frames
+    ///
+    /// # Returns
+    /// * `f32` - The time, in seconds
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// Get how much time the frames have covered in total
+    ///
+    /// # Returns
+    /// * `f32` - The time, in seconds
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+}
+
+impl Default for FrameTimer {
+    /// Create a frame timer that limits each step to a quarter of a second
+    ///
+    /// # Returns
+    /// * `FrameTimer` - The default frame timer
+    fn default() -> Self {
+        Self::new(0.25)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read a config from arguments written as one string
+    fn from_args(args: &str) -> Result<RunConfig, String> {
+        RunConfig::from_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn run_frames_stops_after_exactly_that_many_frames() {
+        let config = RunConfig::run_frames(10);
+        let mut frame_times = FrameTimes::default();
+        let mut iterations = 0;
+
+        // The main loop without a window: record the frame, then check whether to stop
+        loop {
+            iterations += 1;
+            frame_times.record(&RenderStats {
+                total: Duration::from_millis(2),
+                ..RenderStats::default()
+            });
+
+            if config.is_done(frame_times.frames) || iterations > 100 {
+                break;
+            }
+        }
+
+        assert_eq!(iterations, 10);
+        assert_eq!(frame_times.frames, 10);
+        assert_eq!(frame_times.average(), Duration::from_millis(2));
+        assert!(config.no_sleep);
+        assert!(!RunConfig::default().is_done(u32::MAX));
+    }
+
+    #[test]
+    fn frames_argument_turns_off_the_sleep() {
+        assert_eq!(from_args("--frames 10"), Ok(RunConfig::run_frames(10)));
+        assert_eq!(from_args(""), Ok(RunConfig::default()));
+        assert_eq!(from_args("--no-sleep"), Ok(RunConfig::new(None, true)));
+    }
+
+    #[test]
+    fn bad_arguments_are_reported() {
+        assert_eq!(
+            from_args("--frames"),
+            Err("`--frames` needs an amount of frames".to_string())
+        );
+        assert_eq!(
+            from_args("--frames ten"),
+            Err("Invalid amount of frames: `ten`".to_string())
+        );
+        assert_eq!(
+            from_args("--fast"),
+            Err("Unknown argument `--fast`".to_string())
+        );
+    }
+
+    #[test]
+    fn no_frames_average_to_zero() {
+        assert_eq!(FrameTimes::default().average(), Duration::ZERO);
+    }
+
+    #[test]
+    fn frame_timer_reports_the_time_between_frames() {
+        let start = Instant::now();
+        let mut timer = FrameTimer::new(0.25);
+
+        assert_eq!(timer.tick_at(start), 0.0);
+        assert!((timer.tick_at(start + Duration::from_millis(16)) - 0.016).abs() < 1e-6);
+        assert!((timer.tick_at(start + Duration::from_millis(50)) - 0.034).abs() < 1e-6);
+        assert!((timer.dt() - 0.034).abs() < 1e-6);
+        assert!((timer.elapsed() - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn frame_timer_limits_long_stalls() {
+        let start = Instant::now();
+        let mut timer = FrameTimer::default();
+
+        timer.tick_at(start);
+        assert_eq!(timer.tick_at(start + Duration::from_secs(3)), 0.25);
+        // An instant from before the last frame counts as no time at all
+        assert_eq!(timer.tick_at(start), 0.0);
+        assert_eq!(timer.elapsed(), 0.25);
+    }
+}
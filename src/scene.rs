@@ -0,0 +1,823 @@
+//! Contains the scene - every mesh that should be rendered, along with
+//! the limits that stop it from growing out of hand
+
+use crate::animation::Timeline;
+use crate::camera::View;
+use crate::depth::DepthBuffer;
+use crate::raster::RasterConfig;
+use crate::target::RenderTarget;
+use crate::{Matrix, Mesh, Triangle, Vector3D};
+use core::fmt::{self, Display};
+use sdl2::pixels::Color;
+
+/// Limits that apply to the scene as a whole, rather than to a single mesh
+#[derive(Debug, Clone, Copy)]
+pub struct SceneConfig {
+    /// `max_triangles` - The most triangles the scene can hold across all of its meshes
+    pub max_triangles: usize,
+}
+
+impl Default for SceneConfig {
+    /// Create a default config with a budget that is comfortable for the software pipeline
+    ///
+    /// # Returns
+    /// * `SceneConfig` - The default config
+    fn default() -> Self {
+        Self {
+            max_triangles: 100_000,
+        }
+    }
+}
+
+/// How the triangles of a mesh are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Only the edges are drawn, in the scene's line colour
+    Wireframe,
+    /// The triangles are filled in with the scene's fill colour, nearest in front
+    Filled,
+}
+
+/// A mesh in the scene, along with how it should be drawn
+pub struct SceneObject {
+    /// `mesh` - The mesh that is drawn
+    pub mesh: Mesh,
+    /// `render_mode` - How this mesh is drawn, or `None` to use the scene's render mode
+    pub render_mode: Option<RenderMode>,
+    /// `drawn` - Whether `Scene::draw` draws the mesh. Meshes that are drawn some other way
+    /// are still kept in the scene so they can be picked.
+    pub drawn: bool,
+    /// `timeline` - Moves the mesh over time, or `None` to leave it where it is
+    pub timeline: Option<Timeline>,
+}
+
+impl SceneObject {
+    /// Get where the object's timeline puts its mesh at a point in time
+    ///
+    /// # Arguments
+    /// * `time` - The time to sample the timeline at, in seconds
+    ///
+    /// # Returns
+    /// * `Option<Matrix>` - The matrix to transform the mesh by, or `None` if the object has
+    ///   no timeline
+    pub fn matrix_at(&self, time: f32) -> Option<Matrix> {
+        self.timeline
+            .as_ref()
+            .map(|timeline| timeline.sample(time).matrix())
+    }
+
+    /// Get the triangles of the object where they are at a point in time
+    ///
+    /// # Arguments
+    /// * `time` - The time to sample the timeline at, in seconds
+    ///
+    /// # Returns
+    /// * `impl Iterator<Item = Triangle>` - Each triangle in world coordinates
+    fn triangles_at(&self, time: f32) -> impl Iterator<Item = Triangle> + '_ {
+        let matrix = self.matrix_at(time);
+
+        self.mesh.mat.iter().map(move |triangle| match &matrix {
+            Some(matrix) => triangle.transformed(matrix),
+            None => triangle.clone(),
+        })
+    }
+}
+
+/// The reasons a mesh can't be added to the scene
+#[derive(Debug)]
+pub enum SceneError {
+    /// Adding the mesh would take the scene over its triangle budget
+    TriangleBudgetExceeded {
+        /// `mesh` - The index the mesh would have had in the scene
+        mesh: usize,
+        /// `triangles` - The amount of triangles in the mesh
+        triangles: usize,
+        /// `total` - The amount of triangles already in the scene
+        total: usize,
+        /// `max` - The triangle budget of the scene
+        max: usize,
+    },
+}
+
+impl Display for SceneError {
+    /// Print the error
+    ///
+    /// # Arguments
+    /// * `f` - The formatter
+    ///
+    /// # Returns
+    /// * `fmt::Result` - The result of the formatter
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::TriangleBudgetExceeded {
+                mesh,
+                triangles,
+                total,
+                max,
+            } => write!(
+                f,
+                "Mesh {} has {} triangles, which would take the scene from {} to {} triangles (the limit is {})",
+                mesh,
+                triangles,
+                total,
+                total + triangles,
+                max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// Which mesh in the scene was picked. Objects and instances are indexed separately, so the
+/// index alone doesn't say which list it is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Picked {
+    /// One of the scene's objects
+    Object {
+        /// `object` - The index of the object in `Scene::objects`
+        object: usize,
+    },
+    /// One copy of an instanced mesh
+    Instance {
+        /// `instances` - The index of the instanced mesh in `Scene::instances`
+        instances: usize,
+        /// `transform` - The index of the copy's transform in `Instances::transforms`
+        transform: usize,
+    },
+}
+
+/// What is under a pixel of the screen
+#[derive(Debug, Clone, Copy)]
+pub struct PickResult {
+    /// `picked` - The mesh that was hit
+    pub picked: Picked,
+    /// `triangle` - The index of the triangle in the mesh
+    pub triangle: usize,
+    /// `point` - Where the triangle was hit
+    pub point: Vector3D,
+}
+
+/// A mesh that is drawn many times over, once for each of its transforms. The mesh is only
+/// stored once, no matter how many transforms there are.
+pub struct Instances {
+    /// `mesh` - The mesh that is drawn
+    pub mesh: Mesh,
+    /// `transforms` - Where (and how) each copy of the mesh is drawn
+    pub transforms: Vec<Matrix>,
+}
+
+impl Instances {
+    /// Get the amount of triangles drawn across every instance
+    ///
+    /// # Returns
+    /// * `usize` - The triangles in the mesh times the amount of instances
+    pub fn triangle_count(&self) -> usize {
+        self.mesh.mat.len() * self.transforms.len()
+    }
+}
+
+/// Everything that should be rendered. Meshes are checked against the scene budget as they
+/// are added, so a huge model is rejected up front instead of stalling the game loop.
+pub struct Scene {
+    /// `objects` - The meshes in the scene
+    pub objects: Vec<SceneObject>,
+    /// `instances` - The meshes that are drawn at many transforms
+    pub instances: Vec<Instances>,
+    /// `config` - The limits of the scene
+    pub config: SceneConfig,
+    /// `render_mode` - How meshes are drawn unless they say otherwise. Instances are
+    /// always drawn this way.
+    pub render_mode: RenderMode,
+    /// `line_color` - The colour wireframe meshes are drawn in
+    pub line_color: Color,
+    /// `fill_color` - The colour filled meshes are drawn in
+    pub fill_color: Color,
+    /// `raster_config` - How filled meshes are filled
+    pub raster_config: RasterConfig,
+    /// `time` - How long the scene has been playing, in seconds, which is where the objects'
+    /// timelines are sampled
+    pub time: f32,
+}
+
+impl Scene {
+    /// Create a new, empty scene
+    ///
+    /// # Arguments
+    /// * `config` - The limits of the scene
+    ///
+    /// # Returns
+    /// * `Scene` - The new scene
+    pub fn new(config: SceneConfig) -> Self {
+        Self {
+            objects: Vec::new(),
+            instances: Vec::new(),
+            config,
+            render_mode: RenderMode::Wireframe,
+            line_color: Color::RGB(255, 255, 255),
+            fill_color: Color::RGB(70, 70, 90),
+            raster_config: RasterConfig::default(),
+            time: 0.0,
+        }
+    }
+
+    /// Move the scene on in time, so animated objects move along their timelines the next
+    /// time the scene is drawn or picked
+    ///
+    /// # Arguments
+    /// * `dt` - How long has passed, in seconds
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn advance(&mut self, dt: f32) -> () {
+        self.time += dt;
+    }
+
+    /// Get the amount of triangles in the scene
+    ///
+    /// # Returns
+    /// * `usize` - The sum of the triangles in every mesh and every instance
+    pub fn triangle_count(&self) -> usize {
+        self.objects
+            .iter()
+            .map(|object| object.mesh.mat.len())
+            .sum::<usize>()
+            + self
+                .instances
+                .iter()
+                .map(Instances::triangle_count)
+                .sum::<usize>()
+    }
+
+    /// Check that adding more triangles would keep the scene within its budget
+    ///
+    /// # Arguments
+    /// * `mesh` - The index the new mesh would have
+    /// * `triangles` - The amount of triangles that would be added
+    ///
+    /// # Returns
+    /// * `Result<(), SceneError>` - Nothing if they fit, otherwise why they didn't
+    fn check_budget(&self, mesh: usize, triangles: usize) -> Result<(), SceneError> {
+        let total = self.triangle_count();
+
+        if total + triangles > self.config.max_triangles {
+            Err(SceneError::TriangleBudgetExceeded {
+                mesh,
+                triangles,
+                total,
+                max: self.config.max_triangles,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Add a mesh to the scene, as long as it fits in the triangle budget
+    ///
+    /// # Arguments
+    /// * `mesh` - The mesh to add
+    ///
+    /// # Returns
+    /// * `Result<usize, SceneError>` - The index of the mesh in the scene, or why it didn't fit
+    pub fn add(&mut self, mesh: Mesh) -> Result<usize, SceneError> {
+        self.add_object(SceneObject {
+            mesh,
+            render_mode: None,
+            drawn: true,
+            timeline: None,
+        })
+    }
+
+    /// Add a mesh to the scene that is always drawn in a render mode, whatever the scene's
+    /// render mode is, as long as it fits in the triangle budget
+    ///
+    /// # Arguments
+    /// * `mesh` - The mesh to add
+    /// * `render_mode` - How the mesh is drawn
+    ///
+    /// # Returns
+    /// * `Result<usize, SceneError>` - The index of the mesh in the scene, or why it didn't fit
+    pub fn add_with_mode(
+        &mut self,
+        mesh: Mesh,
+        render_mode: RenderMode,
+    ) -> Result<usize, SceneError> {
+        self.add_object(SceneObject {
+            mesh,
+            render_mode: Some(render_mode),
+            drawn: true,
+            timeline: None,
+        })
+    }
+
+    /// Add a mesh that is moved along a timeline as the scene plays, as long as it fits in
+    /// the triangle budget
+    ///
+    /// # Arguments
+    /// * `mesh` - The mesh to add
+    /// * `timeline` - Where the mesh is moved to over time
+    ///
+    /// # Returns
+    /// * `Result<usize, SceneError>` - The index of the mesh in the scene, or why it didn't fit
+    pub fn add_animated(&mut self, mesh: Mesh, timeline: Timeline) -> Result<usize, SceneError> {
+        self.add_object(SceneObject {
+            mesh,
+            render_mode: None,
+            drawn: true,
+            timeline: Some(timeline),
+        })
+    }
+
+    /// Add a mesh that is drawn outside of the scene, so that it can be picked along with
+    /// everything else without being drawn twice, as long as it fits in the triangle budget
+    ///
+    /// # Arguments
+    /// * `mesh` - The mesh to add
+    ///
+    /// # Returns
+    /// * `Result<usize, SceneError>` - The index of the mesh in the scene, or why it didn't fit
+    pub fn add_undrawn(&mut self, mesh: Mesh) -> Result<usize, SceneError> {
+        self.add_object(SceneObject {
+            mesh,
+            render_mode: None,
+            drawn: false,
+            timeline: None,
+        })
+    }
+
+    /// Add an object to the scene, as long as it fits in the triangle budget
+    ///
+    /// # Arguments
+    /// * `object` - The object to add
+    ///
+    /// # Returns
+    /// * `Result<usize, SceneError>` - The index of the object in the scene, or why it didn't
+    ///   fit
+    fn add_object(&mut self, object: SceneObject) -> Result<usize, SceneError> {
+        self.check_budget(self.objects.len(), object.mesh.mat.len())?;
+
+        self.objects.push(object);
+        Ok(self.objects.len() - 1)
+    }
+
+    /// Add a mesh that should be drawn once for each of a list of transforms, as long as
+    /// every copy fits in the triangle budget
+    ///
+    /// # Arguments
+    /// * `mesh` - The mesh to add
+    /// * `transforms` - The transform of each copy
+    ///
+    /// # Returns
+    /// * `Result<usize, SceneError>` - The index of the instances in the scene, or why they
+    ///   didn't fit
+    pub fn add_instanced(
+        &mut self,
+        mesh: Mesh,
+        transforms: Vec<Matrix>,
+    ) -> Result<usize, SceneError> {
+        let instances = Instances { mesh, transforms };
+        self.check_budget(self.instances.len(), instances.triangle_count())?;
+
+        self.instances.push(instances);
+        Ok(self.instances.len() - 1)
+    }
+
+    /// Find what is under a pixel of the screen (e.g. where the mouse was clicked), checking
+    /// both the objects and every copy of the instanced meshes
+    ///
+    /// # Arguments
+    /// * `pixel` - The pixel, in screen coordinates
+    /// * `projection` - The view and projection matrix
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `Option<PickResult>` - The nearest triangle under the pixel, if there is one. The
+    ///   point is in world coordinates.
+    pub fn pick(
+        &self,
+        pixel: (i32, i32),
+        projection: &Matrix,
+        width: f32,
+        height: f32,
+    ) -> Option<PickResult> {
+        let objects = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(object, scene_object)| {
+                let hit = match scene_object.matrix_at(self.time) {
+                    Some(matrix) => scene_object
+                        .mesh
+                        .transformed(&matrix)
+                        .pick(pixel, projection, width, height),
+                    None => scene_object.mesh.pick(pixel, projection, width, height),
+                }?;
+                Some((Picked::Object { object }, hit))
+            });
+        let instances = self
+            .instances
+            .iter()
+            .enumerate()
+            .flat_map(|(instances, copies)| {
+                copies
+                    .transforms
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(transform, matrix)| {
+                        let hit = copies
+                            .mesh
+                            .transformed(matrix)
+                            .pick(pixel, projection, width, height)?;
+                        Some((
+                            Picked::Instance {
+                                instances,
+                                transform,
+                            },
+                            hit,
+                        ))
+                    })
+            });
+
+        objects
+            .chain(instances)
+            .min_by(|(_, (_, _, a)), (_, (_, _, b))| a.total_cmp(b))
+            .map(|(picked, (triangle, point, _))| PickResult {
+                picked,
+                triangle,
+                point,
+            })
+    }
+
+    /// Get the triangle a pick hit, e.g. to highlight it
+    ///
+    /// # Arguments
+    /// * `result` - What was picked
+    ///
+    /// # Returns
+    /// * `Option<Triangle>` - The triangle in world coordinates, or `None` if the scene has
+    ///   changed so that it isn't there any more
+    pub fn picked_triangle(&self, result: &PickResult) -> Option<Triangle> {
+        match result.picked {
+            Picked::Object { object } => self
+                .objects
+                .get(object)?
+                .triangles_at(self.time)
+                .nth(result.triangle),
+            Picked::Instance {
+                instances,
+                transform,
+            } => {
+                let copies = self.instances.get(instances)?;
+                let triangle = copies.mesh.mat.get(result.triangle)?;
+
+                Some(triangle.transformed(copies.transforms.get(transform)?))
+            }
+        }
+    }
+
+    /// Draw everything in the scene, each mesh in its own render mode. Filled meshes hide each
+    /// other (and whatever else is in the depth buffer) by depth, but wireframes are drawn
+    /// over everything. Instanced meshes are transformed on the fly, so they are never copied.
+    /// Every triangle is clipped against the near plane before it is projected. Objects that
+    /// are drawn outside of the scene are skipped, and animated objects are drawn where their
+    /// timeline has them at the scene's time.
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the scene on
+    /// * `view` - How the camera sees the scene
+    /// * `depth_buffer` - The depth of what has been drawn so far, which is the same size as
+    ///   the target. It isn't cleared, so the scene can be drawn along with other meshes.
+    ///
+    /// # Returns
+    /// * `usize` - The amount of triangles that were drawn, after clipping
+    pub fn draw<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        view: &View,
+        depth_buffer: &mut DepthBuffer,
+    ) -> usize {
+        let mut drawn = 0;
+
+        for object in self.objects.iter().filter(|object| object.drawn) {
+            drawn += self.draw_triangles(
+                target,
+                view,
+                depth_buffer,
+                object.triangles_at(self.time),
+                object.render_mode.unwrap_or(self.render_mode),
+            );
+        }
+
+        for instances in &self.instances {
+            for transform in &instances.transforms {
+                drawn += self.draw_triangles(
+                    target,
+                    view,
+                    depth_buffer,
+                    instances
+                        .mesh
+                        .mat
+                        .iter()
+                        .map(|triangle| triangle.transformed(transform)),
+                    self.render_mode,
+                );
+            }
+        }
+
+        drawn
+    }
+
+    /// Clip, project and draw triangles in a render mode
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the triangles on
+    /// * `view` - How the camera sees the scene
+    /// * `depth_buffer` - The depth of what has been drawn so far
+    /// * `triangles` - The triangles, in world coordinates
+    /// * `render_mode` - How the triangles are drawn
+    ///
+    /// # Returns
+    /// * `usize` - The amount of triangles that were drawn, after clipping
+    fn draw_triangles<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        view: &View,
+        depth_buffer: &mut DepthBuffer,
+        triangles: impl Iterator<Item = Triangle>,
+        render_mode: RenderMode,
+    ) -> usize {
+        let mut drawn = 0;
+
+        if render_mode == RenderMode::Wireframe {
+            target.set_draw_color(self.line_color);
+        }
+
+        for triangle in triangles {
+            for clipped in view.clip_triangle(&triangle) {
+                let projected = view.project(&clipped);
+
+                match render_mode {
+                    RenderMode::Wireframe => projected.draw(target),
+                    RenderMode::Filled => {
+                        projected.fill(target, self.fill_color, depth_buffer, &self.raster_config)
+                    }
+                }
+                drawn += 1;
+            }
+        }
+
+        drawn
+    }
+}
+
+impl Default for Scene {
+    /// Create an empty scene with the default limits
+    ///
+    /// # Returns
+    /// * `Scene` - The default scene
+    fn default() -> Self {
+        Self::new(SceneConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::Transform;
+    use crate::camera::Camera;
+    use crate::cube;
+    use crate::target::RecordingTarget;
+
+    #[test]
+    fn adding_past_the_budget_fails_with_the_offending_mesh() {
+        let mut scene = Scene::new(SceneConfig { max_triangles: 30 });
+
+        assert_eq!(scene.add(cube::get_cube_mesh()).unwrap(), 0);
+        assert_eq!(scene.add(cube::get_cube_mesh()).unwrap(), 1);
+
+        let error = scene.add(cube::get_cube_mesh()).unwrap_err();
+        assert!(matches!(
+            error,
+            SceneError::TriangleBudgetExceeded {
+                mesh: 2,
+                triangles: 12,
+                total: 24,
+                max: 30,
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Mesh 2 has 12 triangles, which would take the scene from 24 to 36 triangles (the limit is 30)"
+        );
+        assert_eq!(scene.objects.len(), 2);
+        assert_eq!(scene.triangle_count(), 24);
+    }
+
+    /// The view from the default camera of a 200 by 150 display
+    fn test_view() -> View {
+        View::new(
+            &Camera::default(),
+            &Matrix::projection(90.0, 150.0 / 200.0, 0.1, 100.0),
+            0.1,
+            200.0,
+            150.0,
+        )
+    }
+
+    #[test]
+    fn every_instance_is_drawn() {
+        let mut scene = Scene::default();
+        let transforms = (0..100)
+            .map(|i| Matrix::translation((i % 10) as f32 - 5.0, (i / 10) as f32 - 5.0, 10.0))
+            .collect();
+        scene
+            .add_instanced(cube::get_cube_mesh(), transforms)
+            .unwrap();
+        let mut target = RecordingTarget::new();
+
+        let drawn = scene.draw(&mut target, &test_view(), &mut DepthBuffer::new(200, 150));
+
+        assert_eq!(scene.triangle_count(), 100 * 12);
+        assert_eq!(drawn, 100 * 12);
+        assert_eq!(target.lines.len(), 100 * 12 * 3);
+    }
+
+    /// A cube placed so that the middle of the screen is well inside the first triangle of
+    /// its front face, at a depth
+    fn cube_in_front_at(z: f32) -> Mesh {
+        cube::get_cube_mesh().transformed(&Matrix::translation(-0.3, -0.7, z))
+    }
+
+    #[test]
+    fn picking_the_middle_of_the_screen_hits_the_nearest_front_face() {
+        let mut scene = Scene::default();
+        scene.add(cube_in_front_at(4.0)).unwrap();
+        scene.add(cube_in_front_at(-0.5)).unwrap();
+        let view = test_view();
+
+        let result = scene
+            .pick((100, 75), &view.view_projection(), 200.0, 150.0)
+            .unwrap();
+
+        assert_eq!(result.picked, Picked::Object { object: 1 });
+        assert_eq!(result.triangle, 0);
+        assert!((result.point - Vector3D::new(0.0, 0.0, -0.5)).length() < 0.05);
+    }
+
+    #[test]
+    fn picking_tells_instances_apart_from_objects() {
+        let mut scene = Scene::default();
+        scene.add(cube_in_front_at(4.0)).unwrap();
+        scene
+            .add_instanced(
+                cube::get_cube_mesh(),
+                vec![
+                    Matrix::translation(5.0, 0.0, 0.0),
+                    Matrix::translation(-0.3, -0.7, 1.0),
+                ],
+            )
+            .unwrap();
+        let view = test_view();
+
+        let result = scene
+            .pick((100, 75), &view.view_projection(), 200.0, 150.0)
+            .unwrap();
+
+        assert_eq!(
+            result.picked,
+            Picked::Instance {
+                instances: 0,
+                transform: 1,
+            }
+        );
+        assert!(scene
+            .pick((0, 0), &view.view_projection(), 200.0, 150.0)
+            .is_none());
+    }
+
+    #[test]
+    fn undrawn_objects_are_picked_but_not_drawn() {
+        let mut scene = Scene::default();
+        scene.add_undrawn(cube_in_front_at(1.0)).unwrap();
+        scene
+            .add_instanced(
+                cube::get_cube_mesh(),
+                vec![Matrix::translation(-0.3, -0.7, 4.0)],
+            )
+            .unwrap();
+        let view = test_view();
+        let mut target = RecordingTarget::new();
+
+        let drawn = scene.draw(&mut target, &view, &mut DepthBuffer::new(200, 150));
+        let result = scene
+            .pick((100, 75), &view.view_projection(), 200.0, 150.0)
+            .unwrap();
+
+        assert_eq!(drawn, 12);
+        assert_eq!(result.picked, Picked::Object { object: 0 });
+        assert_eq!(
+            scene.picked_triangle(&result).unwrap().a,
+            scene.objects[0].mesh.mat[result.triangle].a
+        );
+    }
+
+    #[test]
+    fn picked_instance_triangles_are_moved_to_their_copy() {
+        let mut scene = Scene::default();
+        scene
+            .add_instanced(
+                cube::get_cube_mesh(),
+                vec![
+                    Matrix::translation(5.0, 0.0, 0.0),
+                    Matrix::translation(0.0, 0.0, 2.0),
+                ],
+            )
+            .unwrap();
+        let result = PickResult {
+            picked: Picked::Instance {
+                instances: 0,
+                transform: 1,
+            },
+            triangle: 0,
+            point: Vector3D::default(),
+        };
+
+        let triangle = scene.picked_triangle(&result).unwrap();
+
+        assert_eq!(
+            triangle.a,
+            scene.instances[0].mesh.mat[0].a + Vector3D::new(0.0, 0.0, 2.0)
+        );
+        scene.instances[0].transforms.pop();
+        assert!(scene.picked_triangle(&result).is_none());
+    }
+
+    #[test]
+    fn animated_objects_move_along_their_timeline_as_the_scene_plays() {
+        let mut timeline = Timeline::new(crate::easing::linear);
+        timeline.add_keyframe(
+            0.0,
+            Transform {
+                translation: Vector3D::new(10.0, 0.0, 0.0),
+                ..Transform::default()
+            },
+        );
+        timeline.add_keyframe(2.0, Transform::default());
+        let mut scene = Scene::default();
+        scene.add_animated(cube_in_front_at(4.0), timeline).unwrap();
+        let view = test_view();
+        let pick = |scene: &Scene| scene.pick((100, 75), &view.view_projection(), 200.0, 150.0);
+
+        // Off to the side at first, then in the middle of the screen once the timeline ends
+        assert!(pick(&scene).is_none());
+        scene.advance(1.5);
+        assert!(pick(&scene).is_none());
+        scene.advance(0.5);
+        let result = pick(&scene).unwrap();
+
+        assert_eq!(result.picked, Picked::Object { object: 0 });
+        assert!((result.point - Vector3D::new(0.0, 0.0, 4.0)).length() < 0.1);
+        assert_eq!(
+            scene.picked_triangle(&result).unwrap().a,
+            scene.objects[0].mesh.mat[result.triangle].a
+        );
+    }
+
+    /// Count the lines drawn in a colour
+    fn lines_in(target: &RecordingTarget, color: Color) -> usize {
+        target
+            .lines
+            .iter()
+            .filter(|(_, _, line_color)| *line_color == color)
+            .count()
+    }
+
+    #[test]
+    fn each_object_is_drawn_in_its_own_mode() {
+        let mut scene = Scene::default();
+        scene
+            .add_with_mode(cube_in_front_at(4.0), RenderMode::Filled)
+            .unwrap();
+        scene
+            .add(cube::get_cube_mesh().transformed(&Matrix::translation(1.0, 0.0, 6.0)))
+            .unwrap();
+        let mut target = RecordingTarget::new();
+
+        let drawn = scene.draw(&mut target, &test_view(), &mut DepthBuffer::new(200, 150));
+
+        // Filling draws runs of pixels as lines too, so they are told apart by colour
+        assert_eq!(drawn, 24);
+        assert_eq!(lines_in(&target, scene.line_color), 12 * 3);
+        assert!(lines_in(&target, scene.fill_color) > 0);
+
+        // The override wins over the scene's mode the other way round too
+        scene.render_mode = RenderMode::Filled;
+        scene.objects[0].render_mode = Some(RenderMode::Wireframe);
+        let mut target = RecordingTarget::new();
+        scene.draw(&mut target, &test_view(), &mut DepthBuffer::new(200, 150));
+
+        assert_eq!(lines_in(&target, scene.line_color), 12 * 3);
+        assert!(lines_in(&target, scene.fill_color) > 0);
+    }
+}
@@ -0,0 +1,310 @@
+//! Contains the skybox - a cube of six images around the camera that is
+//! drawn behind the scene in place of the background, so far away scenery
+//! stays put as the view turns
+
+use crate::framebuffer::Framebuffer;
+use crate::target::RenderTarget;
+use crate::{multiply_matrix_vector, Matrix, Vector3D};
+use sdl2::image::LoadSurface;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Point;
+use sdl2::surface::Surface;
+use std::path::Path;
+
+/// A face of the skybox cube, named after the direction it is in from the camera
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
+    /// The face along +x
+    PositiveX,
+    /// The face along -x
+    NegativeX,
+    /// The face along +y, which is down on the screen
+    PositiveY,
+    /// The face along -y, which is up on the screen
+    NegativeY,
+    /// The face along +z, which is straight ahead
+    PositiveZ,
+    /// The face along -z, which is behind the camera
+    NegativeZ,
+}
+
+impl CubeFace {
+    /// Every face, in the order the skybox keeps its images in
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+
+    /// Get the file name (without an extension) the face's image is loaded from
+    ///
+    /// # Returns
+    /// * `&'static str` - The name of the file
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            CubeFace::PositiveX => "px",
+            CubeFace::NegativeX => "nx",
+            CubeFace::PositiveY => "py",
+            CubeFace::NegativeY => "ny",
+            CubeFace::PositiveZ => "pz",
+            CubeFace::NegativeZ => "nz",
+        }
+    }
+
+    /// Work out which face a direction points at, and where on the face it lands. Each face
+    /// is seen upright from inside the cube, the same way the camera sees straight ahead.
+    ///
+    /// # Arguments
+    /// * `direction` - The direction to look up, which doesn't have to be normalized
+    ///
+    /// # Returns
+    /// * `(CubeFace, f32, f32)` - The face, and how far across and down it the direction
+    ///   lands, from 0 to 1
+    pub fn from_direction(direction: &Vector3D) -> (CubeFace, f32, f32) {
+        let Vector3D { x, y, z } = *direction;
+        let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+
+        // The axis the direction leans along most picks the face
+        let (face, across, down, major) = if ax >= ay && ax >= az {
+            if x > 0.0 {
+                (CubeFace::PositiveX, -z, y, ax)
+            } else {
+                (CubeFace::NegativeX, z, y, ax)
+            }
+        } else if ay >= az {
+            if y > 0.0 {
+                (CubeFace::PositiveY, x, -z, ay)
+            } else {
+                (CubeFace::NegativeY, x, z, ay)
+            }
+        } else if z > 0.0 {
+            (CubeFace::PositiveZ, x, y, az)
+        } else {
+            (CubeFace::NegativeZ, -x, y, az)
+        };
+
+        if major == 0.0 {
+            return (CubeFace::PositiveZ, 0.5, 0.5);
+        }
+
+        (
+            face,
+            (across / major + 1.0) * 0.5,
+            (down / major + 1.0) * 0.5,
+        )
+    }
+}
+
+/// A cube of six images drawn around the camera, infinitely far away
+pub struct Skybox {
+    /// `faces` - The image of each face, in the order of `CubeFace::ALL`
+    faces: [Framebuffer; 6],
+}
+
+impl Skybox {
+    /// Create a new skybox
+    ///
+    /// # Arguments
+    /// * `faces` - The image of each face, in the order of `CubeFace::ALL`
+    ///
+    /// # Returns
+    /// * `Skybox` - The new skybox
+    pub fn new(faces: [Framebuffer; 6]) -> Self {
+        Self { faces }
+    }
+
+    /// Load a skybox from six images in a folder, named after their faces (`px.png`,
+    /// `nx.png`, `py.png`, `ny.png`, `pz.png` and `nz.png`)
+    ///
+    /// # Arguments
+    /// * `folder` - The folder the images are in
+    ///
+    /// # Returns
+    /// * `Result<Skybox, String>` - The skybox, or why an image couldn't be loaded
+    pub fn load<P: AsRef<Path>>(folder: P) -> Result<Self, String> {
+        let mut faces = CubeFace::ALL.map(|_| Framebuffer::new(0, 0));
+
+        for (image, face) in faces.iter_mut().zip(CubeFace::ALL) {
+            let path = folder.as_ref().join(format!("{}.png", face.file_name()));
+            let surface = Surface::from_file(&path)
+                .and_then(|surface| surface.convert_format(PixelFormatEnum::RGBA32))
+                .map_err(|error| format!("Couldn't load `{}`: {}", path.display(), error))?;
+
+            let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch());
+            *image = Framebuffer::new(width, height);
+            surface.with_lock(|bytes| {
+                // Rows can be padded, so each one is loaded on its own
+                let rows: Vec<u8> = bytes
+                    .chunks(pitch as usize)
+                    .take(height as usize)
+                    .flat_map(|row| row[..width as usize * 4].iter().copied())
+                    .collect();
+                image.load_rgba_bytes(&rows);
+            });
+        }
+
+        Ok(Self::new(faces))
+    }
+
+    /// Get the colour of the sky in a direction
+    ///
+    /// # Arguments
+    /// * `direction` - The direction to look in
+    ///
+    /// # Returns
+    /// * `Color` - The colour of the nearest pixel of the face the direction points at
+    pub fn sample(&self, direction: &Vector3D) -> Color {
+        let (face, across, down) = CubeFace::from_direction(direction);
+        let image = &self.faces[face as usize];
+
+        let x = ((across * image.width as f32) as i32).clamp(0, image.width as i32 - 1);
+        let y = ((down * image.height as f32) as i32).clamp(0, image.height as i32 - 1);
+
+        image.get(x, y).unwrap_or(Color::BLACK)
+    }
+
+    /// Get the direction the camera looks in through a pixel, by taking the pixel back out
+    /// of the screen at the near and far planes
+    ///
+    /// # Arguments
+    /// * `x` - The x coordinate of the pixel
+    /// * `y` - The y coordinate of the pixel
+    /// * `inverse_view_projection` - The inverse of the view and projection matrices
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `Vector3D` - The direction, normalized
+    pub fn direction(
+        x: f32,
+        y: f32,
+        inverse_view_projection: &Matrix,
+        width: f32,
+        height: f32,
+    ) -> Vector3D {
+        // The reverse of the scale into view in `project_to_screen`
+        let (ndc_x, ndc_y) = (x / width * 2.0 - 1.0, y / height * 2.0 - 1.0);
+
+        let mut near = Vector3D::default();
+        let mut far = Vector3D::default();
+        multiply_matrix_vector(
+            &Vector3D::new(ndc_x, ndc_y, 0.0),
+            &mut near,
+            inverse_view_projection,
+        );
+        multiply_matrix_vector(
+            &Vector3D::new(ndc_x, ndc_y, 1.0),
+            &mut far,
+            inverse_view_projection,
+        );
+
+        (far - near).normalize()
+    }
+
+    /// Draw the sky over the whole target, sampling it through the centre of each pixel
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the sky on
+    /// * `inverse_view_projection` - The inverse of the view and projection matrices
+    /// * `width` - The width of the target
+    /// * `height` - The height of the target
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        inverse_view_projection: &Matrix,
+        width: u32,
+        height: u32,
+    ) -> () {
+        for y in 0..height {
+            for x in 0..width {
+                let direction = Skybox::direction(
+                    x as f32 + 0.5,
+                    y as f32 + 0.5,
+                    inverse_view_projection,
+                    width as f32,
+                    height as f32,
+                );
+
+                target.set_draw_color(self.sample(&direction));
+                target.draw_point(Point::new(x as i32, y as i32));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+
+    /// A skybox with each face a single pixel of its own colour
+    fn coloured_skybox() -> Skybox {
+        Skybox::new(CubeFace::ALL.map(|face| {
+            let mut image = Framebuffer::new(1, 1);
+            image.set(0, 0, Color::RGB(face as u8 * 40, 0, 0));
+
+            image
+        }))
+    }
+
+    /// Get the inverse view and projection of a camera, the same way the main loop does
+    fn inverse_view_projection(camera: &Camera) -> Matrix {
+        camera
+            .view_matrix()
+            .multiply(&Matrix::projection(90.0, 150.0 / 200.0, 0.1, 100.0))
+            .inverse()
+            .unwrap()
+    }
+
+    #[test]
+    fn screen_centre_looks_at_the_forward_face() {
+        let camera = Camera::default();
+        let direction =
+            Skybox::direction(100.0, 75.0, &inverse_view_projection(&camera), 200.0, 150.0);
+
+        assert!((direction - Vector3D::new(0.0, 0.0, 1.0)).length() < 1e-4);
+        assert_eq!(CubeFace::from_direction(&direction).0, CubeFace::PositiveZ);
+        assert_eq!(
+            coloured_skybox().sample(&direction),
+            Color::RGB(CubeFace::PositiveZ as u8 * 40, 0, 0)
+        );
+    }
+
+    #[test]
+    fn screen_centre_follows_the_camera() {
+        let camera = Camera::new(
+            Vector3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(-5.0, 0.0, 0.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+        let direction =
+            Skybox::direction(100.0, 75.0, &inverse_view_projection(&camera), 200.0, 150.0);
+
+        assert!((direction - Vector3D::new(-1.0, 0.0, 0.0)).length() < 1e-4);
+        assert_eq!(CubeFace::from_direction(&direction).0, CubeFace::NegativeX);
+    }
+
+    #[test]
+    fn straight_at_a_face_lands_in_its_middle() {
+        for (direction, face) in [
+            (Vector3D::new(2.0, 0.0, 0.0), CubeFace::PositiveX),
+            (Vector3D::new(-2.0, 0.0, 0.0), CubeFace::NegativeX),
+            (Vector3D::new(0.0, 2.0, 0.0), CubeFace::PositiveY),
+            (Vector3D::new(0.0, -2.0, 0.0), CubeFace::NegativeY),
+            (Vector3D::new(0.0, 0.0, 2.0), CubeFace::PositiveZ),
+            (Vector3D::new(0.0, 0.0, -2.0), CubeFace::NegativeZ),
+        ] {
+            assert_eq!(CubeFace::from_direction(&direction), (face, 0.5, 0.5));
+        }
+
+        // Right and down on the screen is right and down on the forward face
+        let (_, across, down) = CubeFace::from_direction(&Vector3D::new(0.5, 0.5, 1.0));
+        assert_eq!((across, down), (0.75, 0.75));
+    }
+}
@@ -0,0 +1,159 @@
+//! Contains spring meshes, whose vertices can be knocked out of place and
+//! then wobble back to where they started, for jiggly soft-body effects
+
+use crate::{Mesh, Triangle, Vector3D};
+
+/// A mesh whose vertices are each held to their rest position by a damped spring
+pub struct SpringMesh {
+    /// `stiffness` - How hard the springs pull the vertices back, per unit they are out of
+    /// place. Stiffer springs wobble faster.
+    pub stiffness: f32,
+    /// `damping` - How much the vertices are slowed down, per unit of speed. Without any the
+    /// vertices wobble forever.
+    pub damping: f32,
+    /// `rest` - Where each vertex wants to be
+    rest: Vec<Vector3D>,
+    /// `positions` - Where each vertex is
+    positions: Vec<Vector3D>,
+    /// `velocities` - How fast each vertex is moving, in units per second
+    velocities: Vec<Vector3D>,
+    /// `faces` - The indices of the corners of each triangle
+    faces: Vec<[usize; 3]>,
+}
+
+impl SpringMesh {
+    /// Create a new spring mesh, at rest in the shape of a mesh. Vertices shared by several
+    /// triangles are welded so they move together.
+    ///
+    /// # Arguments
+    /// * `mesh` - The shape the mesh rests in
+    /// * `stiffness` - How hard the springs pull the vertices back
+    /// * `damping` - How much the vertices are slowed down
+    ///
+    /// # Returns
+    /// * `SpringMesh` - The new spring mesh
+    pub fn new(mesh: &Mesh, stiffness: f32, damping: f32) -> Self {
+        let (rest, faces) = mesh.weld_vertices();
+
+        Self {
+            stiffness,
+            damping,
+            positions: rest.clone(),
+            velocities: vec![Vector3D::default(); rest.len()],
+            rest,
+            faces,
+        }
+    }
+
+    /// Get where each vertex is
+    ///
+    /// # Returns
+    /// * `&[Vector3D]` - The position of each welded vertex
+    pub fn positions(&self) -> &[Vector3D] {
+        &self.positions
+    }
+
+    /// Get where each vertex wants to be
+    ///
+    /// # Returns
+    /// * `&[Vector3D]` - The rest position of each welded vertex
+    pub fn rest_positions(&self) -> &[Vector3D] {
+        &self.rest
+    }
+
+    /// Knock a vertex out of place, leaving its spring to pull it back
+    ///
+    /// # Arguments
+    /// * `vertex` - The index of the welded vertex
+    /// * `offset` - How far to move it
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn displace(&mut self, vertex: usize, offset: Vector3D) -> () {
+        self.positions[vertex] += offset;
+    }
+
+    /// Move the vertices on by a step of time. The velocity is updated before the position
+    /// (semi-implicit Euler), which keeps the springs from gaining energy and blowing up.
+    ///
+    /// # Arguments
+    /// * `dt` - How much time has passed, in seconds
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn update(&mut self, dt: f32) -> () {
+        for ((position, velocity), rest) in self
+            .positions
+            .iter_mut()
+            .zip(self.velocities.iter_mut())
+            .zip(&self.rest)
+        {
+            let acceleration = (*position - *rest) * -self.stiffness - *velocity * self.damping;
+
+            *velocity += acceleration * dt;
+            *position += *velocity * dt;
+        }
+    }
+
+    /// Get the mesh in its current shape
+    ///
+    /// # Returns
+    /// * `Mesh` - The mesh, with each vertex where it is now
+    pub fn mesh(&self) -> Mesh {
+        Mesh::new(
+            self.faces
+                .iter()
+                .map(|[a, b, c]| {
+                    Triangle::new(self.positions[*a], self.positions[*b], self.positions[*c])
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube;
+
+    #[test]
+    fn displaced_vertex_returns_towards_rest() {
+        let mut spring = SpringMesh::new(&cube::get_cube_mesh(), 40.0, 4.0);
+        assert_eq!(spring.positions().len(), 8);
+
+        spring.displace(3, Vector3D::new(0.5, 0.0, 0.0));
+        let distance =
+            |spring: &SpringMesh| (spring.positions()[3] - spring.rest_positions()[3]).length();
+        let start = distance(&spring);
+
+        for _ in 0..120 {
+            spring.update(1.0 / 60.0);
+        }
+
+        assert!(distance(&spring) < start * 0.1);
+        // Only the knocked vertex moves
+        for (index, (position, rest)) in spring
+            .positions()
+            .iter()
+            .zip(spring.rest_positions())
+            .enumerate()
+        {
+            if index != 3 {
+                assert_eq!(
+                    (position.x, position.y, position.z),
+                    (rest.x, rest.y, rest.z)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn undisplaced_mesh_stays_at_rest() {
+        let mut spring = SpringMesh::new(&cube::get_cube_mesh(), 40.0, 4.0);
+        spring.update(1.0 / 60.0);
+
+        let mesh = spring.mesh();
+        assert_eq!(mesh.mat.len(), 12);
+        assert!((mesh.volume() - 1.0).abs() < 1e-5);
+    }
+}
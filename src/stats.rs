@@ -0,0 +1,375 @@
+//! Contains the statistics that are collected while rendering a frame,
+//! used to find out which stage of the pipeline is the slowest
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long each stage of rendering a single frame took
+#[derive(Debug, Clone, Copy)]
+pub struct RenderStats {
+    /// `transform` - Moving and projecting the vertices
+    pub transform: Duration,
+    /// `clip` - Clipping triangles against the view
+    pub clip: Duration,
+    /// `cull` - Removing triangles that can't be seen
+    pub cull: Duration,
+    /// `rasterize` - Drawing the triangles onto the canvas
+    pub rasterize: Duration,
+    /// `present` - Showing the finished canvas on the screen
+    pub present: Duration,
+    /// `total` - The whole frame, from clearing the canvas to presenting it
+    pub total: Duration,
+    /// `triangles` - The amount of triangles that were drawn
+    pub triangles: usize,
+    /// `triangle_cap` - The most triangles that were allowed to be drawn, if there was a cap
+    pub triangle_cap: Option<usize>,
+}
+
+impl RenderStats {
+    /// Get the time spent in the measured stages, which should be close to `total`. A large
+    /// difference means time is being spent somewhere that isn't instrumented.
+    ///
+    /// # Returns
+    /// * `Duration` - The sum of all of the stage timings
+    pub fn stage_total(&self) -> Duration {
+        self.transform + self.clip + self.cull + self.rasterize + self.present
+    }
+}
+
+impl Default for RenderStats {
+    /// Create empty stats with every timing set to zero
+    ///
+    /// # Returns
+    /// * `RenderStats` - The default stats
+    fn default() -> Self {
+        Self {
+            transform: Duration::ZERO,
+            clip: Duration::ZERO,
+            cull: Duration::ZERO,
+            rasterize: Duration::ZERO,
+            present: Duration::ZERO,
+            total: Duration::ZERO,
+            triangles: 0,
+            triangle_cap: None,
+        }
+    }
+}
+
+/// Somewhere to read the time from. The stages are timed through this rather than by calling
+/// `Instant::now` directly, so the timings can be checked without having to wait.
+pub trait Clock {
+    /// Get the current time
+    ///
+    /// # Returns
+    /// * `Instant` - The current time
+    fn now(&self) -> Instant;
+}
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    /// Get the current time
+    ///
+    /// # Returns
+    /// * `Instant` - The current time, from `Instant::now`
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Times the stages of a frame one after another. Each stage runs from the end of the one
+/// before it, so the stages of a frame always add up to its total.
+pub struct StageTimer<C: Clock> {
+    /// `clock` - Where the time is read from
+    clock: C,
+    /// `frame_start` - When the current frame started
+    frame_start: Instant,
+    /// `stage_start` - When the current stage started
+    stage_start: Instant,
+}
+
+impl<C: Clock> StageTimer<C> {
+    /// Create a new stage timer, with a frame starting now
+    ///
+    /// # Arguments
+    /// * `clock` - Where the time is read from
+    ///
+    /// # Returns
+    /// * `StageTimer<C>` - The new stage timer
+    pub fn new(clock: C) -> Self {
+        let now = clock.now();
+
+        Self {
+            clock,
+            frame_start: now,
+            stage_start: now,
+        }
+    }
+
+    /// Start timing a new frame, along with its first stage
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn start_frame(&mut self) -> () {
+        self.frame_start = self.clock.now();
+        self.stage_start = self.frame_start;
+    }
+
+    /// Finish the current stage and start the next one
+    ///
+    /// # Returns
+    /// * `Duration` - How long the stage that just finished took
+    pub fn lap(&mut self) -> Duration {
+        let now = self.clock.now();
+        let stage = now.saturating_duration_since(self.stage_start);
+
+        self.stage_start = now;
+        stage
+    }
+
+    /// Get how long the current frame has taken so far
+    ///
+    /// # Returns
+    /// * `Duration` - The time since `start_frame`
+    pub fn total(&self) -> Duration {
+        self.clock.now().saturating_duration_since(self.frame_start)
+    }
+}
+
+/// Describe how long each stage of a frame took, e.g. for printing while profiling
+///
+/// # Arguments
+/// * `stats` - The stats of the frame
+///
+/// # Returns
+/// * `String` - Each stage and the total, in milliseconds
+pub fn stage_breakdown(stats: &RenderStats) -> String {
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+    format!(
+        "transform {:.3} ms | cull {:.3} ms | clip {:.3} ms | rasterize {:.3} ms | present {:.3} ms | total {:.3} ms",
+        ms(stats.transform),
+        ms(stats.cull),
+        ms(stats.clip),
+        ms(stats.rasterize),
+        ms(stats.present),
+        ms(stats.total)
+    )
+}
+
+/// Writes the stats of each frame as a row of CSV, so performance can be compared between runs
+pub struct StatsLog<W: Write> {
+    /// `writer` - Where the rows are written to
+    writer: W,
+    /// `rows` - How many rows have been written, not counting the header
+    rows: u32,
+}
+
+impl<W: Write> StatsLog<W> {
+    /// How many rows are written between each flush, so a run that crashes still leaves most
+    /// of its rows behind without flushing every frame
+    const FLUSH_INTERVAL: u32 = 60;
+
+    /// Create a new stats log, writing the header straight away
+    ///
+    /// # Arguments
+    /// * `writer` - Where the rows should be written to
+    ///
+    /// # Returns
+    /// * `io::Result<StatsLog<W>>` - The new log, or the error from writing the header
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(
+            writer,
+            "frame,total_ms,transform_ms,clip_ms,cull_ms,rasterize_ms,present_ms,triangles"
+        )?;
+
+        Ok(Self { writer, rows: 0 })
+    }
+
+    /// Write the stats of a frame
+    ///
+    /// # Arguments
+    /// * `frame` - The index of the frame
+    /// * `stats` - The stats of the frame
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Nothing, or the error from writing the row
+    pub fn record(&mut self, frame: u32, stats: &RenderStats) -> io::Result<()> {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+        writeln!(
+            self.writer,
+            "{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{}",
+            frame,
+            ms(stats.total),
+            ms(stats.transform),
+            ms(stats.clip),
+            ms(stats.cull),
+            ms(stats.rasterize),
+            ms(stats.present),
+            stats.triangles
+        )?;
+        self.rows += 1;
+
+        if self.rows.is_multiple_of(Self::FLUSH_INTERVAL) {
+            self.writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write out any rows that are still buffered
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Nothing, or the error from flushing
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl StatsLog<BufWriter<File>> {
+    /// Create a stats log that writes to a file, replacing the file if it already exists
+    ///
+    /// # Arguments
+    /// * `path` - The path of the CSV file
+    ///
+    /// # Returns
+    /// * `io::Result<StatsLog<BufWriter<File>>>` - The new log, or why the file couldn't be
+    ///   written
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A clock that only moves when it is told to, shared between its clones
+    #[derive(Clone)]
+    struct FakeClock {
+        now: Rc<Cell<Instant>>,
+    }
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self {
+                now: Rc::new(Cell::new(Instant::now())),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.now.get()
+        }
+    }
+
+    #[test]
+    fn stage_timers_add_up_to_the_total() {
+        let clock = FakeClock::new();
+        let mut timer = StageTimer::new(clock.clone());
+        let mut stats = RenderStats::default();
+
+        timer.start_frame();
+        clock.advance(Duration::from_micros(1500));
+        stats.transform = timer.lap();
+        clock.advance(Duration::from_micros(300));
+        stats.cull = timer.lap();
+        clock.advance(Duration::from_micros(700));
+        stats.clip = timer.lap();
+        clock.advance(Duration::from_millis(9));
+        stats.rasterize = timer.lap();
+        clock.advance(Duration::from_millis(2));
+        stats.present = timer.lap();
+        stats.total = timer.total();
+
+        assert_eq!(stats.rasterize, Duration::from_millis(9));
+        assert_eq!(stats.total, Duration::from_micros(13500));
+        assert!(stats.stage_total().abs_diff(stats.total) < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn starting_a_frame_resets_the_timers() {
+        let clock = FakeClock::new();
+        let mut timer = StageTimer::new(clock.clone());
+
+        clock.advance(Duration::from_secs(1));
+        timer.start_frame();
+        clock.advance(Duration::from_millis(4));
+
+        assert_eq!(timer.lap(), Duration::from_millis(4));
+        assert_eq!(timer.total(), Duration::from_millis(4));
+    }
+
+    /// A writer that keeps what is written and counts how often it is flushed
+    #[derive(Default)]
+    struct CountingWriter {
+        written: Vec<u8>,
+        flushes: usize,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stats_log_writes_a_header_and_a_row_per_frame() {
+        let mut log = StatsLog::new(Vec::new()).unwrap();
+
+        for frame in 0..3 {
+            let stats = RenderStats {
+                total: Duration::from_micros(16_500),
+                rasterize: Duration::from_millis(10),
+                triangles: 12 + frame as usize,
+                ..RenderStats::default()
+            };
+            log.record(frame, &stats).unwrap();
+        }
+
+        let csv = String::from_utf8(log.writer).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "frame,total_ms,transform_ms,clip_ms,cull_ms,rasterize_ms,present_ms,triangles"
+        );
+        assert_eq!(lines[1], "0,16.500,0.000,0.000,0.000,10.000,0.000,12");
+        assert_eq!(lines[3], "2,16.500,0.000,0.000,0.000,10.000,0.000,14");
+        for line in &lines {
+            assert_eq!(line.split(',').count(), 8);
+        }
+    }
+
+    #[test]
+    fn stats_log_flushes_every_so_often() {
+        let mut log = StatsLog::new(CountingWriter::default()).unwrap();
+
+        for frame in 0..StatsLog::<CountingWriter>::FLUSH_INTERVAL * 2 + 1 {
+            log.record(frame, &RenderStats::default()).unwrap();
+        }
+        assert_eq!(log.writer.flushes, 2);
+
+        log.flush().unwrap();
+        assert_eq!(log.writer.flushes, 3);
+        assert_eq!(log.rows, 121);
+    }
+}
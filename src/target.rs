@@ -0,0 +1,225 @@
+//! Contains the render target abstraction - anything the engine can draw
+//! onto, whether that is the SDL canvas or an off-screen buffer
+
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+use sdl2::render::BlendMode;
+
+/// Something that lines and points can be drawn onto. Everything in the engine draws through
+/// this, so the same scene can go to the window or to an off-screen buffer.
+pub trait RenderTarget {
+    /// Set the colour that the following draw calls use
+    ///
+    /// # Arguments
+    /// * `color` - The colour to draw with
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn set_draw_color(&mut self, color: Color) -> ();
+
+    /// Fill the whole target with the draw colour
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn clear(&mut self) -> ();
+
+    /// Draw a line between two points
+    ///
+    /// # Arguments
+    /// * `start` - The start of the line
+    /// * `end` - The end of the line
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_line(&mut self, start: Point, end: Point) -> ();
+
+    /// Draw a single point
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_point(&mut self, point: Point) -> ();
+
+    /// Draw a single point that only partly covers its pixel, blending the draw colour with
+    /// what is already there
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    /// * `coverage` - How much of the pixel is covered, from 0 to 1
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn blend_point(&mut self, point: Point, coverage: f32) -> ();
+}
+
+impl RenderTarget for sdl2::render::Canvas<sdl2::video::Window> {
+    /// Set the colour that the following draw calls use
+    ///
+    /// # Arguments
+    /// * `color` - The colour to draw with
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn set_draw_color(&mut self, color: Color) -> () {
+        sdl2::render::Canvas::set_draw_color(self, color);
+    }
+
+    /// Fill the whole canvas with the draw colour
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn clear(&mut self) -> () {
+        sdl2::render::Canvas::clear(self);
+    }
+
+    /// Draw a line between two points
+    ///
+    /// # Arguments
+    /// * `start` - The start of the line
+    /// * `end` - The end of the line
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_line(&mut self, start: Point, end: Point) -> () {
+        sdl2::render::Canvas::draw_line(self, start, end);
+    }
+
+    /// Draw a single point
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_point(&mut self, point: Point) -> () {
+        sdl2::render::Canvas::draw_point(self, point);
+    }
+
+    /// Draw a single point that only partly covers its pixel, letting SDL blend it by alpha
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    /// * `coverage` - How much of the pixel is covered, from 0 to 1
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn blend_point(&mut self, point: Point, coverage: f32) -> () {
+        let color = self.draw_color();
+        let blend_mode = self.blend_mode();
+
+        self.set_blend_mode(BlendMode::Blend);
+        sdl2::render::Canvas::set_draw_color(
+            self,
+            Color::RGBA(
+                color.r,
+                color.g,
+                color.b,
+                (coverage.clamp(0.0, 1.0) * 255.0) as u8,
+            ),
+        );
+        sdl2::render::Canvas::draw_point(self, point);
+
+        sdl2::render::Canvas::set_draw_color(self, color);
+        self.set_blend_mode(blend_mode);
+    }
+}
+
+/// A target that remembers every draw call rather than drawing anything, so tests can check
+/// what would have been drawn
+#[cfg(test)]
+pub struct RecordingTarget {
+    /// `color` - The current draw colour
+    pub color: Color,
+    /// `clears` - How many times the target was cleared
+    pub clears: usize,
+    /// `lines` - The start, end and colour of every line drawn
+    pub lines: Vec<(Point, Point, Color)>,
+    /// `points` - The position and colour of every point drawn
+    pub points: Vec<(Point, Color)>,
+    /// `blended` - The position and coverage of every blended point drawn
+    pub blended: Vec<(Point, f32)>,
+}
+
+#[cfg(test)]
+impl RecordingTarget {
+    /// Create a new recording target that hasn't seen any draw calls
+    ///
+    /// # Returns
+    /// * `RecordingTarget` - The new recording target
+    pub fn new() -> Self {
+        Self {
+            color: Color::RGB(255, 255, 255),
+            clears: 0,
+            lines: Vec::new(),
+            points: Vec::new(),
+            blended: Vec::new(),
+        }
+    }
+
+    /// Get how many draw calls have been made, not counting setting the colour
+    ///
+    /// # Returns
+    /// * `usize` - The amount of clears, lines and points drawn
+    pub fn draw_calls(&self) -> usize {
+        self.clears + self.lines.len() + self.points.len() + self.blended.len()
+    }
+}
+
+#[cfg(test)]
+impl RenderTarget for RecordingTarget {
+    /// Remember the colour that the following draw calls use
+    ///
+    /// # Arguments
+    /// * `color` - The colour to draw with
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn set_draw_color(&mut self, color: Color) -> () {
+        self.color = color;
+    }
+
+    /// Count a clear
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn clear(&mut self) -> () {
+        self.clears += 1;
+    }
+
+    /// Remember a line
+    ///
+    /// # Arguments
+    /// * `start` - The start of the line
+    /// * `end` - The end of the line
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_line(&mut self, start: Point, end: Point) -> () {
+        self.lines.push((start, end, self.color));
+    }
+
+    /// Remember a point
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn draw_point(&mut self, point: Point) -> () {
+        self.points.push((point, self.color));
+    }
+
+    /// Remember a blended point
+    ///
+    /// # Arguments
+    /// * `point` - The point to draw
+    /// * `coverage` - How much of the pixel is covered, from 0 to 1
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn blend_point(&mut self, point: Point, coverage: f32) -> () {
+        self.blended.push((point, coverage));
+    }
+}
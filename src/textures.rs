@@ -0,0 +1,137 @@
+//! Contains textures - images that are wrapped onto meshes with texture
+//! coordinates - and procedural ones that are made without an image file
+
+use sdl2::pixels::Color;
+
+/// How many cells there are along each side of a checkerboard
+const CHECKERBOARD_CELLS: u32 = 8;
+
+/// An image that can be looked up with texture coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    /// `width` - The width of the texture, in pixels
+    pub width: u32,
+    /// `height` - The height of the texture, in pixels
+    pub height: u32,
+    /// `pixels` - The colour of each pixel, row by row from the top left
+    pub pixels: Vec<Color>,
+}
+
+impl Texture {
+    /// Create a new texture
+    ///
+    /// # Arguments
+    /// * `width` - The width of the texture, in pixels
+    /// * `height` - The height of the texture, in pixels
+    /// * `pixels` - The colour of each pixel, row by row from the top left
+    ///
+    /// # Returns
+    /// * `Texture` - The new texture
+    pub fn new(width: u32, height: u32, pixels: Vec<Color>) -> Self {
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Get the colour of a pixel
+    ///
+    /// # Arguments
+    /// * `x` - The column of the pixel
+    /// * `y` - The row of the pixel
+    ///
+    /// # Returns
+    /// * `Option<Color>` - The colour, or `None` if the pixel is off the texture
+    pub fn get(&self, x: u32, y: u32) -> Option<Color> {
+        if x < self.width && y < self.height {
+            self.pixels.get((y * self.width + x) as usize).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Look up the colour at a pair of texture coordinates, using the nearest pixel.
+    /// Coordinates outside 0 to 1 wrap around, so the texture repeats.
+    ///
+    /// # Arguments
+    /// * `u` - How far across the texture to look, from 0 on the left to 1 on the right
+    /// * `v` - How far down the texture to look, from 0 at the top to 1 at the bottom
+    ///
+    /// # Returns
+    /// * `Color` - The colour, or black if the texture is empty
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        if self.width == 0 || self.height == 0 {
+            return Color::BLACK;
+        }
+
+        let x = (u.rem_euclid(1.0) * self.width as f32) as u32;
+        let y = (v.rem_euclid(1.0) * self.height as f32) as u32;
+
+        self.get(x.min(self.width - 1), y.min(self.height - 1))
+            .unwrap_or(Color::BLACK)
+    }
+}
+
+/// Make a square checkerboard texture. Stretched or mirrored texture coordinates are easy to
+/// spot on it, so it is handy for checking them without an image file.
+///
+/// # Arguments
+/// * `size` - The width and height of the texture, in pixels
+/// * `color_a` - The colour of the top left cell and every other cell from it
+/// * `color_b` - The colour of the remaining cells
+///
+/// # Returns
+/// * `Texture` - The checkerboard, with 8 cells along each side (or 1 pixel cells if the
+///   texture is smaller than that)
+pub fn checkerboard(size: u32, color_a: Color, color_b: Color) -> Texture {
+    let cell = (size / CHECKERBOARD_CELLS).max(1);
+
+    let pixels = (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if (x / cell + y / cell).is_multiple_of(2) {
+                color_a
+            } else {
+                color_b
+            }
+        })
+        .collect();
+
+    Texture::new(size, size, pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkerboard_alternates_per_cell() {
+        let (a, b) = (Color::RGB(255, 0, 255), Color::RGB(0, 0, 0));
+        let texture = checkerboard(64, a, b);
+
+        assert_eq!((texture.width, texture.height), (64, 64));
+        assert_eq!(texture.pixels.len(), 64 * 64);
+        // Each cell is 8 pixels across
+        assert_eq!(texture.get(0, 0), Some(a));
+        assert_eq!(texture.get(7, 7), Some(a));
+        assert_eq!(texture.get(8, 0), Some(b));
+        assert_eq!(texture.get(0, 8), Some(b));
+        assert_eq!(texture.get(8, 8), Some(a));
+        assert_eq!(texture.get(63, 0), Some(b));
+        assert_eq!(texture.get(64, 0), None);
+    }
+
+    #[test]
+    fn small_checkerboards_have_single_pixel_cells() {
+        let (a, b) = (Color::WHITE, Color::BLACK);
+        let texture = checkerboard(4, a, b);
+
+        assert_eq!(
+            texture.pixels,
+            vec![a, b, a, b, b, a, b, a, a, b, a, b, b, a, b, a]
+        );
+        assert_eq!(texture.sample(0.3, 0.0), b);
+        assert_eq!(texture.sample(1.3, 0.0), b);
+    }
+}
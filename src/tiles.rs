@@ -0,0 +1,180 @@
+//! Contains tiled rasterization - filling triangles into the software
+//! framebuffer one square tile at a time, so the tiles can be filled on
+//! every core at once when the `rayon` feature is on
+
+use crate::depth::DepthBuffer;
+use crate::framebuffer::Framebuffer;
+use crate::raster::RasterConfig;
+use crate::Triangle;
+use sdl2::pixels::Color;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// The width and height of a tile, in pixels
+pub const TILE_SIZE: u32 = 64;
+
+/// A square piece of the screen, with its own copy of the pixels and depths under it so it
+/// can be filled without touching any other tile
+struct Tile {
+    /// `framebuffer` - The pixels under the tile
+    framebuffer: Framebuffer,
+    /// `depth_buffer` - The depths under the tile
+    depth_buffer: DepthBuffer,
+    /// `triangles` - The index of each triangle that overlaps the tile, in drawing order
+    triangles: Vec<usize>,
+}
+
+impl Tile {
+    /// Fill in every triangle that overlaps the tile, in order
+    ///
+    /// # Arguments
+    /// * `triangles` - Every triangle being drawn, in screen space, with its colour
+    /// * `config` - How the triangles are filled
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    fn fill(&mut self, triangles: &[(Triangle, Color)], config: &RasterConfig) -> () {
+        for &index in &self.triangles {
+            let (triangle, color) = &triangles[index];
+
+            triangle.fill(
+                &mut self.framebuffer,
+                *color,
+                &mut self.depth_buffer,
+                config,
+            );
+        }
+    }
+}
+
+/// Fill in triangles the same way as calling `Triangle::fill` on each of them in turn, but a
+/// tile at a time. Each triangle is only filled in the tiles it overlaps, and as a tile only
+/// ever sees its own pixels the result doesn't depend on the order the tiles are filled in.
+/// With the `rayon` feature the tiles are filled in parallel, otherwise one after another.
+///
+/// # Arguments
+/// * `framebuffer` - The framebuffer to fill the triangles in
+/// * `depth_buffer` - The depth of what has been drawn so far, the same size as the
+///   framebuffer
+/// * `triangles` - The triangles to fill, in screen space, with their colours
+/// * `config` - How the triangles are filled
+///
+/// # Returns
+/// * `()` - Nothing
+pub fn fill_tiled(
+    framebuffer: &mut Framebuffer,
+    depth_buffer: &mut DepthBuffer,
+    triangles: &[(Triangle, Color)],
+    config: &RasterConfig,
+) -> () {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let columns = width.div_ceil(TILE_SIZE);
+    let rows = height.div_ceil(TILE_SIZE);
+
+    let mut tiles: Vec<Tile> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .map(|(column, row)| {
+            let (x, y) = (column * TILE_SIZE, row * TILE_SIZE);
+            let (tile_width, tile_height) = (TILE_SIZE.min(width - x), TILE_SIZE.min(height - y));
+
+            Tile {
+                framebuffer: framebuffer.region(x as i32, y as i32, tile_width, tile_height),
+                depth_buffer: depth_buffer.region(x as i32, y as i32, tile_width, tile_height),
+                triangles: Vec::new(),
+            }
+        })
+        .collect();
+
+    // Sort the triangles into the tiles their bounds overlap, keeping them in order so
+    // overlapping triangles at the same depth come out the same as drawing them in turn
+    for (index, (triangle, _)) in triangles.iter().enumerate() {
+        let (min_x, min_y, max_x, max_y) = triangle.screen_bounds(width as f32, height as f32);
+
+        if min_x > max_x || min_y > max_y {
+            continue;
+        }
+
+        for row in min_y as u32 / TILE_SIZE..=max_y as u32 / TILE_SIZE {
+            for column in min_x as u32 / TILE_SIZE..=max_x as u32 / TILE_SIZE {
+                tiles[(row * columns + column) as usize]
+                    .triangles
+                    .push(index);
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    tiles
+        .par_iter_mut()
+        .for_each(|tile| tile.fill(triangles, config));
+
+    #[cfg(not(feature = "rayon"))]
+    tiles
+        .iter_mut()
+        .for_each(|tile| tile.fill(triangles, config));
+
+    for tile in &tiles {
+        framebuffer.copy_region(&tile.framebuffer);
+        depth_buffer.copy_region(&tile.depth_buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives;
+    use crate::Matrix;
+
+    /// Project a sphere in front of the camera, shading each triangle by which way it faces
+    fn sphere_triangles(width: u32, height: u32) -> Vec<(Triangle, Color)> {
+        let projection = Matrix::projection(90.0, height as f32 / width as f32, 0.1, 100.0);
+
+        primitives::icosphere(1.5, 2)
+            .transformed(&Matrix::translation(0.0, 0.0, 3.0))
+            .mat
+            .iter()
+            .filter(|triangle| triangle.normal().z < 0.0)
+            .map(|triangle| {
+                let normal = triangle.normal();
+                let shade = |value: f32| ((value * 0.5 + 0.5) * 255.0) as u8;
+
+                (
+                    triangle.project_to_screen(&projection, width as f32, height as f32),
+                    Color::RGB(shade(normal.x), shade(normal.y), shade(normal.z)),
+                )
+            })
+            .collect()
+    }
+
+    /// Fill the sphere tiled and one triangle at a time, and compare the pixels
+    fn assert_tiled_matches_serial(width: u32, height: u32, config: &RasterConfig) {
+        let triangles = sphere_triangles(width, height);
+
+        let mut serial = Framebuffer::new(width, height);
+        let mut serial_depth = DepthBuffer::new(width, height);
+        for (triangle, color) in &triangles {
+            triangle.fill(&mut serial, *color, &mut serial_depth, config);
+        }
+
+        let mut tiled = Framebuffer::new(width, height);
+        let mut tiled_depth = DepthBuffer::new(width, height);
+        fill_tiled(&mut tiled, &mut tiled_depth, &triangles, config);
+
+        assert!(serial
+            .pixels
+            .iter()
+            .any(|&pixel| pixel != Color::RGB(0, 0, 0)));
+        assert!(serial.pixels == tiled.pixels);
+    }
+
+    #[test]
+    fn tiled_sphere_matches_serial() {
+        assert_tiled_matches_serial(200, 150, &RasterConfig::default());
+    }
+
+    #[test]
+    fn tiled_sphere_matches_serial_when_antialiased() {
+        assert_tiled_matches_serial(200, 150, &RasterConfig { antialias: true });
+    }
+}
@@ -0,0 +1,75 @@
+//! Contains wrappers for angles so that whether an angle is in degrees or
+//! radians is part of its type rather than something to remember
+
+use std::f32::consts::PI;
+
+/// An angle in degrees, which is how angles are easiest to write by hand (e.g. a field of view)
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Degrees(pub f32);
+
+/// An angle in radians, which is what the trigonometric functions expect
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Radians(pub f32);
+
+impl Degrees {
+    /// Convert the angle to radians
+    ///
+    /// # Returns
+    /// * `Radians` - The same angle in radians
+    pub fn to_radians(self) -> Radians {
+        Radians(self.0 * PI / 180.0)
+    }
+}
+
+impl Radians {
+    /// Convert the angle to degrees
+    ///
+    /// # Returns
+    /// * `Degrees` - The same angle in degrees
+    pub fn to_degrees(self) -> Degrees {
+        Degrees(self.0 * 180.0 / PI)
+    }
+}
+
+impl From<Degrees> for Radians {
+    /// Convert degrees to radians
+    ///
+    /// # Arguments
+    /// * `degrees` - The angle in degrees
+    ///
+    /// # Returns
+    /// * `Radians` - The same angle in radians
+    fn from(degrees: Degrees) -> Self {
+        degrees.to_radians()
+    }
+}
+
+impl From<Radians> for Degrees {
+    /// Convert radians to degrees
+    ///
+    /// # Arguments
+    /// * `radians` - The angle in radians
+    ///
+    /// # Returns
+    /// * `Degrees` - The same angle in degrees
+    fn from(radians: Radians) -> Self {
+        radians.to_degrees()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_a_turn_is_pi_radians() {
+        assert!((Degrees(180.0).to_radians().0 - PI).abs() < 1e-6);
+        assert!((Radians::from(Degrees(90.0)).0 - PI / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pi_radians_is_half_a_turn() {
+        assert!((Radians(PI).to_degrees().0 - 180.0).abs() < 1e-4);
+        assert!((Degrees::from(Radians(PI / 3.0)).0 - 60.0).abs() < 1e-4);
+    }
+}
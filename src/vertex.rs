@@ -0,0 +1,199 @@
+//! Contains vertices that carry more than a position (e.g. a colour or
+//! texture coordinates), and how those attributes are blended across a
+//! triangle
+
+use crate::Vector3D;
+use sdl2::pixels::Color;
+
+/// Anything that can be blended smoothly between values, which every per-vertex attribute
+/// has to be so that it can be filled in across the face of a triangle
+pub trait Interpolate: Sized {
+    /// Blend between two values
+    ///
+    /// # Arguments
+    /// * `other` - The value to blend towards
+    /// * `t` - How far to blend, where 0 is this value and 1 is `other`
+    ///
+    /// # Returns
+    /// * `Self` - The blended value
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+
+    /// Blend between the values at the three corners of a triangle
+    ///
+    /// # Arguments
+    /// * `a` - The value at the first corner
+    /// * `b` - The value at the second corner
+    /// * `c` - The value at the third corner
+    /// * `weights` - The barycentric weight of each corner, which should add up to 1
+    ///
+    /// # Returns
+    /// * `Self` - The blended value
+    fn barycentric(a: &Self, b: &Self, c: &Self, weights: (f32, f32, f32)) -> Self {
+        let (wa, wb, wc) = weights;
+        let ab = wa + wb;
+
+        // Blend along the edge from a to b first, then from that point towards c
+        if ab == 0.0 {
+            a.lerp(c, 1.0)
+        } else {
+            a.lerp(b, wb / ab).lerp(c, wc)
+        }
+    }
+}
+
+impl Interpolate for f32 {
+    /// Blend between two numbers
+    ///
+    /// # Arguments
+    /// * `other` - The number to blend towards
+    /// * `t` - How far to blend
+    ///
+    /// # Returns
+    /// * `f32` - The blended number
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vector3D {
+    /// Blend between two vectors
+    ///
+    /// # Arguments
+    /// * `other` - The vector to blend towards
+    /// * `t` - How far to blend
+    ///
+    /// # Returns
+    /// * `Vector3D` - The blended vector
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Vector3D::lerp(self, other, t)
+    }
+}
+
+impl Interpolate for Color {
+    /// Blend between two colours, channel by channel
+    ///
+    /// # Arguments
+    /// * `other` - The colour to blend towards
+    /// * `t` - How far to blend
+    ///
+    /// # Returns
+    /// * `Color` - The blended colour
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let blend = |from: u8, to: u8| (from as f32).lerp(&(to as f32), t).round() as u8;
+
+        Color::RGBA(
+            blend(self.r, other.r),
+            blend(self.g, other.g),
+            blend(self.b, other.b),
+            blend(self.a, other.a),
+        )
+    }
+}
+
+impl<A: Interpolate, B: Interpolate> Interpolate for (A, B) {
+    /// Blend between two pairs of attributes, each one on its own
+    ///
+    /// # Arguments
+    /// * `other` - The pair to blend towards
+    /// * `t` - How far to blend
+    ///
+    /// # Returns
+    /// * `(A, B)` - The blended pair
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (self.0.lerp(&other.0, t), self.1.lerp(&other.1, t))
+    }
+}
+
+impl<A: Interpolate, B: Interpolate, C: Interpolate> Interpolate for (A, B, C) {
+    /// Blend between two sets of three attributes, each one on its own
+    ///
+    /// # Arguments
+    /// * `other` - The set to blend towards
+    /// * `t` - How far to blend
+    ///
+    /// # Returns
+    /// * `(A, B, C)` - The blended set
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (
+            self.0.lerp(&other.0, t),
+            self.1.lerp(&other.1, t),
+            self.2.lerp(&other.2, t),
+        )
+    }
+}
+
+/// A position along with whatever else should be known at that point of a mesh, e.g. a
+/// `Color` or a `(Color, f32)` pair. `Triangle` only holds positions, this is for pipelines
+/// that need more.
+#[derive(Debug, Clone, Copy)]
+pub struct Vertex<A> {
+    /// `position` - Where the vertex is
+    pub position: Vector3D,
+    /// `attribute` - The extra data carried by the vertex
+    pub attribute: A,
+}
+
+impl<A> Vertex<A> {
+    /// Create a new vertex
+    ///
+    /// # Arguments
+    /// * `position` - Where the vertex is
+    /// * `attribute` - The extra data carried by the vertex
+    ///
+    /// # Returns
+    /// * `Vertex<A>` - The new vertex
+    pub fn new(position: Vector3D, attribute: A) -> Self {
+        Self {
+            position,
+            attribute,
+        }
+    }
+}
+
+impl<A: Interpolate> Interpolate for Vertex<A> {
+    /// Blend between two vertices, blending the position and the attribute together
+    ///
+    /// # Arguments
+    /// * `other` - The vertex to blend towards
+    /// * `t` - How far to blend
+    ///
+    /// # Returns
+    /// * `Vertex<A>` - The blended vertex
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(&other.position, t),
+            attribute: self.attribute.lerp(&other.attribute, t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colour_and_number_blend_across_a_triangle() {
+        let a = Vertex::new(Vector3D::new(0.0, 0.0, 0.0), (Color::RGB(255, 0, 0), 0.0));
+        let b = Vertex::new(Vector3D::new(1.0, 0.0, 0.0), (Color::RGB(0, 255, 0), 3.0));
+        let c = Vertex::new(Vector3D::new(0.0, 1.0, 0.0), (Color::RGB(0, 0, 255), 6.0));
+
+        // Each corner on its own gives back its own attribute
+        assert_eq!(
+            Vertex::barycentric(&a, &b, &c, (0.0, 1.0, 0.0)).attribute,
+            (Color::RGB(0, 255, 0), 3.0)
+        );
+        assert_eq!(
+            Vertex::barycentric(&a, &b, &c, (0.0, 0.0, 1.0)).attribute,
+            (Color::RGB(0, 0, 255), 6.0)
+        );
+
+        let (color, value) = Vertex::barycentric(&a, &b, &c, (0.5, 0.25, 0.25)).attribute;
+        assert_eq!(color, Color::RGB(128, 64, 64));
+        assert!((value - 2.25).abs() < 1e-6);
+
+        let middle = Vertex::barycentric(&a, &b, &c, (1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0));
+        assert!((middle.position.x - 1.0 / 3.0).abs() < 1e-6);
+        assert!((middle.position.y - 1.0 / 3.0).abs() < 1e-6);
+        assert!((middle.attribute.1 - 3.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,201 @@
+//! Contains the thick wireframe renderer, where each edge is drawn thinner
+//! the further it is from the camera to give a sense of depth
+
+use crate::target::RenderTarget;
+use crate::{project_to_screen, Matrix, Mesh};
+use sdl2::rect::Point;
+
+/// How the thickness of wireframe edges changes with depth
+#[derive(Debug, Clone, Copy)]
+pub struct WireframeConfig {
+    /// `min_thickness` - The thickness of edges at `far_depth` or further, in pixels
+    pub min_thickness: f32,
+    /// `max_thickness` - The thickness of edges at `near_depth` or closer, in pixels
+    pub max_thickness: f32,
+    /// `near_depth` - The depth at which edges are drawn at their thickest
+    pub near_depth: f32,
+    /// `far_depth` - The depth at which edges are drawn at their thinnest
+    pub far_depth: f32,
+}
+
+impl WireframeConfig {
+    /// Create a new wireframe config
+    ///
+    /// # Arguments
+    /// * `min_thickness` - The thickness of the furthest edges, in pixels
+    /// * `max_thickness` - The thickness of the closest edges, in pixels
+    /// * `near_depth` - The depth at which edges are drawn at their thickest
+    /// * `far_depth` - The depth at which edges are drawn at their thinnest
+    ///
+    /// # Returns
+    /// * `WireframeConfig` - The new config
+    pub fn new(min_thickness: f32, max_thickness: f32, near_depth: f32, far_depth: f32) -> Self {
+        Self {
+            min_thickness,
+            max_thickness,
+            near_depth,
+            far_depth,
+        }
+    }
+
+    /// Get how thick an edge should be drawn at a depth, blending between the thickest and
+    /// thinnest edges
+    ///
+    /// # Arguments
+    /// * `depth` - The distance of the edge in front of the camera
+    ///
+    /// # Returns
+    /// * `f32` - The thickness, in pixels, between `min_thickness` and `max_thickness`
+    pub fn thickness(&self, depth: f32) -> f32 {
+        let t = ((depth - self.near_depth) / (self.far_depth - self.near_depth)).clamp(0.0, 1.0);
+
+        self.max_thickness + (self.min_thickness - self.max_thickness) * t
+    }
+
+    /// Draw every edge of a mesh once, with its thickness set by the average depth of its two
+    /// ends. Draws in the target's current draw colour.
+    ///
+    /// # Arguments
+    /// * `target` - The target to draw the mesh on
+    /// * `mesh` - The mesh to draw, in view space
+    /// * `projection` - The projection matrix
+    /// * `width` - The width of the display
+    /// * `height` - The height of the display
+    ///
+    /// # Returns
+    /// * `()` - Nothing
+    pub fn draw<T: RenderTarget>(
+        &self,
+        target: &mut T,
+        mesh: &Mesh,
+        projection: &Matrix,
+        width: f32,
+        height: f32,
+    ) -> () {
+        for (start, end) in mesh.unique_edges() {
+            let depth = (start.z + end.z) / 2.0;
+            let start = project_to_screen(&start, projection, width, height);
+            let end = project_to_screen(&end, projection, width, height);
+
+            draw_thick_line(
+                target,
+                (start.x, start.y),
+                (end.x, end.y),
+                self.thickness(depth),
+            );
+        }
+    }
+}
+
+impl Default for WireframeConfig {
+    /// Create a config that thins edges from 4 pixels to 1 across the depth of the cube, as
+    /// seen from the default camera
+    ///
+    /// # Returns
+    /// * `WireframeConfig` - The default config
+    fn default() -> Self {
+        Self::new(1.0, 4.0, 2.0, 5.0)
+    }
+}
+
+/// Draw a line more than one pixel thick, as a bundle of parallel lines side by side
+///
+/// # Arguments
+/// * `target` - The target to draw the line on
+/// * `start` - The start of the line, in screen coordinates
+/// * `end` - The end of the line, in screen coordinates
+/// * `thickness` - How thick the line is, in pixels (rounded, and at least 1)
+///
+/// # Returns
+/// * `()` - Nothing
+pub fn draw_thick_line<T: RenderTarget>(
+    target: &mut T,
+    start: (f32, f32),
+    end: (f32, f32),
+    thickness: f32,
+) -> () {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt();
+
+    if length == 0.0 {
+        target.draw_point(Point::new(start.0 as i32, start.1 as i32));
+        return;
+    }
+
+    // Step sideways across the line, with the bundle centred on it
+    let (across_x, across_y) = (-dy / length, dx / length);
+    let lines = thickness.round().max(1.0) as i32;
+
+    for line in 0..lines {
+        let offset = line as f32 - (lines - 1) as f32 / 2.0;
+        let (offset_x, offset_y) = (across_x * offset, across_y * offset);
+
+        target.draw_line(
+            Point::new(
+                (start.0 + offset_x).round() as i32,
+                (start.1 + offset_y).round() as i32,
+            ),
+            Point::new(
+                (end.0 + offset_x).round() as i32,
+                (end.1 + offset_y).round() as i32,
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::cube;
+    use crate::target::RecordingTarget;
+
+    #[test]
+    fn thickness_runs_from_near_to_far() {
+        let config = WireframeConfig::new(1.0, 4.0, 2.0, 5.0);
+
+        assert_eq!(config.thickness(2.0), 4.0);
+        assert_eq!(config.thickness(3.5), 2.5);
+        assert_eq!(config.thickness(5.0), 1.0);
+        // Beyond either end the thickness is clamped
+        assert_eq!(config.thickness(0.5), 4.0);
+        assert_eq!(config.thickness(50.0), 1.0);
+    }
+
+    #[test]
+    fn thick_line_is_a_bundle_of_lines() {
+        let mut target = RecordingTarget::new();
+        draw_thick_line(&mut target, (10.0, 10.0), (50.0, 10.0), 3.0);
+
+        let rows: Vec<(i32, i32)> = target
+            .lines
+            .iter()
+            .map(|(start, end, _)| (start.y(), end.y()))
+            .collect();
+        assert_eq!(rows, vec![(9, 9), (10, 10), (11, 11)]);
+
+        let mut target = RecordingTarget::new();
+        draw_thick_line(&mut target, (10.0, 10.0), (10.0, 10.0), 3.0);
+        assert_eq!((target.lines.len(), target.points.len()), (0, 1));
+    }
+
+    #[test]
+    fn nearer_cube_edges_are_drawn_thicker() {
+        let mut target = RecordingTarget::new();
+        let projection = Matrix::projection(90.0, 150.0 / 200.0, 0.1, 100.0);
+
+        WireframeConfig::default().draw(
+            &mut target,
+            &cube::get_cube_mesh().transformed(&Camera::default().view_matrix()),
+            &projection,
+            200.0,
+            150.0,
+        );
+
+        // The 5 edges of the near face are 3 pixels thick, the 5 of the far face 2, and the 8
+        // running between them 3 (2.5 rounded up). The edge from the origin runs straight away
+        // from the camera, so it is only a point.
+        assert_eq!(target.lines.len(), 5 * 3 + 5 * 2 + 7 * 3);
+        assert_eq!(target.points.len(), 1);
+    }
+}